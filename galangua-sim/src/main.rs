@@ -0,0 +1,224 @@
+mod tally;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use galangua_common::app::bot::{run_bot, BotEnv, HeuristicBot};
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::app::game_config::{GameConfig, DIFFICULTIES, MAX_START_SHIPS, MIN_START_SHIPS};
+use galangua_common::app::score_holder::{PlayRecord, ScoreHolder};
+use galangua_common::framework::headless::{NullSystem, NullTimer};
+use galangua_common::framework::input_log::{InputLog, InputReplayer};
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::{AppTrait, SystemTrait};
+use galangua_common::util::pad::PadBit;
+use galangua_ecs::app::{BotGame as EcsBotGame, GalanguaEcsApp};
+use galangua_oo::app::{BotGame as OoBotGame, GalanguaApp};
+
+use crate::tally::Tally;
+
+const APP_NAME: &str = "galangua-sim";
+const DEFAULT_MAX_FRAMES: u32 = 60 * 60 * 60;  // An hour in game.
+
+// App which can be run by an input script.
+trait ScriptApp: AppTrait<NullRenderer> {
+    fn observe(&self) -> Option<Observation>;
+    fn score_holder(&self) -> Option<ScoreHolder>;
+}
+
+impl<S: SystemTrait> ScriptApp for GalanguaApp<NullTimer, S> {
+    fn observe(&self) -> Option<Observation> { self.observe() }
+    fn score_holder(&self) -> Option<ScoreHolder> { self.score_holder() }
+}
+
+impl<S: SystemTrait> ScriptApp for GalanguaEcsApp<NullTimer, S> {
+    fn observe(&self) -> Option<Observation> { self.observe() }
+    fn score_holder(&self) -> Option<ScoreHolder> { self.score_holder() }
+}
+
+// Game which can be played by the bot.
+trait RecordedEnv: BotEnv {
+    fn record(&self) -> PlayRecord;
+}
+
+impl<S: SystemTrait> RecordedEnv for OoBotGame<S> {
+    fn record(&self) -> PlayRecord { self.score_holder().record }
+}
+
+impl<S: SystemTrait> RecordedEnv for EcsBotGame<S> {
+    fn record(&self) -> PlayRecord { self.score_holder().record }
+}
+
+// Tallies every frame stepped by the bot.
+struct TallyEnv<E> {
+    env: E,
+    observation: Observation,
+    tally: Tally,
+}
+
+impl<E: RecordedEnv> TallyEnv<E> {
+    fn new(env: E) -> Self {
+        let observation = env.observe();
+        Self {
+            env,
+            observation,
+            tally: Tally::default(),
+        }
+    }
+}
+
+impl<E: RecordedEnv> BotEnv for TallyEnv<E> {
+    fn observe(&self) -> Observation {
+        self.observation.clone()
+    }
+
+    fn step(&mut self, bits: PadBit) -> bool {
+        let result = self.env.step(bits);
+        self.observation = self.env.observe();
+        self.tally.update(&self.observation, self.env.record());
+        result
+    }
+}
+
+fn run_bot_game<E: RecordedEnv>(env: E, seed: u64, max_frames: u32) -> String {
+    let mut env = TallyEnv::new(env);
+    let mut bot = HeuristicBot::default();
+    let run = run_bot(&mut env, &mut bot, max_frames);
+    env.tally.to_json(seed, run.game_over).to_string()
+}
+
+// Runs the whole app from the title, until the first game is over.
+fn run_script_game<A: ScriptApp>(app: A, log: InputLog, max_frames: u32) -> String {
+    let seed = log.seed;
+    let mut replayer = InputReplayer::new(app, log);
+    let mut tally = Tally::default();
+    let mut started = false;
+    let mut game_over = false;
+    for _ in 0..max_frames {
+        if !replayer.update() {
+            break;
+        }
+        let app = replayer.app();
+        match (app.observe(), app.score_holder()) {
+            (Some(observation), Some(score_holder)) => {
+                tally.update(&observation, score_holder.record);
+                started = true;
+            }
+            _ => {
+                if started {
+                    game_over = true;
+                    break;
+                }
+                if replayer.is_finished() {
+                    break;  // Script ends without starting a game.
+                }
+            }
+        }
+    }
+    tally.to_json(seed, game_over).to_string()
+}
+
+fn create_system(config: &GameConfig) -> Result<NullSystem, String> {
+    let mut system = NullSystem::default();
+    config.save(&mut system).map_err(|e| e.to_string())?;
+    Ok(system)
+}
+
+fn parse_arg<T: std::str::FromStr>(matches: &clap::ArgMatches, name: &str, default: T) -> Result<T, String> {
+    if let Some(value) = matches.value_of(name) {
+        String::from(value).parse().map_err(|_| format!("Illegal {}: {}", name, value))
+    } else {
+        Ok(default)
+    }
+}
+
+pub fn main() -> Result<(), String> {
+    let matches = clap::App::new(APP_NAME)
+        .about("Runs games without the window and the sound, and prints a JSON summary of each game per line.
+Games are played by the bot, or by input scripts recorded with `--record`.")
+        .arg(clap::Arg::with_name("oo")
+             .help("Run OO version")
+             .long("oo"))
+        .arg(clap::Arg::with_name("games")
+             .help("Specify the number of games played by the bot (default: 1)")
+             .short("n")
+             .long("games")
+             .takes_value(true))
+        .arg(clap::Arg::with_name("seed")
+             .help("Specify random seed of the first game, incremented for each game (default: current time)")
+             .long("seed")
+             .takes_value(true))
+        .arg(clap::Arg::with_name("script")
+             .help("Play input scripts instead of the bot, one game for each")
+             .long("script")
+             .takes_value(true)
+             .multiple(true)
+             .value_name("FILE")
+             .conflicts_with_all(&["games", "seed"]))
+        .arg(clap::Arg::with_name("max-frames")
+             .help("Specify frames to give up a game (default: 216000)")
+             .long("max-frames")
+             .takes_value(true))
+        .arg(clap::Arg::with_name("ships")
+             .help("Specify starting ships (default: 3)")
+             .long("ships")
+             .takes_value(true))
+        .arg(clap::Arg::with_name("difficulty")
+             .help("Specify difficulty: easy, normal, hard or adaptive (default: normal)")
+             .long("difficulty")
+             .takes_value(true))
+        .get_matches();
+
+    let max_frames = parse_arg(&matches, "max-frames", DEFAULT_MAX_FRAMES)?;
+    let mut config = GameConfig::default();
+    config.start_ships = parse_arg(&matches, "ships", config.start_ships)?;
+    if !(MIN_START_SHIPS..=MAX_START_SHIPS).contains(&config.start_ships) {
+        return Err(format!("Illegal ships: {}", config.start_ships));
+    }
+    if let Some(difficulty) = matches.value_of("difficulty") {
+        config.difficulty = *DIFFICULTIES.iter()
+            .find(|d| d.name().eq_ignore_ascii_case(difficulty))
+            .ok_or_else(|| format!("Illegal difficulty: {}", difficulty))?;
+    }
+
+    if let Some(filenames) = matches.values_of("script") {
+        for filename in filenames {
+            let text = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+            let log = InputLog::from_json_str(&text).map_err(|e| format!("{}: {}", filename, e))?;
+            // Settings recorded in the script take priority.
+            let mut system = create_system(&config)?;
+            log.restore_saved_values(&mut system);
+            let stage_definition = match &log.stage {
+                Some(text) => StageDefinition::from_json_str(text).map_err(|e| format!("{}: {}", filename, e))?,
+                None => StageDefinition::default(),
+            };
+            let summary = if matches.is_present("oo") {
+                let mut app = GalanguaApp::new(NullTimer, system, log.seed);
+                app.set_stage_definition(stage_definition);
+                run_script_game(app, log, max_frames)
+            } else {
+                let mut app = GalanguaEcsApp::new(NullTimer, system, log.seed);
+                app.set_stage_definition(stage_definition);
+                run_script_game(app, log, max_frames)
+            };
+            println!("{}", summary);
+        }
+    } else {
+        let games: u64 = parse_arg(&matches, "games", 1)?;
+        let first_seed = if matches.is_present("seed") {
+            parse_arg(&matches, "seed", 0)?
+        } else {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+        };
+        for i in 0..games {
+            let seed = first_seed.wrapping_add(i);
+            let summary = if matches.is_present("oo") {
+                run_bot_game(OoBotGame::new(seed, config, false, NullSystem::default()), seed, max_frames)
+            } else {
+                run_bot_game(EcsBotGame::new(seed, config, false, NullSystem::default()), seed, max_frames)
+            };
+            println!("{}", summary);
+        }
+    }
+    Ok(())
+}
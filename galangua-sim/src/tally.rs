@@ -0,0 +1,59 @@
+use serde_json::{json, Value};
+
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::score_holder::PlayRecord;
+
+// Summary of a game, from the observation and the record of each frame.
+#[derive(Default)]
+pub struct Tally {
+    pub frames: u32,
+    pub score: u32,
+    pub stage: u16,
+    pub record: PlayRecord,
+}
+
+impl Tally {
+    pub fn update(&mut self, observation: &Observation, record: PlayRecord) {
+        self.frames += 1;
+        self.score = observation.score;
+        self.stage = observation.stage;
+        self.record = record;
+    }
+
+    pub fn to_json(&self, seed: u64, game_over: bool) -> Value {
+        json!({
+            "seed": seed,
+            "score": self.score,
+            "stage": self.stage + 1,
+            "deaths": self.record.lost_count,
+            "captures": self.record.captured_count,
+            "recaptures": self.record.rescued_count,
+            "enemies_down": self.record.destroyed_count,
+            "shots_fired": self.record.shot_count,
+            "shots_hit": self.record.hit_count,
+            "frames": self.frames,
+            "game_over": game_over,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut tally = Tally::default();
+        tally.update(&Observation { score: 100, ..Default::default() }, PlayRecord::default());
+        let record = PlayRecord { lost_count: 2, captured_count: 1, rescued_count: 1, ..Default::default() };
+        tally.update(&Observation { score: 200, stage: 1, ..Default::default() }, record);
+
+        let json = tally.to_json(5, true);
+        assert_eq!(200, json["score"]);
+        assert_eq!(2, json["stage"]);
+        assert_eq!(2, json["deaths"]);
+        assert_eq!(1, json["captures"]);
+        assert_eq!(1, json["recaptures"]);
+        assert_eq!(2, json["frames"]);
+    }
+}
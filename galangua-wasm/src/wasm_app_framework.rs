@@ -42,7 +42,8 @@ impl WasmAppFramework {
             },
             is_touch_device,
         );
-        let mut app = GalanguaApp::new(timer, system);
+        let seed = (js_sys::Math::random() * (u32::MAX as f64)) as u64;
+        let mut app = GalanguaApp::new(timer, system, seed);
 
         app.init(&mut renderer);
 
@@ -72,6 +73,10 @@ impl WasmAppFramework {
         }
     }
 
+    pub fn on_focus(&mut self, focused: bool) {
+        self.app.on_focus(focused);
+    }
+
     pub fn update(&mut self) {
         self.app.update();
     }
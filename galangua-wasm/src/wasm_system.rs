@@ -1,10 +1,18 @@
+use serde_json::Value;
 use wasm_bindgen::prelude::*;
 
+use galangua_common::app::sound_patch::find_patch;
+use galangua_common::framework::key_value_store::KeyValueStore;
 use galangua_common::framework::SystemTrait;
+use galangua_common::util::synth::render;
 
 #[wasm_bindgen]
 extern "C" {
-    fn play_se(channel: u32, filename: &str);
+    fn load_se(filename: &str);
+    fn play_se(channel: u32, filename: &str, looping: bool);
+    fn stop_se(channel: u32, fade_ms: u32);
+    fn stop_all_se();
+    fn set_se_volume(channel: u32, volume: f32);
 }
 
 pub struct WasmSystem<
@@ -13,6 +21,7 @@ pub struct WasmSystem<
 > {
     get_item: F,
     set_item: G,
+    pending: KeyValueStore,  // Values set, written to the storage out of transactions.
     is_touch_device: bool,
 }
 
@@ -24,28 +33,82 @@ impl<
         WasmSystem {
             get_item,
             set_item,
+            pending: KeyValueStore::default(),
             is_touch_device,
         }
     }
+
+    fn flush(&mut self) {
+        for key in self.pending.take_dirty_keys() {
+            if let Some(value) = self.pending.get(&key) {
+                (self.set_item)(&key, JsValue::from(value.to_string()));
+            }
+        }
+    }
 }
 
 impl<
     F: Fn(&str) -> Option<JsValue>,
     G: Fn(&str, JsValue)
 > SystemTrait for WasmSystem<F, G> {
-    fn get_u32(&self, key: &str) -> Option<u32> {
+    // Values are stored as JSON text, a text which is not JSON is taken as a plain string.
+    fn get_value(&self, key: &str) -> Option<Value> {
+        if let Some(value) = self.pending.get(key) {
+            return Some(value.clone());
+        }
         (self.get_item)(key)
             .map(|value| value.as_string()).flatten()
-            .map(|string| string.parse().ok()).flatten()
+            .map(|string| serde_json::from_str(&string).unwrap_or(Value::String(string)))
+    }
+
+    fn set_value(&mut self, key: &str, value: Value) {
+        self.pending.set(key, value);
+        self.flush();
     }
 
-    fn set_u32(&mut self, key: &str, value: u32) {
-        (self.set_item)(key, JsValue::from(value));
+    fn begin_transaction(&mut self) {
+        self.pending.begin_transaction();
+    }
+
+    fn commit_transaction(&mut self) {
+        self.pending.commit_transaction();
+        self.flush();
     }
 
     fn is_touch_device(&self) -> bool { self.is_touch_device }
 
+    fn load_sounds(&mut self, filenames: &[&str]) {
+        for filename in filenames {
+            load_se(filename);
+        }
+    }
+
     fn play_se(&mut self, channel: u32, filename: &str) {
-        play_se(channel, filename);
+        play_se(channel, filename, false);
     }
+
+    fn play_loop(&mut self, channel: u32, filename: &str) {
+        play_se(channel, filename, true);
+    }
+
+    fn stop_se(&mut self, channel: u32, fade_ms: u32) {
+        stop_se(channel, fade_ms);
+    }
+
+    fn stop_all_se(&mut self) {
+        stop_all_se();
+    }
+
+    fn set_volume(&mut self, channel: u32, volume: f32) {
+        set_se_volume(channel, volume);
+    }
+}
+
+// Substitute for a missing audio file, called from JavaScript: samples in [-1, 1].
+#[wasm_bindgen]
+pub fn synthesize_se(filename: &str, sample_rate: u32) -> Option<Vec<f32>> {
+    let patch = find_patch(filename)?;
+    Some(render(patch, sample_rate).iter()
+        .map(|&sample| sample as f32 / 32768.0)
+        .collect())
 }
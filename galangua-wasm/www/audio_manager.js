@@ -1,3 +1,5 @@
+import {synthesize_se} from 'galangua-wasm'
+
 class AudioManager {
   constructor(channelCount) {
     this.audios = {}
@@ -10,6 +12,12 @@ class AudioManager {
     this.context = new audioContext()
 
     this.channels = new Array(channelCount)
+    // Each channel has its own gain for the volume, and each sound has a fader.
+    this.gains = [...Array(channelCount)].map(() => {
+      const gain = this.context.createGain()
+      gain.connect(this.context.destination)
+      return gain
+    })
   }
 
   toggleEnabled() {
@@ -18,37 +26,56 @@ class AudioManager {
       this.stopAll()
   }
 
-  playSe(channel, filename) {
+  playSe(channel, filename, looping = false) {
     if (!this.enabled)
       return
 
     if (filename in this.audios) {
      if (channel < this.channels.length) {
-        if (this.channels[channel] != null) {
-          this.channels[channel].stop()
-        }
+        this.stop(channel, 0)
 
+        const fader = this.context.createGain()
+        fader.connect(this.gains[channel])
         const source = this.context.createBufferSource()
-        source.connect(this.context.destination)
-        this.channels[channel] = source
+        source.connect(fader)
+        this.channels[channel] = {source, fader}
 
         source.buffer = this.audios[filename]
+        source.loop = looping
         source.start(0)
       }
     } else if (!(filename in this.audioLoadings)) {
       this.loadAudio(filename)
-        .then(() => this.playSe(channel, filename))
+        .then(() => this.playSe(channel, filename, looping))
         .catch(err => console.error(`Audio eror: ${err}`))
     }
   }
 
+  stop(channel, fadeMs) {
+    const playing = this.channels[channel]
+    if (playing == null)
+      return
+
+    const {source, fader} = playing
+    if (fadeMs > 0) {
+      const now = this.context.currentTime
+      fader.gain.setValueAtTime(fader.gain.value, now)
+      fader.gain.linearRampToValueAtTime(0, now + fadeMs / 1000)
+      source.stop(now + fadeMs / 1000)
+    } else {
+      source.stop()
+    }
+    this.channels[channel] = null
+  }
+
   stopAll() {
-    for (let ch = 0; ch < this.channels.length; ++ch) {
-      const source = this.channels[ch]
-      if (source != null) {
-        source.stop()
-        this.channels[ch] = null
-      }
+    for (let ch = 0; ch < this.channels.length; ++ch)
+      this.stop(ch, 0)
+  }
+
+  setVolume(channel, volume) {
+    if (channel < this.channels.length) {
+      this.gains[channel].gain.value = volume
     }
   }
 
@@ -58,16 +85,33 @@ class AudioManager {
     }))
   }
 
+  // Loads each file only once, also when requested again while loading.
+  // Synthesizes the sound when the file is missing.
   loadAudio(filename) {
-    return new Promise((resolve, reject) => {
-      this.audioLoadings[filename] = true
+    if (filename in this.audioLoadings)
+      return this.audioLoadings[filename]
+
+    const promise = this.loadAudioFile(filename)
+      .catch(err => {
+        if (!this.synthesize(filename))
+          throw err
+      })
+    this.audioLoadings[filename] = promise
+    return promise
+  }
 
+  loadAudioFile(filename) {
+    return new Promise((resolve, reject) => {
       const path = `${filename}.mp3`
       const request = new XMLHttpRequest()
       request.open('GET', path, true)
       request.responseType = 'arraybuffer'
 
       request.onload = () => {
+        if (request.status !== 200) {
+          reject(`${path}: ${request.status}`)
+          return
+        }
         this.context.decodeAudioData(
           request.response,
           (buffer) => {
@@ -85,6 +129,16 @@ class AudioManager {
       request.send()
     })
   }
+
+  synthesize(filename) {
+    const samples = synthesize_se(filename, this.context.sampleRate)
+    if (samples == null || samples.length === 0)
+      return false
+    const buffer = this.context.createBuffer(1, samples.length, this.context.sampleRate)
+    buffer.copyToChannel(samples, 0)
+    this.audios[filename] = buffer
+    return true
+  }
 }
 
 export const audioManager = new AudioManager()
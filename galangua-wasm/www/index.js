@@ -1,7 +1,7 @@
 import {WasmAppFramework, WasmRenderer} from 'galangua-wasm'
 import {audioManager} from './audio_manager'
 
-const CHANNEL_COUNT = 3
+const CHANNEL_COUNT = 5
 
 const AUDIO_ASSETS = [
   'assets/audio/se_get_1',
@@ -16,8 +16,25 @@ const ICON_SOUND_OFF = 'assets/imgs/sound_off.svg'
 
 const CANVAS_ID = 'mycanvas'
 
-window.play_se = function play_se(channel, filename) {
-  audioManager.playSe(channel, filename)
+window.load_se = function load_se(filename) {
+  audioManager.loadAudio(filename)
+    .catch(err => console.error(`Audio error: ${err}`))
+}
+
+window.play_se = function play_se(channel, filename, looping) {
+  audioManager.playSe(channel, filename, looping)
+}
+
+window.stop_se = function stop_se(channel, fadeMs) {
+  audioManager.stop(channel, fadeMs)
+}
+
+window.stop_all_se = function stop_all_se() {
+  audioManager.stopAll()
+}
+
+window.set_se_volume = function set_se_volume(channel, volume) {
+  audioManager.setVolume(channel, volume)
 }
 
 function isTouchDevice() {
@@ -166,6 +183,8 @@ fitCanvas()
 disableBounce()
 setupResizeListener()
 
+audioManager.createContext(CHANNEL_COUNT)  // Before the app loads sounds.
+
 const renderer = WasmRenderer.new(CANVAS_ID)
 const framework = WasmAppFramework.new(
   renderer, isTouchDevice(),
@@ -185,6 +204,12 @@ document.addEventListener('keydown', (event) => {
 document.addEventListener('keyup', (event) => {
   framework.on_key(event.code, false)
 })
+document.addEventListener('visibilitychange', (_) => {
+  framework.on_focus(document.visibilityState === 'visible')
+})
+window.addEventListener('blur', (_) => {
+  framework.on_focus(false)
+})
 
 const loop = (function() {
   const target_fps = 60
@@ -212,7 +237,6 @@ const loop = (function() {
 })()
 
 const cover = createCoverScreen('Loading...')
-audioManager.createContext(CHANNEL_COUNT)
 audioManager.loadAllAudios(AUDIO_ASSETS)
   .then(() => {
     document.body.removeChild(cover)
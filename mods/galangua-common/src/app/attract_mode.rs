@@ -0,0 +1,85 @@
+use crate::app::consts::*;
+use crate::app::bot::{Bot, HeuristicBot};
+use crate::app::game::observation::Observation;
+use crate::framework::RendererTrait;
+use crate::util::pad::Pad;
+
+// Title screen shows the ranking and then the demo play, and goes back to the title.
+const TITLE_FRAMES: u32 = 10 * 60;
+const RANKING_FRAMES: u32 = 6 * 60;
+pub const DEMO_FRAMES: u32 = 60 * 60;
+
+// Demo play always runs the same game, for the capture to happen.
+pub const DEMO_SEED: u64 = 1981;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttractPhase {
+    Title,
+    Ranking,
+    Demo,
+}
+
+pub fn attract_phase(frame_count: u32) -> AttractPhase {
+    match frame_count {
+        n if n < TITLE_FRAMES => AttractPhase::Title,
+        n if n < TITLE_FRAMES + RANKING_FRAMES => AttractPhase::Ranking,
+        _ => AttractPhase::Demo,
+    }
+}
+
+// Title frame count to show the ranking from.
+pub fn ranking_start_frame() -> u32 {
+    TITLE_FRAMES
+}
+
+// Game controlled by `HeuristicBot` instead of the player.
+pub struct DemoPlay {
+    bot: HeuristicBot,
+    pad: Pad,
+    frame_count: u32,
+}
+
+impl Default for DemoPlay {
+    fn default() -> Self {
+        Self {
+            bot: HeuristicBot::for_demo(),
+            pad: Pad::default(),
+            frame_count: 0,
+        }
+    }
+}
+
+impl DemoPlay {
+    // Returns the input for the next frame, decided from the current game state.
+    pub fn update(&mut self, observation: &Observation) -> &Pad {
+        self.frame_count = self.frame_count.saturating_add(1);
+        self.pad.set_bits(self.bot.decide(observation));
+        self.pad.update();
+        &self.pad
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.frame_count >= DEMO_FRAMES
+    }
+
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R) {
+        if self.frame_count & 32 == 0 {
+            renderer.set_texture_color_mod("font", 255, 255, 255);
+            let msg = "DEMO PLAY";
+            renderer.draw_str("font", (WIDTH / 8 - msg.len() as i32) / 2 * 8, 20 * 8, msg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attract_phase() {
+        assert_eq!(AttractPhase::Title, attract_phase(0));
+        assert_eq!(AttractPhase::Ranking, attract_phase(ranking_start_frame()));
+        assert_eq!(AttractPhase::Demo, attract_phase(TITLE_FRAMES + RANKING_FRAMES));
+        assert_eq!(AttractPhase::Demo, attract_phase(u32::MAX));
+    }
+}
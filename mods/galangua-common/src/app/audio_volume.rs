@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::consts::*;
+use crate::framework::SystemTrait;
+
+pub const MAX_VOLUME: u32 = 10;
+
+// Volume levels in 0 - `MAX_VOLUME`, the SE and the BGM are scaled by the master.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioVolume {
+    pub master: u32,
+    pub se: u32,
+    pub bgm: u32,
+}
+
+impl Default for AudioVolume {
+    fn default() -> Self {
+        Self {
+            master: MAX_VOLUME,
+            se: MAX_VOLUME,
+            bgm: MAX_VOLUME,
+        }
+    }
+}
+
+impl AudioVolume {
+    pub fn load<S: SystemTrait>(system: &S) -> Self {
+        system.get_json::<AudioVolume>(KEY_AUDIO_VOLUME)
+            .filter(|volume| volume.levels().iter().all(|level| *level <= MAX_VOLUME))
+            .unwrap_or_default()
+    }
+
+    pub fn save<S: SystemTrait>(&self, system: &mut S) -> serde_json::Result<()> {
+        system.set_json(KEY_AUDIO_VOLUME, self)
+    }
+
+    // Sets the volume of all channels.
+    pub fn apply<S: SystemTrait>(&self, system: &mut S) {
+        for channel in 0..CHANNEL_COUNT {
+            system.set_volume(channel, self.channel_volume(channel));
+        }
+    }
+
+    pub fn channel_volume(&self, channel: u32) -> f32 {
+        let level = if channel == CH_JINGLE { self.bgm } else { self.se };
+        (self.master * level) as f32 / (MAX_VOLUME * MAX_VOLUME) as f32
+    }
+
+    fn levels(&self) -> [u32; 3] {
+        [self.master, self.se, self.bgm]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+
+    #[test]
+    fn test_channel_volume() {
+        let volume = AudioVolume { master: 5, se: 10, bgm: 0 };
+        assert_eq!(0.5, volume.channel_volume(CH_SHOT));
+        assert_eq!(0.0, volume.channel_volume(CH_JINGLE));
+    }
+
+    #[test]
+    fn test_load_illegal() {
+        let mut system = NullSystem::default();
+        AudioVolume { master: 11, se: 0, bgm: 0 }.save(&mut system).unwrap();
+        assert_eq!(AudioVolume::default(), AudioVolume::load(&system));
+    }
+}
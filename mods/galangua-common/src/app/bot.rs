@@ -0,0 +1,348 @@
+use crate::app::consts::*;
+use crate::app::game::formation_table::{BASE_Y, Y_COUNT};
+use crate::app::game::observation::{EnemyObservation, Observation};
+use crate::app::game::{CaptureState, EnemyType, FormationIndex};
+use crate::framework::types::Vec2I;
+use crate::util::math::ONE;
+use crate::util::pad::PadBit;
+
+// Enemies below the formation are attacking.
+const ATTACK_Y: i32 = (BASE_Y + Y_COUNT as i32 * 16 + 8) * ONE;
+const SHOT_RANGE: i32 = 8 * ONE;
+const TARGET_RANGE_Y: i32 = 64 * ONE;  // Enemies closer than this are avoided, not targeted.
+const DODGE_STEP: i32 = 16 * ONE;
+const SHOT_HIT_RANGE: Vec2I = Vec2I { x: 12 * ONE, y: 12 * ONE };
+const ENEMY_HIT_RANGE: Vec2I = Vec2I { x: 18 * ONE, y: 18 * ONE };
+const SHOT_PREDICT_FRAMES: i32 = 40;
+const ENEMY_PREDICT_FRAMES: i32 = 16;  // Enemies change the direction.
+const SHOT_SPEED_Y: i32 = 2 * ONE;  // Assumed for a shot seen at the first time.
+const MIN_X: i32 = 16 * ONE;
+const MAX_X: i32 = (WIDTH - 16) * ONE;
+
+// Game is regarded as stuck if nothing changes in this frames.
+const STALL_FRAMES: u32 = 3 * 60 * 60;
+
+// Decides the input for each frame from the game state.
+pub trait Bot {
+    fn decide(&mut self, observation: &Observation) -> PadBit;
+}
+
+// Game which can be played by a `Bot`, without the title and the rendering.
+pub trait BotEnv {
+    fn observe(&self) -> Observation;
+    // Returns `false` when the game is over.
+    fn step(&mut self, bits: PadBit) -> bool;
+}
+
+#[derive(Clone, Debug)]
+pub struct BotRun {
+    pub frames: u32,
+    pub game_over: bool,
+    pub stalled: bool,
+    pub observation: Observation,  // At the last frame.
+}
+
+// Plays until the game is over, `max_frames` passes or the game gets stuck.
+pub fn run_bot<E: BotEnv, B: Bot>(env: &mut E, bot: &mut B, max_frames: u32) -> BotRun {
+    let mut observation = env.observe();
+    let mut progress = progress_of(&observation);
+    let mut progress_frame = 0;
+    for frame in 0..max_frames {
+        let bits = bot.decide(&observation);
+        let running = env.step(bits);
+        observation = env.observe();
+        if !running {
+            return BotRun { frames: frame + 1, game_over: true, stalled: false, observation };
+        }
+
+        let current = progress_of(&observation);
+        if current != progress {
+            progress = current;
+            progress_frame = frame;
+        } else if frame - progress_frame >= STALL_FRAMES {
+            return BotRun { frames: frame + 1, game_over: false, stalled: true, observation };
+        }
+    }
+    BotRun { frames: max_frames, game_over: false, stalled: false, observation }
+}
+
+fn progress_of(observation: &Observation) -> (u32, u32, u16, usize) {
+    (observation.score, observation.left_ship, observation.stage, observation.enemies.len())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Phase {
+    Capture,  // Gets captured by the tractor beam, for the demo play.
+    Play,
+}
+
+// Shoots enemies while dodging shots, and recaptures the fighter if captured.
+pub struct HeuristicBot {
+    phase: Phase,
+    count: u32,
+    prev_enemies: Vec<(FormationIndex, Vec2I)>,  // To predict the movement.
+    prev_shots: Vec<Vec2I>,
+}
+
+impl Default for HeuristicBot {
+    fn default() -> Self {
+        Self {
+            phase: Phase::Play,
+            count: 0,
+            prev_enemies: Vec::new(),
+            prev_shots: Vec::new(),
+        }
+    }
+}
+
+impl Bot for HeuristicBot {
+    fn decide(&mut self, observation: &Observation) -> PadBit {
+        self.count = self.count.wrapping_add(1);
+        self.update_phase(observation.capture_state);
+        let bits = self.decide_bits(observation);
+        self.prev_enemies = observation.enemies.iter()
+            .map(|enemy| (enemy.formation_index, enemy.pos))
+            .collect();
+        self.prev_shots = observation.ene_shots.clone();
+        bits
+    }
+}
+
+impl HeuristicBot {
+    // Gets captured once at first, to show the recapture.
+    pub fn for_demo() -> Self {
+        Self {
+            phase: Phase::Capture,
+            ..Self::default()
+        }
+    }
+
+    fn decide_bits(&self, observation: &Observation) -> PadBit {
+        let me = match observation.players.first() {
+            Some(pos) => *pos,
+            None => return PadBit::empty(),
+        };
+        // Dual fighter spreads to the right.
+        let center_x = me.x + (observation.players.len() as i32 - 1) * 8 * ONE;
+
+        let mut bits = PadBit::empty();
+        let (target_x, shoot) = match (self.phase, observation.tractor_beam) {
+            (Phase::Capture, Some(beam_pos)) => (Some(beam_pos.x), false),
+            _ => {
+                let target_x = self.pick_target(observation, &me)
+                    .map(|enemy| self.aim_x(enemy, &me));
+                let shoot = target_x.is_some_and(|x| (x - center_x).abs() < SHOT_RANGE) &&
+                    !self.carrier_in_line(observation, center_x);
+                (target_x, shoot)
+            }
+        };
+        let mut dx = target_x.unwrap_or(CENTER_X) - center_x;
+        // Moves to the safest place around, or stays if the way to the target is dangerous.
+        let dir = dx.signum();
+        let danger = |step: i32| self.danger_at(observation, &me, center_x + step * DODGE_STEP);
+        if danger(0) > 0 {
+            dx = [dir, 0, -dir, -1, 1].iter()
+                .filter(|&&step| (MIN_X..=MAX_X).contains(&(center_x + step * DODGE_STEP)))
+                .min_by_key(|&&step| danger(step))
+                .map_or(0, |step| step * DODGE_STEP);
+        } else if dir != 0 && danger(dir) > 0 {
+            dx = 0;
+        }
+        if dx < -ONE {
+            bits |= PadBit::L;
+        } else if dx > ONE {
+            bits |= PadBit::R;
+        }
+        // Shot is fired on the trigger, so release the button every other frame.
+        if shoot && (self.count & 1) == 0 {
+            bits |= PadBit::A;
+        }
+        bits
+    }
+
+    fn update_phase(&mut self, capture_state: CaptureState) {
+        if self.phase == Phase::Capture && (capture_state == CaptureState::Captured || capture_state == CaptureState::Dual) {
+            self.phase = Phase::Play;
+        }
+    }
+
+    fn pick_target<'a>(&self, observation: &'a Observation, me: &Vec2I) -> Option<&'a EnemyObservation> {
+        let carrier = captured_fighter_carrier(observation);
+        if let Some(carrier) = carrier.filter(|enemy| enemy.pos.y >= ATTACK_Y) {
+            return Some(carrier);
+        }
+
+        observation.enemies.iter()
+            .filter(|enemy| enemy.enemy_type != EnemyType::CapturedFighter)
+            .filter(|enemy| enemy.pos.y < me.y - TARGET_RANGE_Y)
+            // Leaves the formation until an owl comes with the tractor beam.
+            .filter(|enemy| self.phase != Phase::Capture || (enemy.enemy_type != EnemyType::Owl && enemy.pos.y >= ATTACK_Y))
+            .filter(|enemy| carrier.is_none_or(|carrier| carrier.formation_index != enemy.formation_index))
+            .min_by_key(|enemy| {
+                // Prefer attacking enemies, they come closer.
+                let attacking = if enemy.pos.y >= ATTACK_Y { 0 } else { WIDTH * ONE };
+                (enemy.pos.x - me.x).abs() + attacking
+            })
+    }
+
+    // Where the enemy will be when the shot reaches it.
+    fn aim_x(&self, enemy: &EnemyObservation, me: &Vec2I) -> i32 {
+        let vel = self.enemy_velocity(enemy);
+        let frames = (me.y - enemy.pos.y) / (MYSHOT_SPEED + vel.y).max(ONE);
+        enemy.pos.x + vel.x * frames
+    }
+
+    fn enemy_velocity(&self, enemy: &EnemyObservation) -> Vec2I {
+        self.prev_enemies.iter()
+            .find(|(fi, _)| *fi == enemy.formation_index)
+            .map_or(Vec2I::default(), |(_, pos)| &enemy.pos - pos)
+    }
+
+    // Shooting the owl in the formation loses the captured fighter.
+    fn carrier_in_line(&self, observation: &Observation, x: i32) -> bool {
+        captured_fighter_carrier(observation)
+            .is_some_and(|carrier| carrier.pos.y < ATTACK_Y && (carrier.pos.x - x).abs() < SHOT_RANGE * 2)
+    }
+
+    // Number of enemies and shots which might hit the player at `x` in a while.
+    fn danger_at(&self, observation: &Observation, me: &Vec2I, x: i32) -> usize {
+        let target = Vec2I::new(x, me.y);
+        let shots = observation.ene_shots.iter()
+            .filter(|pos| {
+                // Shots are not identified, so take the nearest one above as the previous position.
+                let prev = self.prev_shots.iter()
+                    .filter(|prev| prev.y < pos.y && pos.y - prev.y < 8 * ONE)
+                    .min_by_key(|prev| (pos.x - prev.x).abs())
+                    .map_or(Vec2I::new(pos.x, pos.y - SHOT_SPEED_Y), |prev| *prev);
+                will_hit(pos, &(*pos - &prev), SHOT_PREDICT_FRAMES, &target, &SHOT_HIT_RANGE)
+            });
+        let enemies = observation.enemies.iter()
+            .filter(|enemy| {
+                will_hit(&enemy.pos, &self.enemy_velocity(enemy), ENEMY_PREDICT_FRAMES, &target, &ENEMY_HIT_RANGE)
+            });
+        shots.count() + enemies.count()
+    }
+}
+
+// Whether the object moving straight comes within `range` of `target` in `frames`.
+fn will_hit(pos: &Vec2I, vel: &Vec2I, frames: i32, target: &Vec2I, range: &Vec2I) -> bool {
+    (0..=frames).step_by(4).any(|t| {
+        let d = &(pos + &(vel * t)) - target;
+        d.x.abs() < range.x && d.y.abs() < range.y
+    })
+}
+
+// Owl which holds the captured fighter, one row below it.
+fn captured_fighter_carrier(observation: &Observation) -> Option<&EnemyObservation> {
+    let fighter = observation.enemies.iter()
+        .find(|enemy| enemy.enemy_type == EnemyType::CapturedFighter)?;
+    let fi = FormationIndex(fighter.formation_index.0, fighter.formation_index.1 + 1);
+    observation.enemies.iter()
+        .find(|enemy| enemy.enemy_type == EnemyType::Owl && enemy.formation_index == fi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enemy(enemy_type: EnemyType, fi: FormationIndex, x: i32, y: i32) -> EnemyObservation {
+        EnemyObservation {
+            enemy_type,
+            formation_index: fi,
+            pos: Vec2I::new(x * ONE, y * ONE),
+            angle: 0,
+            is_formation: false,
+            formation_pos: None,
+        }
+    }
+
+    fn player_observation() -> Observation {
+        Observation {
+            players: vec![Vec2I::new(CENTER_X, PLAYER_Y)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dodge() {
+        let mut observation = player_observation();
+        observation.ene_shots.push(Vec2I::new(CENTER_X - 8 * ONE, PLAYER_Y - 20 * ONE));
+        let mut bot = HeuristicBot::default();
+        assert_eq!(PadBit::R, bot.decide(&observation) & (PadBit::L | PadBit::R));
+    }
+
+    #[test]
+    fn test_shoot_target() {
+        let mut observation = player_observation();
+        let x = CENTER_X / ONE;
+        observation.enemies.push(enemy(EnemyType::Bee, FormationIndex(3, 4), x - 40, 88));
+        observation.enemies.push(enemy(EnemyType::Butterfly, FormationIndex(4, 2), x, 150));
+        let mut bot = HeuristicBot::default();
+        let bits = bot.decide(&observation) | bot.decide(&observation);
+        assert!(bits.contains(PadBit::A));
+        assert!(!bits.intersects(PadBit::L | PadBit::R));
+    }
+
+    #[test]
+    fn test_capture() {
+        // Goes under the tractor beam without shooting.
+        let mut observation = player_observation();
+        observation.capture_state = CaptureState::CaptureAttacking;
+        observation.tractor_beam = Some(Vec2I::new(CENTER_X - 50 * ONE, 200 * ONE));
+        observation.ene_shots.push(Vec2I::new(CENTER_X + 2 * ONE, PLAYER_Y - 20 * ONE));
+        let mut bot = HeuristicBot::for_demo();
+        for _ in 0..2 {
+            assert_eq!(PadBit::L, bot.decide(&observation));
+        }
+
+        // Then goes for the owl which carries the captured fighter.
+        let mut observation = player_observation();
+        let x = CENTER_X / ONE;
+        observation.capture_state = CaptureState::Captured;
+        observation.enemies.push(enemy(EnemyType::Bee, FormationIndex(5, 4), x, 88));
+        observation.enemies.push(enemy(EnemyType::CapturedFighter, FormationIndex(4, 0), x + 60, 150));
+        observation.enemies.push(enemy(EnemyType::Owl, FormationIndex(4, 1), x + 60, 166));
+        assert_eq!(PadBit::R, bot.decide(&observation) & (PadBit::L | PadBit::R));
+        assert_eq!(Phase::Play, bot.phase);
+    }
+
+    #[test]
+    fn test_hold_fire_on_carrier() {
+        // Shooting the owl in the formation makes the captured fighter an enemy.
+        let mut observation = player_observation();
+        let x = CENTER_X / ONE;
+        observation.enemies.push(enemy(EnemyType::CapturedFighter, FormationIndex(4, 0), x + 4, 24));
+        observation.enemies.push(enemy(EnemyType::Owl, FormationIndex(4, 1), x + 4, 40));
+        observation.enemies.push(enemy(EnemyType::Bee, FormationIndex(4, 4), x, 88));
+        let mut bot = HeuristicBot::default();
+        let bits = bot.decide(&observation) | bot.decide(&observation);
+        assert!(!bits.contains(PadBit::A));
+    }
+
+    struct CountdownEnv {
+        left: u32,
+        observation: Observation,
+    }
+
+    impl BotEnv for CountdownEnv {
+        fn observe(&self) -> Observation { self.observation.clone() }
+        fn step(&mut self, _bits: PadBit) -> bool {
+            self.left -= 1;
+            self.left > 0
+        }
+    }
+
+    #[test]
+    fn test_run_bot() {
+        let mut env = CountdownEnv { left: 100, observation: player_observation() };
+        let result = run_bot(&mut env, &mut HeuristicBot::default(), 1000);
+        assert!(result.game_over);
+        assert_eq!(100, result.frames);
+
+        // Nothing happens.
+        let mut env = CountdownEnv { left: u32::MAX, observation: player_observation() };
+        let result = run_bot(&mut env, &mut HeuristicBot::default(), STALL_FRAMES * 2);
+        assert!(result.stalled);
+        assert_eq!(STALL_FRAMES + 1, result.frames);
+    }
+}
@@ -8,6 +8,13 @@ pub const ANGLE_DIV: i32 = 24;
 
 pub const KEY_HIGH_SCORE: &str = "highScore";
 pub const DEFAULT_HIGH_SCORE: u32 = 1000;
+pub const KEY_HIGH_SCORE_TABLE: &str = "highScoreTable";
+pub const KEY_PAD_BINDING: &str = "padBinding";
+pub const KEY_GAME_CONFIG: &str = "gameConfig";
+pub const KEY_LIFETIME_STATS: &str = "lifetimeStats";
+pub const KEY_AUDIO_VOLUME: &str = "audioVolume";
+// Saved values which change the play, kept in an input log to be replayed the same.
+pub const REPLAYED_KEYS: [&str; 4] = [KEY_GAME_CONFIG, KEY_PAD_BINDING, KEY_HIGH_SCORE, KEY_HIGH_SCORE_TABLE];
 
 pub const DEFAULT_LEFT_SHIP: u32 = 3;
 
@@ -16,7 +23,7 @@ pub const PLAYER_Y: i32 = (HEIGHT - 16 - 8) * ONE;
 pub const PLAYER_SPEED: i32 = 3 * ONE / 2;
 pub const MYSHOT_SPEED: i32 = 6 * ONE;
 
-pub const MAX_ENE_SHOT_COUNT: usize = 12;
+pub const MAX_ENE_SHOT_COUNT: usize = 16;  // At the highest rank.
 pub const ENE_SHOT_SPEED1: i32 = 25 * ONE / 10;
 pub const ENE_SHOT_SPEED2: i32 = 40 * ONE / 10;
 
@@ -29,11 +36,14 @@ pub const OWL_DESTROY_SHOT_WAIT: u32 = 3 * 60;
 
 pub const BASE_VOLUME: f32 = 1.0 / 4.0;
 
-pub const CHANNEL_COUNT: u32 = 3;
+pub const CHANNEL_COUNT: u32 = 5;
 pub const CH_SHOT: u32 = 0;
-pub const CH_BOMB: u32 = 0;
-pub const CH_ATTACK: u32 = 1;
-pub const CH_JINGLE: u32 = 2;
+pub const CH_BOMB: u32 = 1;
+pub const CH_ATTACK: u32 = 2;
+pub const CH_JINGLE: u32 = 3;  // Music, in the BGM volume.
+pub const CH_BEAM: u32 = 4;
+
+pub const BEAM_FADE_OUT_MS: u32 = 200;
 
 pub const SE_COUNT_STAGE: &str = "assets/audio/se_get_1";
 pub const SE_MYSHOT: &str = "assets/audio/se_pyuun";
@@ -46,3 +56,9 @@ pub const SE_TRACTOR_BEAM1: &str = "assets/audio/se_pipipi_2";
 pub const SE_TRACTOR_BEAM2: &str = "assets/audio/se_pipipi_1";
 pub const SE_EXTEND_SHIP: &str = "assets/audio/jingle_1up";
 pub const SE_RECAPTURE: &str = "assets/audio/jingle_item01";
+
+// Loaded at the start.
+pub const SOUND_ASSETS: [&str; 10] = [
+    SE_COUNT_STAGE, SE_MYSHOT, SE_DAMAGE, SE_BOMB_ZAKO, SE_BOMB_CAPTURED,
+    SE_ATTACK_START, SE_TRACTOR_BEAM1, SE_TRACTOR_BEAM2, SE_EXTEND_SHIP, SE_RECAPTURE,
+];
@@ -1,17 +1,21 @@
 use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_xoshiro::Xoshiro128Plus;
-use std::cmp::min;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-use crate::app::game::appearance_table::*;
+use crate::app::game::appearance_table::{UnitTableEntry, UNIT_COUNT};
+use crate::app::game::challenging_stage::is_challenging_stage;
+use crate::app::game::difficulty_rank::DifficultyRank;
+use crate::app::game::stage_definition::StageDefinition;
 use crate::app::game::traj::Traj;
 use crate::app::game::traj_command::TrajCommand;
 use crate::app::game::{EnemyType, FormationIndex};
+use crate::app::util::snapshot;
 use crate::framework::types::{Vec2I, ZERO_VEC};
 use crate::util::math::ONE;
 
 const ASSAULT_FORMATION_Y: u8 = 6;
-const UNIT_COUNT: u32 = 5;
 const STEP_WAIT: u32 = 16 / 3;
 
 pub struct NewBorned {
@@ -39,13 +43,14 @@ impl NewBorned {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct Info {
     time: u32,
     enemy_type: EnemyType,
     fi: FormationIndex,
     offset: Vec2I,
     flip_x: bool,
+    #[serde(with = "snapshot::traj_table")]
     traj_table: &'static [TrajCommand],
     shot_enable: bool,
 }
@@ -64,8 +69,10 @@ pub trait Accessor {
     fn is_stationary(&self) -> bool;
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct AppearanceManager {
     stage: u16,
+    challenging: bool,
     paused: bool,
     wait_stationary: bool,
     wait: u32,
@@ -73,14 +80,22 @@ pub struct AppearanceManager {
     time: u32,
     pub done: bool,
     orders: Vec<Info>,
-    orders_ptr: &'static [Info],
+    order_index: usize,
     captured_fighter: Option<FormationIndex>,
+    skip: Vec<FormationIndex>,
+    spawned: Vec<FormationIndex>,
+    #[serde(skip)]
+    definition: Arc<StageDefinition>,  // Not saved, set again with `set_stage_definition`.
+    rng: Xoshiro128Plus,
+    #[serde(default)]
+    rank: DifficultyRank,
 }
 
-impl Default for AppearanceManager {
-    fn default() -> Self {
+impl AppearanceManager {
+    pub fn new(rng: Xoshiro128Plus) -> Self {
         Self {
             stage: 0,
+            challenging: false,
             paused: false,
             wait_stationary: false,
             wait: 0,
@@ -88,18 +103,53 @@ impl Default for AppearanceManager {
             time: 0,
             done: true,
             orders: Vec::new(),
-            orders_ptr: &[],
+            order_index: 0,
             captured_fighter: None,
+            skip: Vec::new(),
+            spawned: Vec::new(),
+            definition: Arc::new(StageDefinition::default()),
+            rng,
+            rank: DifficultyRank::default(),
         }
     }
-}
 
-impl AppearanceManager {
+    pub fn set_stage_definition(&mut self, definition: Arc<StageDefinition>) {
+        self.definition = definition;
+    }
+
+    // Takes effect from the next stage.
+    pub fn set_rank(&mut self, rank: DifficultyRank) {
+        self.rank = rank;
+    }
+
     pub fn restart(&mut self, stage: u16, captured_fighter: Option<FormationIndex>) {
-        *self = Self::default();
+        let definition = self.definition.clone();
+        let rank = self.rank;
+        *self = Self::new(self.rng.clone());
+        self.definition = definition;
+        self.rank = rank;
         self.stage = stage;
+        self.challenging = is_challenging_stage(stage);
         self.done = false;
-        self.captured_fighter = captured_fighter;
+        // Captured fighter waits for the next ordinary stage.
+        self.captured_fighter = if self.challenging { None } else { captured_fighter };
+    }
+
+    // Enemies destroyed in the previous turn, in 2 players mode.
+    pub fn set_skip(&mut self, skip: Vec<FormationIndex>) {
+        self.skip = skip;
+    }
+
+    // Enemies which have appeared in this stage (or in the previous turn) and are not alive.
+    pub fn calc_destroyed<F: Fn(&FormationIndex) -> bool>(&self, is_alive: F) -> Vec<FormationIndex> {
+        self.skip.iter()
+            .chain(self.spawned.iter().filter(|fi| !is_alive(fi)))
+            .copied()
+            .collect()
+    }
+
+    pub fn is_challenging(&self) -> bool {
+        self.challenging
     }
 
     pub fn pause(&mut self, value: bool) {
@@ -127,42 +177,45 @@ impl AppearanceManager {
                 }
                 self.wait_stationary = false;
             }
-            if self.unit >= UNIT_COUNT {
+            if self.unit >= UNIT_COUNT as u32 {
                 self.done = true;
                 return None;
             }
 
             if self.orders.is_empty() {
                 self.set_orders();
-                // orders is owned by vec, so it lives as long as self and not worry about that.
-                self.orders_ptr = unsafe { std::mem::transmute::<&[Info], &'static [Info]>(&self.orders) };
-
+                self.order_index = 0;
                 self.time = 0;
             }
         }
 
-        if self.orders.is_empty() || self.orders_ptr[0].time < self.time {
+        if self.orders.is_empty() || self.orders[self.order_index].time < self.time {
             return None;
         }
 
         let mut new_borns: Vec<NewBorned> = Vec::new();
-        while self.orders_ptr[0].time == self.time {
-            let p = &self.orders_ptr[0];
-            let mut traj = Traj::new(p.traj_table, &p.offset, p.flip_x, p.fi);
-            traj.shot_enable = p.shot_enable;
-
-            let enemy = NewBorned::new(p.enemy_type, ZERO_VEC, 0, 0, p.fi, traj);
-            new_borns.push(enemy);
+        while self.orders[self.order_index].time == self.time {
+            let p = &self.orders[self.order_index];
+            if p.enemy_type == EnemyType::CapturedFighter || !self.skip.contains(&p.fi) {
+                let mut traj = Traj::new(p.traj_table, &p.offset, p.flip_x, p.fi);
+                traj.shot_enable = p.shot_enable;
+
+                let enemy = NewBorned::new(p.enemy_type, ZERO_VEC, 0, 0, p.fi, traj);
+                new_borns.push(enemy);
+                if p.enemy_type != EnemyType::CapturedFighter {
+                    self.spawned.push(p.fi);
+                }
+            }
 
-            self.orders_ptr = &self.orders_ptr[1..];
-            if self.orders_ptr.is_empty() {
+            self.order_index += 1;
+            if self.order_index >= self.orders.len() {
                 break;
             }
         }
 
         self.time += 1;
-        if self.orders_ptr.is_empty() {
-            self.orders_ptr = &[];
+        if self.order_index >= self.orders.len() {
+            self.order_index = 0;
             self.orders.clear();
 
             self.unit += 1;
@@ -181,9 +234,9 @@ impl AppearanceManager {
 
     fn create_orders(&mut self) {
         let base = self.unit * 8;
-        let entry = &UNIT_TABLE[(self.stage as usize) % UNIT_TABLE.len()][self.unit as usize];
-        let assault_count = ASSAULT_TABLE[min(self.stage as usize, ASSAULT_TABLE.len() - 1)]
-            [self.unit as usize] as usize;
+        let definition = self.definition.clone();
+        let entry = self.unit_entry().clone();
+        let assault_count = if self.challenging { 0 } else { definition.assault_count(self.stage, self.unit) as usize };
 
         let div;
         match entry.pat {
@@ -191,7 +244,7 @@ impl AppearanceManager {
                 let flip = if entry.flip_x { 1 } else { 0 };
                 for count in 0..8 {
                     let side = count & 1;
-                    let fi = ORDER[(base + (count / 2 + (side ^ flip) * 4)) as usize];
+                    let fi = definition.order[(base + (count / 2 + (side ^ flip) * 4)) as usize];
                     let info = self.create_info(fi, count);
                     self.orders.push(info);
                 }
@@ -199,7 +252,7 @@ impl AppearanceManager {
             }
             1 => {
                 for count in 0..8 {
-                    let fi = ORDER[(base + (count / 2 + (count & 1) * 4)) as usize];
+                    let fi = definition.order[(base + (count / 2 + (count & 1) * 4)) as usize];
                     let info = self.create_info(fi, count);
                     self.orders.push(info);
                 }
@@ -207,7 +260,7 @@ impl AppearanceManager {
             }
             2 => {
                 for count in 0..8 {
-                    let fi = ORDER[(base + count) as usize];
+                    let fi = definition.order[(base + count) as usize];
                     let info = self.create_info(fi, count);
                     self.orders.push(info);
                 }
@@ -217,7 +270,7 @@ impl AppearanceManager {
                 let flip = if entry.flip_x { 1 } else { 0 };
                 for count in 0..8 {
                     let side = count & 1;
-                    let fi = ORDER[(base + (count / 2 + (side ^ flip) * 4)) as usize];
+                    let fi = definition.order[(base + (count / 2 + (side ^ flip) * 4)) as usize];
                     let info = self.create_info(fi, count);
                     self.orders.push(info);
                 }
@@ -228,11 +281,10 @@ impl AppearanceManager {
         }
 
         if assault_count > 0 {
-            let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
             for i in 0..assault_count * 2 {
                 let lr = i & 1;
                 let n = self.orders.len() / 2;
-                let index = rng.gen_range(0, n + 1);
+                let index = self.rng.gen_range(0, n + 1);
                 self.orders.push(self.orders[lr].clone());
                 // Shift
                 for j in 0..(n - index) {
@@ -247,7 +299,7 @@ impl AppearanceManager {
             recalc_order_time(&mut self.orders, STEP_WAIT, div);
         }
 
-        if self.unit == UNIT_COUNT - 1 {
+        if self.unit == UNIT_COUNT as u32 - 1 {
             if let Some(fi) = self.captured_fighter {
                 let mut info = self.create_info(fi, self.orders.len() as u32);
                 info.enemy_type = EnemyType::CapturedFighter;
@@ -256,10 +308,21 @@ impl AppearanceManager {
         }
     }
 
+    fn unit_entry(&self) -> &UnitTableEntry<'static> {
+        if self.challenging {
+            self.definition.challenging_unit(self.unit)
+        } else {
+            self.definition.unit(self.stage, self.unit)
+        }
+    }
+
     fn create_info(&self, fi: FormationIndex, count: u32) -> Info {
-        let entry = &UNIT_TABLE[(self.stage as usize) % UNIT_TABLE.len()][self.unit as usize];
-        let enemy_types = &ENEMY_TYPE_TABLE[(self.unit * 2) as usize ..
-                                            (self.unit * 2) as usize + 2];
+        let entry = self.unit_entry();
+        let enemy_types = if self.challenging {
+            self.definition.challenging_enemy_types(self.unit)
+        } else {
+            self.definition.enemy_types(self.unit)
+        };
         match entry.pat {
             0 => {
                 let flip = if entry.flip_x { 1 } else { 0 };
@@ -290,8 +353,9 @@ impl AppearanceManager {
     }
 
     fn set_shot_enables(&mut self) {
-        let count = SHOT_ENABLE_TABLE[min(self.stage as usize, SHOT_ENABLE_TABLE.len() - 1)][self.unit as usize];
-        if count == 0 {
+        let count = self.rank.shot_enable_count(self.definition.shot_enable_count(self.stage, self.unit));
+        let count = std::cmp::min(count, self.orders.len() as u32);
+        if count == 0 || self.challenging {
             return;
         }
 
@@ -300,8 +364,7 @@ impl AppearanceManager {
         for i in 0..orders.len() {
             nums.push(i);
         }
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
-        nums.partial_shuffle(&mut rng, count as usize);
+        nums.partial_shuffle(&mut self.rng, count as usize);
 
         for i in 0..count {
             orders[nums[i as usize]].shot_enable = true;
@@ -319,3 +382,38 @@ fn recalc_order_time(orders: &mut Vec<Info>, step_wait: u32, div: u32) {
         order.time = step_wait * (i as u32 / div);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::game_config::Difficulty;
+    use crate::util::rng::create_rng;
+
+    #[test]
+    fn test_shot_enable_rank() {
+        // Second unit of the second stage has 4 enemies which can shoot at normal rank.
+        let shot_enable_count = |difficulty: Difficulty| {
+            let mut appearance_manager = AppearanceManager::new(create_rng(0));
+            appearance_manager.set_rank(DifficultyRank::new(difficulty));
+            appearance_manager.restart(1, None);
+            appearance_manager.unit = 1;
+            appearance_manager.set_orders();
+            appearance_manager.orders.iter().filter(|info| info.shot_enable).count()
+        };
+        assert_eq!(2, shot_enable_count(Difficulty::Easy));
+        assert_eq!(4, shot_enable_count(Difficulty::Normal));
+        assert_eq!(5, shot_enable_count(Difficulty::Hard));
+    }
+
+    #[test]
+    fn test_challenging_stage_definition() {
+        let mut definition = StageDefinition::default();
+        definition.challenging_enemy_types[0] = [EnemyType::Butterfly, EnemyType::Butterfly];
+        let mut appearance_manager = AppearanceManager::new(create_rng(0));
+        appearance_manager.set_stage_definition(Arc::new(definition.clone()));
+        appearance_manager.restart(2, None);
+        appearance_manager.set_orders();
+        assert!(appearance_manager.orders.iter().all(|info| info.enemy_type == EnemyType::Butterfly && !info.shot_enable));
+        assert!(appearance_manager.orders.iter().all(|info| info.traj_table == definition.challenging_units[0].table));
+    }
+}
@@ -6,7 +6,7 @@ use crate::app::game::{EnemyType, FormationIndex};
 
 const fn p(x: u8, y: u8) -> FormationIndex { FormationIndex(x, y) }
 
-pub const ORDER: [FormationIndex; 40] = [
+pub const ORDER: [FormationIndex; 8 * UNIT_COUNT] = [
     p(4, 2), p(5, 2), p(4, 3), p(5, 3),
     p(4, 4), p(5, 4), p(4, 5), p(5, 5),
 
@@ -23,7 +23,7 @@ pub const ORDER: [FormationIndex; 40] = [
     p(0, 4), p(1, 4), p(0, 5), p(1, 5),
 ];
 
-pub const ENEMY_TYPE_TABLE: [EnemyType; 2 * 5] = [
+pub const ENEMY_TYPE_TABLE: [EnemyType; 2 * UNIT_COUNT] = [
     EnemyType::Butterfly, EnemyType::Bee,
     EnemyType::Owl, EnemyType::Butterfly,
     EnemyType::Butterfly, EnemyType::Butterfly,
@@ -31,13 +31,25 @@ pub const ENEMY_TYPE_TABLE: [EnemyType; 2 * 5] = [
     EnemyType::Bee, EnemyType::Bee,
 ];
 
+// Challenging stage uses only Bees and Butterflies, which are destroyed in one shot.
+pub const CHALLENGING_ENEMY_TYPE_TABLE: [EnemyType; 2 * UNIT_COUNT] = [
+    EnemyType::Bee, EnemyType::Butterfly,
+    EnemyType::Butterfly, EnemyType::Butterfly,
+    EnemyType::Bee, EnemyType::Bee,
+    EnemyType::Butterfly, EnemyType::Bee,
+    EnemyType::Bee, EnemyType::Butterfly,
+];
+
+pub const UNIT_COUNT: usize = 5;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct UnitTableEntry<'a> {
     pub pat: usize,
     pub table: &'a [TrajCommand],
     pub flip_x: bool,
 }
 
-counted_array!(pub const UNIT_TABLE: [[UnitTableEntry; 5]; _] = [
+counted_array!(pub const UNIT_TABLE: [[UnitTableEntry; UNIT_COUNT]; _] = [
     [
         UnitTableEntry { pat: 0, table: &COMMAND_TABLE1, flip_x: false },
         UnitTableEntry { pat: 1, table: &COMMAND_TABLE2, flip_x: false },
@@ -68,7 +80,15 @@ counted_array!(pub const UNIT_TABLE: [[UnitTableEntry; 5]; _] = [
     ],
 ]);
 
-counted_array!(pub const ASSAULT_TABLE: [[u32; 5]; _] = [
+pub const CHALLENGING_UNIT_TABLE: [UnitTableEntry; UNIT_COUNT] = [
+    UnitTableEntry { pat: 0, table: &CHALLENGING_TABLE1, flip_x: false },
+    UnitTableEntry { pat: 1, table: &CHALLENGING_TABLE2, flip_x: false },
+    UnitTableEntry { pat: 1, table: &CHALLENGING_TABLE2, flip_x: true },
+    UnitTableEntry { pat: 3, table: &CHALLENGING_TABLE3, flip_x: false },
+    UnitTableEntry { pat: 3, table: &CHALLENGING_TABLE3, flip_x: true },
+];
+
+counted_array!(pub const ASSAULT_TABLE: [[u32; UNIT_COUNT]; _] = [
     [0, 0, 0, 0, 0],
     [0, 0, 0, 0, 0],
     [1, 0, 0, 1, 1],
@@ -82,7 +102,7 @@ counted_array!(pub const ASSAULT_TABLE: [[u32; 5]; _] = [
     [2, 2, 2, 2, 2],
 ]);
 
-counted_array!(pub const SHOT_ENABLE_TABLE: [[u32; 5]; _] = [
+counted_array!(pub const SHOT_ENABLE_TABLE: [[u32; UNIT_COUNT]; _] = [
     [0, 0, 0, 0, 0],
     [3, 4, 4, 4, 4],
     [4, 4, 4, 4, 4],
@@ -1,13 +1,14 @@
 use array_macro::*;
 use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_xoshiro::Xoshiro128Plus;
+use serde::{Deserialize, Serialize};
 
+use crate::app::game::difficulty_rank::DifficultyRank;
 use crate::app::game::formation_table::{X_COUNT, Y_COUNT};
 use crate::app::game::FormationIndex;
 
-const MAX_ATTACKER_COUNT: usize = 3;
-const WAIT: u32 = 30;
+pub const MAX_ATTACKER_COUNT: usize = 5;  // At the highest rank.
 
 pub trait Accessor {
     fn can_capture_attack(&self) -> bool;
@@ -16,29 +17,39 @@ pub trait Accessor {
     fn is_enemy_formation_at(&self, formation_index: &FormationIndex) -> bool;
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct AttackManager {
     enable: bool,
     paused: bool,
     wait: u32,
     attackers: [Option<FormationIndex>; MAX_ATTACKER_COUNT],
     cycle: u32,
+    rng: Xoshiro128Plus,
+    #[serde(default)]
+    rank: DifficultyRank,
 }
 
-impl Default for AttackManager {
-    fn default() -> Self {
+impl AttackManager {
+    pub fn new(rng: Xoshiro128Plus) -> Self {
         Self {
             enable: false,
             paused: false,
             wait: 0,
             attackers: Default::default(),
             cycle: 0,
+            rng,
+            rank: DifficultyRank::default(),
         }
     }
-}
 
-impl AttackManager {
     pub fn restart(&mut self, _stage: u16) {
-        *self = Self::default();
+        let rank = self.rank;
+        *self = Self::new(self.rng.clone());
+        self.rank = rank;
+    }
+
+    pub fn set_rank(&mut self, rank: DifficultyRank) {
+        self.rank = rank;
     }
 
     pub fn set_enable(&mut self, value: bool) {
@@ -53,6 +64,10 @@ impl AttackManager {
         self.attackers.iter().all(|x| x.is_none())
     }
 
+    fn active_slots(&self) -> &[Option<FormationIndex>] {
+        &self.attackers[..self.rank.attacker_count()]
+    }
+
     pub fn update<A: Accessor>(&mut self, accessor: &A) -> Option<(FormationIndex, bool)> {
         self.check_liveness(accessor);
 
@@ -66,16 +81,16 @@ impl AttackManager {
         }
 
         let mut result: Option<(FormationIndex, bool)> = None;
-        if self.attackers.iter().any(|x| x.is_none()) {
+        if self.active_slots().iter().any(|x| x.is_none()) {
             result = self.pick_attacker(accessor);
-            self.wait = WAIT;
+            self.wait = self.rank.attack_wait();
             self.cycle += 1;
         }
         result
     }
 
     pub fn put_attacker(&mut self, formation_index: &FormationIndex) {
-        let slot_index = self.attackers.iter().position(|x| x.is_none()).unwrap();
+        let slot_index = self.active_slots().iter().position(|x| x.is_none()).unwrap();
         self.attackers[slot_index] = Some(*formation_index);
     }
 
@@ -110,8 +125,8 @@ impl AttackManager {
     }
 
     fn pick_random(&mut self, candidates: &[Option<[u8; 2]>; Y_COUNT], rows: &mut [u32]) -> Option<FormationIndex> {
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
-        rows.shuffle(&mut rng);
+        let rng = &mut self.rng;
+        rows.shuffle(rng);
         rows.iter()
             .find_map(|&row| candidates[row as usize].map(|pos| (pos, row)))
             .map(|(pos, row)| {
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::game::appearance_table::UNIT_COUNT;
+use crate::framework::RendererTrait;
+
+const CHALLENGING_STAGE_INTERVAL: u16 = 4;
+const ENEMY_COUNT: u32 = 8 * UNIT_COUNT as u32;
+const HIT_BONUS: u32 = 100;
+const PERFECT_BONUS: u32 = 10000;
+
+const HITS_FRAME: u32 = 30;
+const NUMBER_FRAME: u32 = 90;
+const BONUS_FRAME: u32 = 150;
+const END_FRAME: u32 = 360;
+
+// Stage 3, 7, 11, ...
+pub fn is_challenging_stage(stage: u16) -> bool {
+    stage % CHALLENGING_STAGE_INTERVAL == CHALLENGING_STAGE_INTERVAL - 2
+}
+
+pub fn calc_challenging_bonus(hit_count: u32) -> u32 {
+    if hit_count >= ENEMY_COUNT {
+        PERFECT_BONUS
+    } else {
+        hit_count * HIT_BONUS
+    }
+}
+
+// Result screen after a challenging stage.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ChallengingResult {
+    hit_count: u32,
+    count: u32,
+}
+
+impl ChallengingResult {
+    pub fn new(hit_count: u32) -> Self {
+        Self { hit_count, count: 0 }
+    }
+
+    pub fn bonus(&self) -> u32 {
+        calc_challenging_bonus(self.hit_count)
+    }
+
+    pub fn is_perfect(&self) -> bool {
+        self.hit_count >= ENEMY_COUNT
+    }
+
+    // Returns false when finished.
+    pub fn update(&mut self) -> bool {
+        if self.count < END_FRAME {
+            self.count += 1;
+        }
+        self.count < END_FRAME
+    }
+
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R) {
+        if self.count < HITS_FRAME {
+            return;
+        }
+
+        if self.is_perfect() && (self.count & 16) == 0 {
+            renderer.set_texture_color_mod("font", 255, 0, 0);
+            renderer.draw_str("font", (28 - 8) / 2 * 8, 13 * 8, "PERFECT!");
+        }
+
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", 4 * 8, 16 * 8, "NUMBER OF HITS");
+        if self.count >= NUMBER_FRAME {
+            renderer.set_texture_color_mod("font", 255, 255, 255);
+            renderer.draw_str("font", 20 * 8, 16 * 8, &format!("{:>3}", self.hit_count));
+        }
+
+        if self.count >= BONUS_FRAME {
+            if self.is_perfect() {
+                renderer.set_texture_color_mod("font", 255, 255, 0);
+                renderer.draw_str("font", (28 - 23) / 2 * 8, 19 * 8, &format!("SPECIAL BONUS {} PTS", PERFECT_BONUS));
+            } else {
+                renderer.set_texture_color_mod("font", 0, 255, 255);
+                renderer.draw_str("font", 4 * 8, 19 * 8, "BONUS");
+                renderer.set_texture_color_mod("font", 255, 255, 255);
+                renderer.draw_str("font", 16 * 8, 19 * 8, &format!("{:>7}", self.bonus()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::consts::*;
+    use crate::app::game::stage_definition::StageDefinition;
+    use crate::app::game::traj::{Accessor, Traj};
+    use crate::app::game::FormationIndex;
+    use crate::framework::types::{Vec2I, ZERO_VEC};
+    use crate::util::math::ONE;
+
+    struct DummyAccessor;
+    impl Accessor for DummyAccessor {
+        fn get_formation_pos(&self, _formation_index: &FormationIndex) -> Vec2I { ZERO_VEC }
+        fn get_stage_no(&self) -> u16 { 2 }
+    }
+
+    #[test]
+    fn test_is_challenging_stage() {
+        let stages: Vec<u16> = (0..12).filter(|&stage| is_challenging_stage(stage)).collect();
+        assert_eq!(vec![2, 6, 10], stages);
+    }
+
+    #[test]
+    fn test_bonus() {
+        assert_eq!(0, calc_challenging_bonus(0));
+        assert_eq!(3900, calc_challenging_bonus(39));
+        assert_eq!(10000, calc_challenging_bonus(40));
+    }
+
+    #[test]
+    fn test_traj_leaves_screen() {
+        let margin = 8 * ONE;
+        for entry in StageDefinition::default().challenging_units.iter() {
+            for &flip_x in [false, true].iter() {
+                let mut traj = Traj::new(entry.table, &ZERO_VEC, flip_x, FormationIndex(0, 0));
+                let mut frame = 0;
+                while traj.update(&DummyAccessor) {
+                    frame += 1;
+                    assert!(frame < 1000);
+                }
+                let pos = traj.pos();
+                assert!(pos.x < -margin || pos.x > WIDTH * ONE + margin ||
+                        pos.y < -margin || pos.y > HEIGHT * ONE + margin, "{:?}", pos);
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::consts::*;
+use crate::app::game_config::Difficulty;
+
+pub const MAX_RANK: u32 = 10;
+
+const NORMAL_RANK: u32 = 5;
+
+// Difficulty rank from 0 (easiest) to `MAX_RANK`, which scales enemy attacks.
+// In adaptive mode, it rises on clearing a stage and falls on losing a ship.
+// Each player has own rank in 2 players mode.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyRank {
+    rank: u32,
+    #[serde(default)]
+    difficulty: Difficulty,
+}
+
+impl Default for DifficultyRank {
+    fn default() -> Self {
+        Self::new(Difficulty::default())
+    }
+}
+
+impl DifficultyRank {
+    pub fn new(difficulty: Difficulty) -> Self {
+        let rank = match difficulty {
+            Difficulty::Easy => 2,
+            Difficulty::Normal | Difficulty::Adaptive => NORMAL_RANK,
+            Difficulty::Hard => 8,
+        };
+        Self { rank, difficulty }
+    }
+
+    pub fn rank(&self) -> u32 {
+        self.rank
+    }
+
+    pub fn on_stage_cleared(&mut self) {
+        if self.difficulty == Difficulty::Adaptive {
+            self.rank = std::cmp::min(self.rank + 1, MAX_RANK);
+        }
+    }
+
+    pub fn on_player_dead(&mut self) {
+        if self.difficulty == Difficulty::Adaptive {
+            self.rank = self.rank.saturating_sub(2);
+        }
+    }
+
+    // Enemies which attack at the same time.
+    pub fn attacker_count(&self) -> usize {
+        2 + (self.rank as usize * 3 / MAX_RANK as usize)
+    }
+
+    // Frames between picking attackers.
+    pub fn attack_wait(&self) -> u32 {
+        45 - self.rank * 3
+    }
+
+    pub fn max_ene_shot_count(&self) -> usize {
+        MAX_ENE_SHOT_COUNT - 8 + (self.rank as usize * 8 / MAX_RANK as usize)
+    }
+
+    pub fn ene_shot_speed(&self, speed: i32) -> i32 {
+        speed * (90 + self.rank as i32 * 2) / 100
+    }
+
+    // Enemies which can shoot in an appearance unit, from `count` of the stage.
+    pub fn shot_enable_count(&self, count: u32) -> u32 {
+        count * (NORMAL_RANK + self.rank) / (NORMAL_RANK * 2)
+    }
+
+    // Rush doesn't change with the rank, to keep the preset of the difficulty.
+    pub fn rush_threshold(&self) -> u32 {
+        self.difficulty.rush_threshold()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::game::attack_manager::MAX_ATTACKER_COUNT;
+
+    #[test]
+    fn test_normal() {
+        // Normal rank keeps the original parameters.
+        let rank = DifficultyRank::new(Difficulty::Normal);
+        assert_eq!(3, rank.attacker_count());
+        assert_eq!(30, rank.attack_wait());
+        assert_eq!(12, rank.max_ene_shot_count());
+        assert_eq!(1000, rank.ene_shot_speed(1000));
+        assert_eq!(4, rank.shot_enable_count(4));
+        assert_eq!(RUSH_THRESHOLD, rank.rush_threshold());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rank = DifficultyRank::new(Difficulty::Adaptive);
+        for _ in 0..MAX_RANK {
+            rank.on_stage_cleared();
+        }
+        assert_eq!(MAX_RANK, rank.rank());
+        assert_eq!(MAX_ATTACKER_COUNT, rank.attacker_count());
+        assert_eq!(MAX_ENE_SHOT_COUNT, rank.max_ene_shot_count());
+        assert_eq!(6, rank.shot_enable_count(4));
+        assert_eq!(RUSH_THRESHOLD, rank.rush_threshold());
+
+        for _ in 0..MAX_RANK {
+            rank.on_player_dead();
+        }
+        assert_eq!(0, rank.rank());
+        assert!(rank.attack_wait() > 30);
+        assert_eq!(2, rank.shot_enable_count(4));
+        assert_eq!(0, rank.shot_enable_count(0));
+
+        let mut rank = DifficultyRank::new(Difficulty::Hard);
+        rank.on_player_dead();
+        assert_eq!(8, rank.rank());
+        assert_eq!(RUSH_THRESHOLD + 3, rank.rush_threshold());
+    }
+}
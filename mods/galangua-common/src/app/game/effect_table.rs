@@ -2,11 +2,11 @@ use crate::app::game::EarnedPointType;
 
 pub const FLASH_ENEMY_FRAME: u32 = 2;
 
-pub const FLASH_ENEMY_SPRITE_NAMES: [&str; 4] = [
-    "gopher_flash",
-    "dman_flash",
-    "cpp_flash",
-    "rustacean_flash",
+pub const FLASH_ENEMY_SPRITE_TABLE: [[&str; 1]; 4] = [
+    ["gopher_flash"],
+    ["dman_flash"],
+    ["cpp_flash"],
+    ["rustacean_flash"],
 ];
 
 //
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::app::consts::*;
 use crate::app::game::FormationIndex;
 use crate::framework::types::Vec2I;
@@ -5,12 +7,13 @@ use crate::util::math::ONE;
 
 use super::formation_table::*;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 enum MovingPat {
     Slide,
     Scale,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Formation {
     xtbl: [i32; X_COUNT],
     ytbl: [i32; Y_COUNT],
@@ -103,4 +106,9 @@ impl Formation {
     pub fn pos(&self, index: &FormationIndex) -> Vec2I {
         Vec2I::new(self.xtbl[index.0 as usize], self.ytbl[index.1 as usize])
     }
+
+    // Returns `None` if the index is out of the formation.
+    pub fn get_pos(&self, index: &FormationIndex) -> Option<Vec2I> {
+        Some(Vec2I::new(*self.xtbl.get(index.0 as usize)?, *self.ytbl.get(index.1 as usize)?))
+    }
 }
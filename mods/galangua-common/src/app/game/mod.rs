@@ -1,20 +1,35 @@
 pub mod appearance_manager;
 pub mod appearance_table;
 pub mod attack_manager;
+pub mod challenging_stage;
+pub mod difficulty_rank;
 pub mod effect_table;
 pub mod formation;
 pub mod formation_table;
+pub mod observation;
+pub mod player_state;
+pub mod stage_definition;
+pub mod sprite_table;
 pub mod stage_indicator;
 pub mod star_manager;
 pub mod tractor_beam_table;
 pub mod traj;
 pub mod traj_command;
 pub mod traj_command_table;
+pub mod traj_script;
 
-#[derive(Clone, Copy, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct FormationIndex(pub u8, pub u8);  // x, y
 
-#[derive(Clone, Copy, PartialEq)]
+impl FormationIndex {
+    // Row major order, same as the enemy slots in OO version:
+    // decides which enemy is hit when a shot overlaps several ones.
+    pub fn order(&self) -> (u8, u8) { (self.1, self.0) }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EnemyType {
     Bee,
     Butterfly,
@@ -22,7 +37,7 @@ pub enum EnemyType {
     CapturedFighter,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum EarnedPointType {
     Point1600,
     Point1000,
@@ -30,8 +45,9 @@ pub enum EarnedPointType {
     Point400,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum CaptureState {
+    #[default]
     NoCapture,
     CaptureAttacking,
     Capturing,
@@ -0,0 +1,105 @@
+use super::formation::Formation;
+use super::{CaptureState, EnemyType, FormationIndex};
+use crate::framework::types::Vec2I;
+
+// Implementation independent view of the game state, to compare the OO and ECS versions
+// and to let bots play.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Observation {
+    pub score: u32,
+    pub left_ship: u32,
+    pub stage: u16,
+    pub players: Vec<Vec2I>,  // Excludes the dead one.
+    pub my_shots: Vec<Vec2I>,  // Includes the dual one.
+    pub enemies: Vec<EnemyObservation>,
+    pub ene_shots: Vec<Vec2I>,
+    pub capture_state: CaptureState,
+    pub tractor_beam: Option<Vec2I>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnemyObservation {
+    pub enemy_type: EnemyType,
+    pub formation_index: FormationIndex,
+    pub pos: Vec2I,
+    pub angle: i32,
+    pub is_formation: bool,
+    pub formation_pos: Option<Vec2I>,  // None for assault enemies, which have no place.
+}
+
+impl Observation {
+    // Sort collections, because the iteration order differs between the implementations.
+    pub fn normalize(&mut self) {
+        self.players.sort_by_key(|pos| (pos.x, pos.y));
+        self.my_shots.sort_by_key(|pos| (pos.x, pos.y));
+        self.enemies.sort_by_key(|enemy| enemy.formation_index.order());
+        self.ene_shots.sort_by_key(|pos| (pos.x, pos.y));
+    }
+
+    pub fn set_formation_pos(&mut self, formation: &Formation) {
+        for enemy in self.enemies.iter_mut() {
+            enemy.formation_pos = formation.get_pos(&enemy.formation_index);
+        }
+    }
+
+    // Returns the name of the first field which differs.
+    pub fn diff(&self, other: &Observation) -> Option<&'static str> {
+        if self.score != other.score {
+            Some("score")
+        } else if self.left_ship != other.left_ship {
+            Some("left_ship")
+        } else if self.stage != other.stage {
+            Some("stage")
+        } else if self.players != other.players {
+            Some("players")
+        } else if self.my_shots != other.my_shots {
+            Some("my_shots")
+        } else if self.enemies != other.enemies {
+            Some("enemies")
+        } else if self.ene_shots != other.ene_shots {
+            Some("ene_shots")
+        } else if self.capture_state != other.capture_state {
+            Some("capture_state")
+        } else if self.tractor_beam != other.tractor_beam {
+            Some("tractor_beam")
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let enemy = |x, y| EnemyObservation {
+            enemy_type: EnemyType::Bee,
+            formation_index: FormationIndex(x, y),
+            pos: Vec2I::new(0, 0),
+            angle: 0,
+            is_formation: true,
+            formation_pos: None,
+        };
+        let mut observation = Observation {
+            enemies: vec![enemy(1, 4), enemy(3, 3), enemy(0, 4)],
+            ene_shots: vec![Vec2I::new(5, 1), Vec2I::new(2, 9)],
+            ..Default::default()
+        };
+        observation.normalize();
+        assert_eq!(vec![enemy(3, 3), enemy(0, 4), enemy(1, 4)], observation.enemies);
+        assert_eq!(vec![Vec2I::new(2, 9), Vec2I::new(5, 1)], observation.ene_shots);
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Observation { score: 100, ..Default::default() };
+        let mut b = a.clone();
+        assert_eq!(None, a.diff(&b));
+        b.ene_shots.push(Vec2I::new(1, 2));
+        assert_eq!(Some("ene_shots"), a.diff(&b));
+        b.left_ship = 2;
+        assert_eq!(Some("left_ship"), a.diff(&b));
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::consts::*;
+use crate::app::game::difficulty_rank::DifficultyRank;
+use crate::app::game::{CaptureState, FormationIndex};
+
+// Per player state which is kept while the other player plays, in 2 players mode.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub stage: u16,
+    pub left_ship: u32,
+    pub capture_state: CaptureState,
+    pub capture_enemy_fi: FormationIndex,
+    pub destroyed: Vec<FormationIndex>,  // Enemies which don't appear again in the stage.
+    pub hit_count: u32,
+    #[serde(default)]
+    pub rank: DifficultyRank,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        Self {
+            stage: 0,
+            left_ship: DEFAULT_LEFT_SHIP,
+            capture_state: CaptureState::NoCapture,
+            capture_enemy_fi: FormationIndex(0, 0),
+            destroyed: Vec::new(),
+            hit_count: 0,
+            rank: DifficultyRank::default(),
+        }
+    }
+}
+
+impl PlayerState {
+    pub fn new(
+        stage: u16, left_ship: u32, capture_state: CaptureState, capture_enemy_fi: FormationIndex,
+        destroyed: Vec<FormationIndex>, hit_count: u32, rank: DifficultyRank,
+    ) -> Self {
+        // Only a captured fighter survives the switch, attacking enemies are cleared.
+        let (capture_state, capture_enemy_fi) = if capture_state == CaptureState::Captured {
+            (capture_state, capture_enemy_fi)
+        } else {
+            (CaptureState::NoCapture, FormationIndex(0, 0))
+        };
+        Self { stage, left_ship, capture_state, capture_enemy_fi, destroyed, hit_count, rank }
+    }
+}
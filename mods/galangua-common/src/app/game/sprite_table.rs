@@ -0,0 +1,74 @@
+use crate::app::game::effect_table::*;
+
+// Sprites in the sprite sheet, to restore sprite names from save states.
+pub const SPRITE_NAMES: [&str; 64] = [
+    "beam00", "beam01", "beam02", "beam03", "beam04", "beam05",
+    "beam06", "beam07", "beam08", "beam09", "beam10", "beam11",
+    "beam12", "beam13", "beam14", "beam15", "beam16", "beam17",
+    "beam18", "beam19", "beam20", "beam21", "beam22", "beam23",
+    "beam24", "beam25", "beam26", "beam27", "beam28", "cpp11",
+    "cpp12", "cpp21", "cpp22", "cpp_flash", "dman1", "dman2",
+    "dman_flash", "ene_exp1", "ene_exp2", "ene_exp3", "ene_exp4", "ene_exp5",
+    "ene_shot", "flag1", "flag10", "flag20", "flag30", "flag5",
+    "flag50", "gopher1", "gopher2", "gopher_flash", "myshot", "pl_exp1",
+    "pl_exp2", "pl_exp3", "pl_exp4", "pts1000", "pts1600", "pts400",
+    "pts800", "rustacean", "rustacean_captured", "rustacean_flash",
+];
+
+pub fn find_sprite_name(name: &str) -> Option<&'static str> {
+    SPRITE_NAMES.iter()
+        .find(|sprite_name| **sprite_name == name)
+        .copied()
+}
+
+// Sprite animations, to restore them from save states.
+const SPRITE_ANIME_TABLES: [&[&str]; 10] = [
+    &FLASH_ENEMY_SPRITE_TABLE[0],
+    &FLASH_ENEMY_SPRITE_TABLE[1],
+    &FLASH_ENEMY_SPRITE_TABLE[2],
+    &FLASH_ENEMY_SPRITE_TABLE[3],
+    &EARNED_POINT_SPRITE_TABLE[0],
+    &EARNED_POINT_SPRITE_TABLE[1],
+    &EARNED_POINT_SPRITE_TABLE[2],
+    &EARNED_POINT_SPRITE_TABLE[3],
+    &ENEMY_EXPLOSION_SPRITE_TABLE,
+    &PLAYER_EXPLOSION_SPRITE_TABLE,
+];
+
+// Finds the animation which `sprites` is a tail of, because ECS version advances the animation by slicing.
+pub fn find_sprite_anime<S: AsRef<str>>(sprites: &[S]) -> Option<&'static [&'static str]> {
+    SPRITE_ANIME_TABLES.iter()
+        .find(|table| table.len() >= sprites.len() &&
+              table[table.len() - sprites.len()..].iter().zip(sprites).all(|(a, b)| *a == b.as_ref()))
+        .map(|table| &table[table.len() - sprites.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprite_sheet() {
+        let sheet: serde_json::Value = serde_json::from_str(include_str!("../../../../../assets/chr.json")).unwrap();
+        let mut names: Vec<&str> = sheet["frames"].as_object().unwrap().keys()
+            .map(|key| key.strip_suffix(".png").unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(SPRITE_NAMES.to_vec(), names);
+
+        for table in SPRITE_ANIME_TABLES.iter() {
+            assert!(table.iter().all(|name| find_sprite_name(name).is_some()), "{:?}", table);
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(Some("gopher1"), find_sprite_name(&String::from("gopher1")));
+        assert_eq!(None, find_sprite_name("gopher3"));
+
+        assert_eq!(Some(&ENEMY_EXPLOSION_SPRITE_TABLE[..]), find_sprite_anime(&ENEMY_EXPLOSION_SPRITE_TABLE));
+        assert_eq!(Some(&["ene_exp4", "ene_exp5"][..]), find_sprite_anime(&["ene_exp4", "ene_exp5"]));
+        assert_eq!(Some(&["pts800"][..]), find_sprite_anime(&[String::from("pts800")]));
+        assert_eq!(None, find_sprite_anime(&["ene_exp3", "ene_exp5"]));
+    }
+}
@@ -0,0 +1,425 @@
+use serde_json::Value;
+use std::cmp::min;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::app::game::appearance_table::*;
+use crate::app::game::formation_table::{X_COUNT, Y_COUNT};
+use crate::app::game::traj_command::TrajCommand;
+use crate::app::game::traj_command_table::find_traj_table;
+use crate::app::game::traj_script::parse_traj_script;
+use crate::app::game::{EnemyType, FormationIndex};
+
+const PATTERN_COUNT: u64 = 4;
+const MAX_ASSAULT_COUNT: u64 = (X_COUNT / 2) as u64;
+const MAX_SHOT_ENABLE_COUNT: u64 = 8;
+
+// Stage waves, which is read by `AppearanceManager`.
+// `units` cycles through stages, `assault` and `shot_enable` use the last row after they run out.
+// Challenging stages use `challenging_units` and `challenging_enemy_types` instead, without assaults or shots.
+// `traj` of a unit is a table name, or an inline script given as an array of lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StageDefinition {
+    pub order: Vec<FormationIndex>,
+    pub enemy_types: Vec<[EnemyType; 2]>,
+    pub units: Vec<Vec<UnitTableEntry<'static>>>,
+    pub assault: Vec<Vec<u32>>,
+    pub shot_enable: Vec<Vec<u32>>,
+    pub challenging_enemy_types: Vec<[EnemyType; 2]>,
+    pub challenging_units: Vec<UnitTableEntry<'static>>,
+}
+
+impl Default for StageDefinition {
+    fn default() -> Self {
+        Self {
+            order: ORDER.to_vec(),
+            enemy_types: ENEMY_TYPE_TABLE.chunks(2).map(|pair| [pair[0], pair[1]]).collect(),
+            units: UNIT_TABLE.iter().map(|units| units.to_vec()).collect(),
+            assault: ASSAULT_TABLE.iter().map(|row| row.to_vec()).collect(),
+            shot_enable: SHOT_ENABLE_TABLE.iter().map(|row| row.to_vec()).collect(),
+            challenging_enemy_types: CHALLENGING_ENEMY_TYPE_TABLE.chunks(2).map(|pair| [pair[0], pair[1]]).collect(),
+            challenging_units: CHALLENGING_UNIT_TABLE.to_vec(),
+        }
+    }
+}
+
+impl StageDefinition {
+    pub fn from_json_str(text: &str) -> Result<Self, StageDefinitionError> {
+        let value: Value = serde_json::from_str(text).map_err(|e| {
+            let message = e.to_string();
+            // Position is held separately.
+            let message = message.split(" at line ").next().unwrap_or(&message);
+            StageDefinitionError::new(e.line(), e.column(), "", message)
+        })?;
+        parse_definition(&value).map_err(|(field, message)| {
+            let (line, column) = find_position(&index_positions(text), &field);
+            StageDefinitionError::new(line, column, &field, &message)
+        })
+    }
+
+    pub fn unit(&self, stage: u16, unit: u32) -> &UnitTableEntry<'static> {
+        &self.units[stage as usize % self.units.len()][unit as usize]
+    }
+
+    pub fn enemy_types(&self, unit: u32) -> &[EnemyType; 2] {
+        &self.enemy_types[unit as usize]
+    }
+
+    pub fn assault_count(&self, stage: u16, unit: u32) -> u32 {
+        self.assault[min(stage as usize, self.assault.len() - 1)][unit as usize]
+    }
+
+    pub fn shot_enable_count(&self, stage: u16, unit: u32) -> u32 {
+        self.shot_enable[min(stage as usize, self.shot_enable.len() - 1)][unit as usize]
+    }
+
+    pub fn challenging_unit(&self, unit: u32) -> &UnitTableEntry<'static> {
+        &self.challenging_units[unit as usize]
+    }
+
+    pub fn challenging_enemy_types(&self, unit: u32) -> &[EnemyType; 2] {
+        &self.challenging_enemy_types[unit as usize]
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StageDefinitionError {
+    pub line: usize,
+    pub column: usize,
+    pub field: String,  // e.g. "units[1][0].traj", empty for syntax errors.
+    pub message: String,
+}
+
+impl StageDefinitionError {
+    fn new(line: usize, column: usize, field: &str, message: &str) -> Self {
+        Self { line, column, field: String::from(field), message: String::from(message) }
+    }
+}
+
+impl fmt::Display for StageDefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+        } else {
+            write!(f, "line {}, column {}: {}: {}", self.line, self.column, self.field, self.message)
+        }
+    }
+}
+
+// Validation error: (field, message)
+type FieldError = (String, String);
+
+fn parse_definition(value: &Value) -> Result<StageDefinition, FieldError> {
+    let root = value.as_object().ok_or_else(|| (String::new(), String::from("object expected")))?;
+    const FIELDS: [&str; 7] = ["order", "enemy_types", "units", "assault", "shot_enable", "challenging_enemy_types", "challenging_units"];
+    if let Some(key) = root.keys().find(|key| !FIELDS.contains(&key.as_str())) {
+        return Err((key.clone(), String::from("unknown field")));
+    }
+
+    let order = parse_array(&value["order"], "order", Some(8 * UNIT_COUNT), |value, field| {
+        let x = parse_u64(&value[0], &format!("{}[0]", field), X_COUNT as u64 - 1)?;
+        let y = parse_u64(&value[1], &format!("{}[1]", field), Y_COUNT as u64 - 1)?;
+        Ok(FormationIndex(x as u8, y as u8))
+    })?;
+    if let Some(i) = (1..order.len()).find(|&i| order[..i].contains(&order[i])) {
+        return Err((format!("order[{}]", i), format!("duplicated ({}, {})", order[i].0, order[i].1)));
+    }
+    let enemy_types = parse_array(&value["enemy_types"], "enemy_types", Some(UNIT_COUNT), parse_enemy_type_pair)?;
+    let units = parse_array(&value["units"], "units", None, |value, field| {
+        parse_array(value, field, Some(UNIT_COUNT), parse_unit)
+    })?;
+    let assault = parse_array(&value["assault"], "assault", None, |value, field| {
+        parse_array(value, field, Some(UNIT_COUNT), |value, field| parse_u64(value, field, MAX_ASSAULT_COUNT).map(|n| n as u32))
+    })?;
+    let shot_enable = parse_array(&value["shot_enable"], "shot_enable", None, |value, field| {
+        parse_array(value, field, Some(UNIT_COUNT), |value, field| parse_u64(value, field, MAX_SHOT_ENABLE_COUNT).map(|n| n as u32))
+    })?;
+    let challenging_enemy_types = parse_array(&value["challenging_enemy_types"], "challenging_enemy_types", Some(UNIT_COUNT), parse_enemy_type_pair)?;
+    let challenging_units = parse_array(&value["challenging_units"], "challenging_units", Some(UNIT_COUNT), parse_unit)?;
+
+    Ok(StageDefinition { order, enemy_types, units, assault, shot_enable, challenging_enemy_types, challenging_units })
+}
+
+fn parse_array<T, F>(value: &Value, field: &str, len: Option<usize>, f: F) -> Result<Vec<T>, FieldError>
+where
+    F: Fn(&Value, &str) -> Result<T, FieldError>,
+{
+    let array = value.as_array().ok_or_else(|| (String::from(field), String::from("array expected")))?;
+    match len {
+        Some(len) if array.len() != len => {
+            return Err((String::from(field), format!("expected {} entries, got {}", len, array.len())));
+        }
+        None if array.is_empty() => {
+            return Err((String::from(field), String::from("empty")));
+        }
+        _ => {}
+    }
+    array.iter().enumerate()
+        .map(|(i, elem)| f(elem, &format!("{}[{}]", field, i)))
+        .collect()
+}
+
+fn parse_u64(value: &Value, field: &str, max: u64) -> Result<u64, FieldError> {
+    match value.as_u64() {
+        Some(n) if n <= max => Ok(n),
+        Some(n) => Err((String::from(field), format!("{} out of range (max {})", n, max))),
+        None => Err((String::from(field), String::from("unsigned integer expected"))),
+    }
+}
+
+fn parse_enemy_type(value: &Value, field: &str) -> Result<EnemyType, FieldError> {
+    match value.as_str() {
+        Some("bee") => Ok(EnemyType::Bee),
+        Some("butterfly") => Ok(EnemyType::Butterfly),
+        Some("owl") => Ok(EnemyType::Owl),
+        Some(name) => Err((String::from(field), format!("unknown enemy type `{}`", name))),
+        None => Err((String::from(field), String::from("string expected"))),
+    }
+}
+
+fn parse_enemy_type_pair(value: &Value, field: &str) -> Result<[EnemyType; 2], FieldError> {
+    let pair = parse_array(value, field, Some(2), parse_enemy_type)?;
+    Ok([pair[0], pair[1]])
+}
+
+fn parse_unit(value: &Value, field: &str) -> Result<UnitTableEntry<'static>, FieldError> {
+    let object = value.as_object().ok_or_else(|| (String::from(field), String::from("object expected")))?;
+    if let Some(key) = object.keys().find(|key| !["pattern", "traj", "flip_x"].contains(&key.as_str())) {
+        return Err((format!("{}.{}", field, key), String::from("unknown field")));
+    }
+
+    let pat = parse_u64(&value["pattern"], &format!("{}.pattern", field), PATTERN_COUNT - 1)? as usize;
+    let traj_field = format!("{}.traj", field);
+    let table = match &value["traj"] {
+        Value::String(name) => find_traj_table(name).ok_or_else(|| (traj_field, format!("unknown traj `{}`", name)))?,
+        Value::Array(_) => parse_inline_traj(&value["traj"], &traj_field)?,
+        _ => return Err((traj_field, String::from("string expected"))),
+    };
+    let flip_x = match &value["flip_x"] {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        _ => return Err((format!("{}.flip_x", field), String::from("bool expected"))),
+    };
+    Ok(UnitTableEntry { pat, table, flip_x })
+}
+
+// Each element is a line of the script, so errors point to the line in JSON.
+// Leaked to share `UnitTableEntry<'static>` with the built-in tables, definitions are loaded only at startup.
+fn parse_inline_traj(value: &Value, field: &str) -> Result<&'static [TrajCommand], FieldError> {
+    let lines = parse_array(value, field, None, |value, field| {
+        value.as_str().map(String::from).ok_or_else(|| (String::from(field), String::from("string expected")))
+    })?;
+    let commands = parse_traj_script(&lines.join("\n")).map_err(|e| {
+        let line = e.line();
+        if line >= 1 && line <= lines.len() {
+            (format!("{}[{}]", field, line - 1), e.to_string())
+        } else {
+            (String::from(field), e.to_string())
+        }
+    })?;
+    Ok(Box::leak(commands.into_boxed_slice()))
+}
+
+// Falls back to the parent, for a missing field.
+fn find_position(positions: &HashMap<String, (usize, usize)>, field: &str) -> (usize, usize) {
+    let mut path = field;
+    loop {
+        if let Some(&position) = positions.get(path) {
+            return position;
+        }
+        match path.rfind(['.', '[']) {
+            Some(i) => path = &path[..i],
+            None => return positions.get("").copied().unwrap_or((1, 1)),
+        }
+    }
+}
+
+// Records where each value starts, keyed by field path like "units[1][0].traj".
+// Assumes `text` is valid JSON.
+fn index_positions(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut scanner = Scanner { chars: text.chars().peekable(), line: 1, column: 1, positions: HashMap::new() };
+    scanner.scan_value(String::new());
+    scanner.positions
+}
+
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl<'a> Scanner<'a> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) -> Option<char> {
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                return Some(c);
+            }
+            self.next();
+        }
+        None
+    }
+
+    fn scan_value(&mut self, path: String) {
+        let c = match self.skip_whitespace() {
+            Some(c) => c,
+            None => return,
+        };
+        self.positions.insert(path.clone(), (self.line, self.column));
+        match c {
+            '{' => {
+                self.next();
+                while let Some(c) = self.skip_whitespace() {
+                    self.next();
+                    if c == '}' {
+                        break;
+                    }
+                    if c == '"' {
+                        let key = self.scan_string();
+                        self.skip_whitespace();
+                        self.next();  // ':'
+                        let child = if path.is_empty() { key } else { format!("{}.{}", path, key) };
+                        self.scan_value(child);
+                    }
+                }
+            }
+            '[' => {
+                self.next();
+                let mut index = 0;
+                loop {
+                    match self.skip_whitespace() {
+                        Some(']') => { self.next(); break; }
+                        Some(',') => { self.next(); }
+                        Some(_) => {
+                            self.scan_value(format!("{}[{}]", path, index));
+                            index += 1;
+                        }
+                        None => break,
+                    }
+                }
+            }
+            '"' => {
+                self.next();
+                self.scan_string();
+            }
+            _ => {
+                while let Some(&c) = self.chars.peek() {
+                    if c == ',' || c == ']' || c == '}' || c.is_whitespace() {
+                        break;
+                    }
+                    self.next();
+                }
+            }
+        }
+    }
+
+    // Called after the opening quote.
+    fn scan_string(&mut self) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                '"' => break,
+                '\\' => { self.next(); }
+                _ => s.push(c),
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::game::traj_command_table::COMMAND_TABLE1;
+    use crate::app::game::traj_script::to_traj_script;
+
+    fn load_asset() -> String {
+        std::fs::read_to_string("../../assets/stages.json").unwrap()
+    }
+
+    fn line_of(text: &str, pattern: &str) -> usize {
+        text.lines().position(|line| line.contains(pattern)).unwrap() + 1
+    }
+
+    #[test]
+    fn test_asset_is_same_as_default() {
+        assert_eq!(Ok(StageDefinition::default()), StageDefinition::from_json_str(&load_asset()));
+    }
+
+    #[test]
+    fn test_error_position() {
+        let text = load_asset().replacen(r#""COMMAND_TABLE3""#, r#""NO_SUCH_TABLE""#, 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!((line_of(&text, "NO_SUCH_TABLE"), "units[1][0].traj", "unknown traj `NO_SUCH_TABLE`"),
+                   (err.line, err.field.as_str(), err.message.as_str()));
+
+        let text = load_asset().replacen(r#""owl""#, r#""boss""#, 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!((line_of(&text, "boss"), "enemy_types[1][0]"), (err.line, err.field.as_str()));
+    }
+
+    #[test]
+    fn test_validation() {
+        let err = StageDefinition::from_json_str(r#"{"order": [[10, 0]]}"#).unwrap_err();
+        assert_eq!(("order", "expected 40 entries, got 1"), (err.field.as_str(), err.message.as_str()));
+
+        let text = load_asset().replacen(r#""traj": "COMMAND_TABLE2", "#, "", 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!((line_of(&text, r#"{"pattern": 1, "flip_x""#), "units[0][1].traj", "string expected"),
+                   (err.line, err.field.as_str(), err.message.as_str()));
+
+        let err = StageDefinition::from_json_str("{\n  \"order\": [\n").unwrap_err();
+        assert_eq!(("", 3), (err.field.as_str(), err.line));
+
+        let text = load_asset().replacen("[9, 4]", "[10, 4]", 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!((line_of(&text, "[10, 4]"), 6, "order[32][0]"), (err.line, err.column, err.field.as_str()));
+
+        let text = load_asset().replacen(r#""CHALLENGING_TABLE3", "flip_x": true"#, r#""CHALLENGING_TABLE3", "flip_x": 1"#, 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!(("challenging_units[4].flip_x", "bool expected"), (err.field.as_str(), err.message.as_str()));
+    }
+
+    #[test]
+    fn test_duplicated_order() {
+        let text = load_asset().replacen("[1, 5]", "[4, 2]", 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!((line_of(&text, "[0, 5], [4, 2]"), "order[39]", "duplicated (4, 2)"),
+                   (err.line, err.field.as_str(), err.message.as_str()));
+    }
+
+    #[test]
+    fn test_inline_traj() {
+        let script = to_traj_script(&COMMAND_TABLE1);
+        let lines = serde_json::to_string_pretty(&script.lines().collect::<Vec<_>>()).unwrap();
+        let text = load_asset().replacen(r#""COMMAND_TABLE1""#, &lines, 1);
+        assert_eq!(Ok(StageDefinition::default()), StageDefinition::from_json_str(&text));
+
+        let text = load_asset().replacen(r#""COMMAND_TABLE1""#, r#"[
+            "Speed 3",
+            "Bogus 1"
+        ]"#, 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!((line_of(&text, "Bogus"), "units[0][0].traj[1]", "Line 2: unknown command `Bogus`"),
+                   (err.line, err.field.as_str(), err.message.as_str()));
+
+        let text = load_asset().replacen(r#""COMMAND_TABLE1""#, r#"["repeat 2", "Delay 1"]"#, 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!(("units[0][0].traj[0]", "Line 1: `repeat` without `end`"), (err.field.as_str(), err.message.as_str()));
+
+        let text = load_asset().replacen(r#""COMMAND_TABLE1""#, r#"["Delay 1", 2]"#, 1);
+        let err = StageDefinition::from_json_str(&text).unwrap_err();
+        assert_eq!(("units[0][0].traj[1]", "string expected"), (err.field.as_str(), err.message.as_str()));
+    }
+}
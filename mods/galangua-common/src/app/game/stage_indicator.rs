@@ -1,4 +1,5 @@
 use counted_array::counted_array;
+use serde::{Deserialize, Serialize};
 
 use crate::app::consts::*;
 use crate::framework::types::Vec2I;
@@ -11,7 +12,7 @@ const FLAG10_WIDTH: u16 = 16;
 const FLAG5_WIDTH: u16 = 8;
 const FLAG1_WIDTH: u16 = 8;
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct StageIndicator {
     stage: u16,
     wait: u32,
@@ -1,8 +1,10 @@
 use array_macro::*;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_xoshiro::Xoshiro128Plus;
+use serde::{Deserialize, Serialize};
 
 use crate::app::consts::*;
+use crate::app::util::snapshot;
 use crate::framework::types::Vec2I;
 use crate::framework::RendererTrait;
 use crate::util::math::{round_vec, ONE};
@@ -11,24 +13,25 @@ const STAR_COUNT: usize = 256;
 const MAX_SPEED: i32 = ONE;
 const REVERSE_SPEED: i32 = -3 * ONE;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum State {
     Stop,
     Normal,
     Capturing,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StarManager {
     state: State,
     frame_count: i32,
     scroll_vel: i32,
+    #[serde(with = "snapshot::array")]
     stars: [Star; STAR_COUNT],
+    rng: Xoshiro128Plus,
 }
 
-impl Default for StarManager {
-    fn default() -> Self {
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
+impl StarManager {
+    pub fn new(mut rng: Xoshiro128Plus) -> Self {
         let stars = array![|_i|
             Star {
                 pos: Vec2I::new(rng.gen_range(0, WIDTH) * ONE,
@@ -43,11 +46,10 @@ impl Default for StarManager {
             frame_count: 0,
             scroll_vel: 0,
             stars,
+            rng,
         }
     }
-}
 
-impl StarManager {
     pub fn update(&mut self) {
         self.frame_count = (self.frame_count + 1) & 63;
         if self.state != State::Stop && self.scroll_vel < MAX_SPEED {
@@ -55,7 +57,7 @@ impl StarManager {
         }
 
         let capturing = self.state == State::Capturing;
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
+        let rng = &mut self.rng;
         let vy = if capturing { REVERSE_SPEED } else { self.scroll_vel };
         for star in self.stars.iter_mut() {
             let mut y = star.pos.y + vy;
@@ -70,7 +72,7 @@ impl StarManager {
             star.pos.y = y;
             if warp {
                 star.pos.x = rng.gen_range(0, WIDTH) * ONE;
-                star.c = choose_random_color(&mut rng);
+                star.c = choose_random_color(rng);
                 star.t = rng.gen_range(0, 64);
             }
         }
@@ -105,7 +107,7 @@ impl StarManager {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct Star {
     pos: Vec2I,
     t: i32,
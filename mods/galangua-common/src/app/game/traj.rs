@@ -1,7 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 use crate::app::consts::*;
 use crate::app::game::traj_command::TrajCommand;
 use crate::app::game::traj_command::TrajCommand::*;
 use crate::app::game::FormationIndex;
+use crate::app::util::snapshot;
 use crate::framework::types::{Vec2I, ZERO_VEC};
 use crate::util::math::{calc_velocity, ANGLE, COS_TABLE, ONE, SIN_TABLE};
 
@@ -10,11 +13,13 @@ pub trait Accessor {
     fn get_stage_no(&self) -> u16;
 }
 
+#[derive(Serialize, Deserialize)]
 enum WaitPred {
     WaitYG(i32),
 }
 
 // Trajectory
+#[derive(Serialize, Deserialize)]
 pub struct Traj {
     pos: Vec2I,
     pub angle: i32,
@@ -25,12 +30,14 @@ pub struct Traj {
     fi: FormationIndex,
     pub shot_enable: bool,
 
+    #[serde(with = "snapshot::traj_table")]
     command_table: &'static [TrajCommand],
     delay: u32,
     wait_pred: Option<WaitPred>,
     shot: Option<u32>,
 
     #[cfg(debug_assertions)]
+    #[serde(skip)]
     command_table_vec: Option<Vec<TrajCommand>>,
 }
 
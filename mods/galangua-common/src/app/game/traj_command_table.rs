@@ -212,3 +212,72 @@ counted_array!(pub const OWL_RUSH_ATTACK_TABLE: [TrajCommand; _] = [
     CopyFormationX,
     Angle((ANGLE / 2) * ONE),
 ]);
+
+// Challenging stage: fly through and leave the screen.
+
+counted_array!(pub const CHALLENGING_TABLE1: [TrajCommand; _] = [
+    Pos((WIDTH / 2 + 24) * ONE, -8 * ONE),
+    Speed(3 * ONE),
+    Angle((ANGLE / 2) * ONE),
+    VAngle(0),
+    Delay(50),
+    VAngle(2 * ONE),
+    Delay(128),
+    VAngle(0),
+    Delay(70),
+]);
+
+counted_array!(pub const CHALLENGING_TABLE2: [TrajCommand; _] = [
+    Pos(-8 * ONE, 244 * ONE),
+    Speed(3 * ONE),
+    Angle((ANGLE / 4) * ONE),
+    VAngle(-2 * ONE),
+    Delay(32),
+    VAngle(0),
+    Delay(30),
+    VAngle(2 * ONE),
+    Delay(64),
+    VAngle(0),
+    Delay(80),
+]);
+
+counted_array!(pub const CHALLENGING_TABLE3: [TrajCommand; _] = [
+    Pos((WIDTH / 2 + 40) * ONE, -8 * ONE),
+    Speed(3 * ONE),
+    Angle((ANGLE / 2) * ONE),
+    VAngle(0),
+    Delay(30),
+    VAngle(2 * ONE),
+    Delay(96),
+    VAngle(0),
+    Delay(70),
+]);
+
+counted_array!(pub const TRAJ_TABLES: [(&str, &[TrajCommand]); _] = [
+    ("COMMAND_TABLE1", &COMMAND_TABLE1),
+    ("COMMAND_TABLE2", &COMMAND_TABLE2),
+    ("COMMAND_TABLE3", &COMMAND_TABLE3),
+    ("BEE_ATTACK_TABLE", &BEE_ATTACK_TABLE),
+    ("BEE_ATTACK_RUSH_CONT_TABLE", &BEE_ATTACK_RUSH_CONT_TABLE),
+    ("BUTTERFLY_ATTACK_TABLE", &BUTTERFLY_ATTACK_TABLE),
+    ("OWL_ATTACK_TABLE", &OWL_ATTACK_TABLE),
+    ("BEE_RUSH_ATTACK_TABLE", &BEE_RUSH_ATTACK_TABLE),
+    ("BUTTERFLY_RUSH_ATTACK_TABLE", &BUTTERFLY_RUSH_ATTACK_TABLE),
+    ("OWL_RUSH_ATTACK_TABLE", &OWL_RUSH_ATTACK_TABLE),
+    ("CHALLENGING_TABLE1", &CHALLENGING_TABLE1),
+    ("CHALLENGING_TABLE2", &CHALLENGING_TABLE2),
+    ("CHALLENGING_TABLE3", &CHALLENGING_TABLE3),
+]);
+
+pub fn find_traj_table(name: &str) -> Option<&'static [TrajCommand]> {
+    TRAJ_TABLES.iter()
+        .find(|(table_name, _)| *table_name == name)
+        .map(|(_, table)| *table)
+}
+
+// Finds the table which `commands` is a tail of, and returns its name and the offset.
+pub fn find_traj_table_position(commands: &[TrajCommand]) -> Option<(&'static str, usize)> {
+    TRAJ_TABLES.iter()
+        .find(|(_, table)| table.ends_with(commands))
+        .map(|(name, table)| (*name, table.len() - commands.len()))
+}
@@ -0,0 +1,360 @@
+// Text format for trajectory commands.
+//
+//   # Comment, until the end of line.
+//   Speed 2.5          # Length in dots, 1.0 = ONE
+//   Angle 128          # Angle in ANGLE units (256 = 360 degrees), or with `deg` suffix: 180deg
+//   Delay 10           # Frame count
+//   top:               # Label
+//   VAngle 3
+//   Delay 4
+//   loop top 2         # Repeat from the label, 2 more times
+//   repeat 3           # Repeat the block 3 times
+//     Shot 8
+//   end
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::traj_command::TrajCommand;
+use crate::util::math::{ANGLE, ONE};
+
+const MAX_COMMAND_COUNT: usize = 10000;
+const DEGREE_SUFFIX: &str = "deg";
+
+#[derive(Debug, PartialEq)]
+pub enum TrajScriptError {
+    UnknownCommand { line: usize, name: String },
+    ArgumentCount { line: usize, command: String, expected: usize },
+    InvalidNumber { line: usize, text: String },
+    InvalidUnit { line: usize, text: String },
+    DuplicateLabel { line: usize, label: String },
+    UndefinedLabel { line: usize, label: String },
+    UnmatchedEnd { line: usize },
+    UnclosedRepeat { line: usize },
+    TooManyCommands { line: usize },
+}
+
+impl TrajScriptError {
+    pub fn line(&self) -> usize {
+        match *self {
+            TrajScriptError::UnknownCommand { line, .. } |
+            TrajScriptError::ArgumentCount { line, .. } |
+            TrajScriptError::InvalidNumber { line, .. } |
+            TrajScriptError::InvalidUnit { line, .. } |
+            TrajScriptError::DuplicateLabel { line, .. } |
+            TrajScriptError::UndefinedLabel { line, .. } |
+            TrajScriptError::UnmatchedEnd { line } |
+            TrajScriptError::UnclosedRepeat { line } |
+            TrajScriptError::TooManyCommands { line } => line,
+        }
+    }
+}
+
+impl fmt::Display for TrajScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: ", self.line())?;
+        match self {
+            TrajScriptError::UnknownCommand { name, .. } => write!(f, "unknown command `{}`", name),
+            TrajScriptError::ArgumentCount { command, expected, .. } => write!(f, "`{}` takes {} argument(s)", command, expected),
+            TrajScriptError::InvalidNumber { text, .. } => write!(f, "number expected, but `{}`", text),
+            TrajScriptError::InvalidUnit { text, .. } => write!(f, "unit is not allowed here: `{}`", text),
+            TrajScriptError::DuplicateLabel { label, .. } => write!(f, "duplicate label `{}`", label),
+            TrajScriptError::UndefinedLabel { label, .. } => write!(f, "undefined label `{}`", label),
+            TrajScriptError::UnmatchedEnd { .. } => write!(f, "`end` without `repeat`"),
+            TrajScriptError::UnclosedRepeat { .. } => write!(f, "`repeat` without `end`"),
+            TrajScriptError::TooManyCommands { .. } => write!(f, "too many commands, over {}", MAX_COMMAND_COUNT),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Arg {
+    Length,  // dots, scaled by ONE
+    Angle,   // ANGLE units or degrees, scaled by ONE
+    Frame,
+}
+
+fn command_args(name: &str) -> Option<&'static [Arg]> {
+    let args: &'static [Arg] = match name {
+        "Pos" | "AddPos" => &[Arg::Length, Arg::Length],
+        "Speed" | "WaitYG" => &[Arg::Length],
+        "Angle" | "VAngle" => &[Arg::Angle],
+        "DestAngle" => &[Arg::Angle, Arg::Length],
+        "Delay" | "Shot" => &[Arg::Frame],
+        "Accelerate" | "CopyFormationX" => &[],
+        _ => return None,
+    };
+    Some(args)
+}
+
+fn build_command(name: &str, v: &[i32]) -> TrajCommand {
+    match name {
+        "Pos" => TrajCommand::Pos(v[0], v[1]),
+        "Speed" => TrajCommand::Speed(v[0]),
+        "Angle" => TrajCommand::Angle(v[0]),
+        "VAngle" => TrajCommand::VAngle(v[0]),
+        "Delay" => TrajCommand::Delay(v[0] as u32),
+        "Accelerate" => TrajCommand::Accelerate,
+        "DestAngle" => TrajCommand::DestAngle(v[0], v[1]),
+        "WaitYG" => TrajCommand::WaitYG(v[0]),
+        "AddPos" => TrajCommand::AddPos(v[0], v[1]),
+        "CopyFormationX" => TrajCommand::CopyFormationX,
+        "Shot" => TrajCommand::Shot(v[0] as u32),
+        _ => panic!("Illegal command: {}", name),
+    }
+}
+
+fn parse_arg(word: &str, arg: Arg, line: usize) -> Result<i32, TrajScriptError> {
+    let invalid_number = || TrajScriptError::InvalidNumber { line, text: String::from(word) };
+    match arg {
+        Arg::Frame => word.parse::<u32>().ok().and_then(|n| i32::try_from(n).ok()).ok_or_else(invalid_number),
+        Arg::Length | Arg::Angle => {
+            let (number, scale) = match word.strip_suffix(DEGREE_SUFFIX) {
+                Some(number) => {
+                    if let Arg::Length = arg {
+                        return Err(TrajScriptError::InvalidUnit { line, text: String::from(word) });
+                    }
+                    (number, (ANGLE * ONE) as f64 / 360.0)
+                }
+                None => (word, ONE as f64),
+            };
+            // `f64` also takes `NaN`, `inf` and too large exponents, which can't be stored.
+            let value = number.parse::<f64>().map_err(|_| invalid_number())? * scale;
+            if !value.is_finite() || value.round() < i32::MIN as f64 || value.round() > i32::MAX as f64 {
+                return Err(invalid_number());
+            }
+            Ok(value.round() as i32)
+        }
+    }
+}
+
+// Counts over `MAX_COMMAND_COUNT` are rejected, not to spin on an empty body.
+fn parse_count(word: &str, line: usize) -> Result<usize, TrajScriptError> {
+    let count = word.parse::<usize>().map_err(|_| TrajScriptError::InvalidNumber { line, text: String::from(word) })?;
+    if count > MAX_COMMAND_COUNT {
+        return Err(TrajScriptError::TooManyCommands { line });
+    }
+    Ok(count)
+}
+
+fn check_count(commands: &[TrajCommand], line: usize) -> Result<(), TrajScriptError> {
+    if commands.len() > MAX_COMMAND_COUNT {
+        Err(TrajScriptError::TooManyCommands { line })
+    } else {
+        Ok(())
+    }
+}
+
+// Checks the size before expanding, not to allocate a huge buffer.
+fn append_repeated(commands: &mut Vec<TrajCommand>, body: &[TrajCommand], count: usize, line: usize) -> Result<(), TrajScriptError> {
+    if commands.len().saturating_add(body.len().saturating_mul(count)) > MAX_COMMAND_COUNT {
+        return Err(TrajScriptError::TooManyCommands { line });
+    }
+    for _ in 0..count {
+        commands.extend_from_slice(body);
+    }
+    Ok(())
+}
+
+// Commands in a `repeat` block, or the whole script.
+#[derive(Default)]
+struct Block {
+    commands: Vec<TrajCommand>,
+    labels: HashMap<String, usize>,  // label -> index of `commands`
+    line: usize,
+    count: usize,
+}
+
+// Loops are expanded while parsing, because `Traj` runs commands only forward.
+pub fn parse_traj_script(text: &str) -> Result<Vec<TrajCommand>, TrajScriptError> {
+    let mut blocks = vec![Block { count: 1, ..Block::default() }];
+
+    for (i, raw_line) in text.lines().enumerate() {
+        let line = i + 1;
+        let words: Vec<&str> = raw_line.split('#').next().unwrap().split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        let Block { commands, labels, .. } = blocks.last_mut().unwrap();
+        match words[0] {
+            word if word.ends_with(':') && words.len() == 1 => {
+                let label = String::from(&word[..word.len() - 1]);
+                if labels.insert(label.clone(), commands.len()).is_some() {
+                    return Err(TrajScriptError::DuplicateLabel { line, label });
+                }
+            }
+            "loop" => {
+                if words.len() != 3 {
+                    return Err(TrajScriptError::ArgumentCount { line, command: String::from("loop"), expected: 2 });
+                }
+                let start = *labels.get(words[1])
+                    .ok_or_else(|| TrajScriptError::UndefinedLabel { line, label: String::from(words[1]) })?;
+                let count = parse_count(words[2], line)?;
+                let body = commands[start..].to_vec();
+                append_repeated(commands, &body, count, line)?;
+            }
+            "repeat" => {
+                if words.len() != 2 {
+                    return Err(TrajScriptError::ArgumentCount { line, command: String::from("repeat"), expected: 1 });
+                }
+                let count = parse_count(words[1], line)?;
+                blocks.push(Block { line, count, ..Block::default() });
+            }
+            "end" => {
+                if blocks.len() <= 1 {
+                    return Err(TrajScriptError::UnmatchedEnd { line });
+                }
+                let block = blocks.pop().unwrap();
+                let commands = &mut blocks.last_mut().unwrap().commands;
+                append_repeated(commands, &block.commands, block.count, line)?;
+            }
+            name => {
+                let args = command_args(name)
+                    .ok_or_else(|| TrajScriptError::UnknownCommand { line, name: String::from(name) })?;
+                if words.len() - 1 != args.len() {
+                    return Err(TrajScriptError::ArgumentCount { line, command: String::from(name), expected: args.len() });
+                }
+                let values = words[1..].iter().zip(args.iter())
+                    .map(|(word, &arg)| parse_arg(word, arg, line))
+                    .collect::<Result<Vec<i32>, _>>()?;
+                commands.push(build_command(name, &values));
+                check_count(commands, line)?;
+            }
+        }
+    }
+
+    if blocks.len() > 1 {
+        let line = blocks.pop().unwrap().line;
+        return Err(TrajScriptError::UnclosedRepeat { line });
+    }
+    Ok(blocks.pop().unwrap().commands)
+}
+
+fn format_fixed(value: i32) -> String {
+    // Every multiple of 1/ONE has a short exact decimal representation.
+    format!("{}", value as f64 / ONE as f64)
+}
+
+// Writes one command per line, which `parse_traj_script` reads back to the same commands.
+pub fn to_traj_script(commands: &[TrajCommand]) -> String {
+    let mut text = String::new();
+    for command in commands {
+        let line = match *command {
+            TrajCommand::Pos(x, y) => format!("Pos {} {}", format_fixed(x), format_fixed(y)),
+            TrajCommand::Speed(speed) => format!("Speed {}", format_fixed(speed)),
+            TrajCommand::Angle(angle) => format!("Angle {}", format_fixed(angle)),
+            TrajCommand::VAngle(vangle) => format!("VAngle {}", format_fixed(vangle)),
+            TrajCommand::Delay(delay) => format!("Delay {}", delay),
+            TrajCommand::Accelerate => String::from("Accelerate"),
+            TrajCommand::DestAngle(angle, radius) => format!("DestAngle {} {}", format_fixed(angle), format_fixed(radius)),
+            TrajCommand::WaitYG(y) => format!("WaitYG {}", format_fixed(y)),
+            TrajCommand::AddPos(x, y) => format!("AddPos {} {}", format_fixed(x), format_fixed(y)),
+            TrajCommand::CopyFormationX => String::from("CopyFormationX"),
+            TrajCommand::Shot(delay) => format!("Shot {}", delay),
+        };
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::game::traj_command_table::TRAJ_TABLES;
+
+    #[test]
+    fn test_round_trip() {
+        for (name, table) in TRAJ_TABLES.iter() {
+            let text = to_traj_script(table);
+            assert_eq!(Ok(table.to_vec()), parse_traj_script(&text), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_debug_files() {
+        for entry in std::fs::read_dir("../../debug").unwrap() {
+            let path = entry.unwrap().path();
+            let text = std::fs::read_to_string(&path).unwrap();
+            assert!(parse_traj_script(&text).is_ok(), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "
+            # Comment
+            Speed 2.5  # Trailing comment
+            Angle 180deg
+            DestAngle -64 12
+            Shot 8
+            Accelerate
+        ";
+        assert_eq!(Ok(vec![
+            TrajCommand::Speed(5 * ONE / 2),
+            TrajCommand::Angle(ANGLE / 2 * ONE),
+            TrajCommand::DestAngle(-64 * ONE, 12 * ONE),
+            TrajCommand::Shot(8),
+            TrajCommand::Accelerate,
+        ]), parse_traj_script(text));
+    }
+
+    #[test]
+    fn test_loop() {
+        let text = "
+            Delay 1
+            top:
+            VAngle 1
+            repeat 2
+              Shot 3
+            end
+            loop top 1
+        ";
+        let shot = TrajCommand::Shot(3);
+        let vangle = TrajCommand::VAngle(ONE);
+        assert_eq!(Ok(vec![
+            TrajCommand::Delay(1),
+            vangle.clone(), shot.clone(), shot.clone(),
+            vangle, shot.clone(), shot,
+        ]), parse_traj_script(text));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Err(TrajScriptError::UnknownCommand { line: 2, name: String::from("Jump") }),
+                   parse_traj_script("Delay 1\nJump 3"));
+        assert_eq!(Err(TrajScriptError::ArgumentCount { line: 1, command: String::from("Pos"), expected: 2 }),
+                   parse_traj_script("Pos 1"));
+        assert_eq!(Err(TrajScriptError::InvalidNumber { line: 1, text: String::from("1.5") }),
+                   parse_traj_script("Delay 1.5"));
+        for (command, text) in [("Speed", "NaN"), ("Speed", "inf"), ("Speed", "-inf"), ("Speed", "1e400"),
+                                 ("Speed", "8388608"), ("Angle", "20000000deg"), ("Delay", "2147483648")] {
+            assert_eq!(Err(TrajScriptError::InvalidNumber { line: 1, text: String::from(text) }),
+                       parse_traj_script(&format!("{} {}", command, text)));
+        }
+        assert_eq!(Ok(vec![TrajCommand::Speed(i32::MAX - 255)]), parse_traj_script("Speed 8388607"));
+        assert_eq!(Err(TrajScriptError::InvalidUnit { line: 1, text: String::from("3deg") }),
+                   parse_traj_script("Speed 3deg"));
+        assert_eq!(Err(TrajScriptError::UndefinedLabel { line: 1, label: String::from("x") }),
+                   parse_traj_script("loop x 1"));
+        assert_eq!(Err(TrajScriptError::DuplicateLabel { line: 2, label: String::from("x") }),
+                   parse_traj_script("x:\nx:"));
+        assert_eq!(Err(TrajScriptError::UnmatchedEnd { line: 1 }), parse_traj_script("end"));
+        assert_eq!(Err(TrajScriptError::UnclosedRepeat { line: 1 }), parse_traj_script("repeat 2\nShot 1"));
+        assert_eq!(Err(TrajScriptError::TooManyCommands { line: 5 }),
+                   parse_traj_script("repeat 100\nrepeat 101\nShot 1\nend\nend"));
+    }
+
+    #[test]
+    fn test_huge_count() {
+        assert_eq!(Err(TrajScriptError::TooManyCommands { line: 2 }),
+                   parse_traj_script("a:\nloop a 18446744073709551615"));
+        assert_eq!(Err(TrajScriptError::TooManyCommands { line: 1 }),
+                   parse_traj_script("repeat 4000000000\nend"));
+        assert_eq!(Err(TrajScriptError::TooManyCommands { line: 3 }),
+                   parse_traj_script("a:\nShot 1\nloop a 10000"));
+        assert_eq!(Ok(vec![]), parse_traj_script("repeat 10000\nend"));
+    }
+}
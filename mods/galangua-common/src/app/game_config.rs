@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::consts::*;
+use crate::framework::SystemTrait;
+
+pub const MIN_START_SHIPS: u32 = 2;
+pub const MAX_START_SHIPS: u32 = 5;
+
+// Scores to get an extra ship: `first`, `second`, and every `every` after the second.
+// Zero means no more extend.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExtendScores {
+    pub first: u32,
+    pub second: u32,
+    pub every: u32,
+}
+
+pub const EXTEND_PRESETS: [ExtendScores; 8] = [
+    ExtendScores { first: EXTEND_FIRST_SCORE, second: EXTEND_AFTER_SCORE, every: EXTEND_AFTER_SCORE },
+    ExtendScores { first: 20_000, second: 60_000, every: 60_000 },
+    ExtendScores { first: 20_000, second: 60_000, every: 0 },
+    ExtendScores { first: 20_000, second: 70_000, every: 70_000 },
+    ExtendScores { first: 20_000, second: 80_000, every: 80_000 },
+    ExtendScores { first: 30_000, second: 80_000, every: 0 },
+    ExtendScores { first: 30_000, second: 100_000, every: 100_000 },
+    ExtendScores { first: 0, second: 0, every: 0 },
+];
+
+impl ExtendScores {
+    // Returns the lowest extend score above `score`, `None` if it exceeds `u32::MAX`.
+    pub fn next(&self, score: u32) -> Option<u32> {
+        if self.first > score {
+            Some(self.first)
+        } else if self.second > score {
+            Some(self.second)
+        } else if self.every > 0 && self.second > 0 {
+            ((score - self.second) / self.every + 1).checked_mul(self.every)
+                .and_then(|n| self.second.checked_add(n))
+        } else {
+            None
+        }
+    }
+
+    // Whether a ship is extended when the score goes from `before` to `after`.
+    pub fn is_extended(&self, before: u32, after: u32) -> bool {
+        self.next(before).is_some_and(|ext| after >= ext)
+    }
+
+    pub fn name(&self) -> String {
+        if self.first == 0 {
+            return String::from("NONE");
+        }
+        let mut name = format!("{}K", self.first / 1000);
+        if self.second > 0 {
+            name += &format!(" {}K", self.second / 1000);
+            if self.every > 0 {
+                name += &format!(" EVERY {}K", self.every / 1000);
+            }
+        }
+        name
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Adaptive,  // Rank changes with the play.
+}
+
+pub const DIFFICULTIES: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Adaptive];
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+            Difficulty::Adaptive => "ADAPTIVE",
+        }
+    }
+
+    // Remaining enemy count to start rushing.
+    pub fn rush_threshold(self) -> u32 {
+        match self {
+            Difficulty::Easy => RUSH_THRESHOLD - 2,
+            Difficulty::Normal | Difficulty::Adaptive => RUSH_THRESHOLD,
+            Difficulty::Hard => RUSH_THRESHOLD + 3,
+        }
+    }
+}
+
+// Game settings, like DIP switches on the arcade board.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub start_ships: u32,
+    pub extend: ExtendScores,
+    pub difficulty: Difficulty,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            start_ships: DEFAULT_LEFT_SHIP,
+            extend: EXTEND_PRESETS[0],
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    // Broken or out of range saved data falls back to the default.
+    pub fn load<S: SystemTrait>(system: &S) -> Self {
+        system.get_json::<GameConfig>(KEY_GAME_CONFIG)
+            .filter(|config| (MIN_START_SHIPS..=MAX_START_SHIPS).contains(&config.start_ships))
+            .unwrap_or_default()
+    }
+
+    pub fn save<S: SystemTrait>(&self, system: &mut S) -> serde_json::Result<()> {
+        system.set_json(KEY_GAME_CONFIG, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+
+    #[test]
+    fn test_extend() {
+        let extend = ExtendScores { first: 20_000, second: 70_000, every: 70_000 };
+        assert_eq!(Some(20_000), extend.next(0));
+        assert_eq!(Some(70_000), extend.next(20_000));
+        assert_eq!(Some(140_000), extend.next(70_000));
+        assert_eq!(Some(210_000), extend.next(150_000));
+        assert!(extend.is_extended(19_990, 20_000));
+        assert!(!extend.is_extended(20_000, 20_010));
+
+        let extend = ExtendScores { first: 30_000, second: 80_000, every: 0 };
+        assert_eq!(None, extend.next(80_000));
+        assert_eq!(None, EXTEND_PRESETS[EXTEND_PRESETS.len() - 1].next(0));
+
+        // No overflow near the maximum score.
+        let extend = ExtendScores { first: 20_000, second: 70_000, every: 70_000 };
+        assert_eq!(None, extend.next(u32::MAX - 1));
+        assert!(!extend.is_extended(u32::MAX - 1, u32::MAX));
+        let last = 70_000 + (u32::MAX - 70_000) / 70_000 * 70_000;
+        assert_eq!(Some(last), extend.next(last - 1));
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!("20K 50K EVERY 50K", EXTEND_PRESETS[0].name());
+        assert_eq!("30K 80K", EXTEND_PRESETS[5].name());
+        assert_eq!("NONE", EXTEND_PRESETS[7].name());
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut system = NullSystem::default();
+        assert_eq!(GameConfig::default(), GameConfig::load(&system));
+
+        let config = GameConfig { start_ships: 5, extend: EXTEND_PRESETS[3], difficulty: Difficulty::Hard };
+        config.save(&mut system).unwrap();
+        assert_eq!(config, GameConfig::load(&system));
+
+        GameConfig { start_ships: 9, ..config }.save(&mut system).unwrap();
+        assert_eq!(GameConfig::default(), GameConfig::load(&system));
+    }
+}
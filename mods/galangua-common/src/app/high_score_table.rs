@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::consts::*;
+use crate::app::score_holder::PlayRecord;
+use crate::framework::{RendererTrait, SystemTrait};
+
+pub const HIGH_SCORE_TABLE_SIZE: usize = 10;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+    pub stage: u16,  // Stage reached, from 1.
+    pub hit_ratio: u32,  // In 0.1%.
+}
+
+impl HighScoreEntry {
+    pub fn new(initials: &str, score: u32, record: &PlayRecord) -> Self {
+        Self {
+            initials: String::from(initials),
+            score,
+            stage: record.stage.saturating_add(1),
+            hit_ratio: record.hit_ratio(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct HighScoreTable {
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScoreTable {
+    // Broken saved data is ignored.
+    pub fn load<S: SystemTrait>(system: &S) -> Self {
+        let mut entries: Vec<HighScoreEntry> = system.get_json(KEY_HIGH_SCORE_TABLE)
+            .unwrap_or_default();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(HIGH_SCORE_TABLE_SIZE);
+        Self { entries }
+    }
+
+    pub fn save<S: SystemTrait>(&self, system: &mut S) -> serde_json::Result<()> {
+        system.set_json(KEY_HIGH_SCORE_TABLE, &self.entries)
+    }
+
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    pub fn top_score(&self) -> Option<u32> {
+        self.entries.first().map(|entry| entry.score)
+    }
+
+    // Returns the rank from 0 where the score goes in, below the same scores.
+    pub fn rank(&self, score: u32) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+        let rank = self.entries.iter().position(|entry| score > entry.score)
+            .unwrap_or(self.entries.len());
+        if rank < HIGH_SCORE_TABLE_SIZE { Some(rank) } else { None }
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let rank = self.rank(entry.score)?;
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORE_TABLE_SIZE);
+        Some(rank)
+    }
+
+    // `highlight` is the rank of the entry just entered.
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R, highlight: Option<usize>) {
+        renderer.set_texture_color_mod("font", 255, 0, 0);
+        renderer.draw_str("font", 4 * 8, 5 * 8, "THE GALACTIC HEROES");
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", 0, 8 * 8, &format_row("RANK", "SCORE", "NAME", "STG", "HIT%"));
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            if highlight == Some(i) {
+                renderer.set_texture_color_mod("font", 255, 255, 0);
+            } else {
+                renderer.set_texture_color_mod("font", 255, 255, 255);
+            }
+            let score = std::cmp::min(entry.score, 9999999);
+            let text = format_row(
+                &rank_name(i), &score.to_string(), &entry.initials, &entry.stage.to_string(),
+                &format!("{}.{}", entry.hit_ratio / 10, entry.hit_ratio % 10));
+            renderer.draw_str("font", 0, (10 + i as i32 * 2) * 8, &text);
+        }
+    }
+}
+
+pub(super) fn rank_name(rank: usize) -> String {
+    let n = rank + 1;
+    let suffix = match n {
+        1 => "ST",
+        2 => "ND",
+        3 => "RD",
+        _ => "TH",
+    };
+    format!("{}{}", n, suffix)
+}
+
+pub(super) fn format_row(rank: &str, score: &str, name: &str, stage: &str, hit_ratio: &str) -> String {
+    format!("{:>4} {:>7} {:<4} {:>3} {:>5}", rank, score, name, stage, hit_ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+
+    fn entry(initials: &str, score: u32) -> HighScoreEntry {
+        HighScoreEntry { initials: String::from(initials), score, stage: 1, hit_ratio: 0 }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut table = HighScoreTable::default();
+        assert_eq!(None, table.rank(0));
+        assert_eq!(Some(0), table.insert(entry("AAA", 500)));
+        assert_eq!(Some(0), table.insert(entry("BBB", 1000)));
+        assert_eq!(Some(2), table.insert(entry("CCC", 500)));
+        for i in 0..10 {
+            table.insert(entry("DDD", 2000 + i));
+        }
+        assert_eq!(HIGH_SCORE_TABLE_SIZE, table.entries().len());
+        assert_eq!(Some(2009), table.top_score());
+        assert_eq!(None, table.rank(2000));
+        assert_eq!(Some(9), table.rank(2001));
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut system = NullSystem::default();
+        let mut table = HighScoreTable::default();
+        let record = PlayRecord { stage: 4, shot_count: 30, hit_count: 7, ..Default::default() };
+        table.insert(HighScoreEntry::new("XYZ", 12340, &record));
+        table.save(&mut system).unwrap();
+
+        let loaded = HighScoreTable::load(&system);
+        assert_eq!(&[HighScoreEntry { initials: String::from("XYZ"), score: 12340, stage: 5, hit_ratio: 233 }], loaded.entries());
+
+        system.set_str(KEY_HIGH_SCORE_TABLE, "broken");
+        assert!(HighScoreTable::load(&system).entries().is_empty());
+    }
+}
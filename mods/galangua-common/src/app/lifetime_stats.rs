@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::consts::*;
+use crate::app::score_holder::{PlayRecord, ScoreHolder};
+use crate::framework::SystemTrait;
+
+// Totals of all games played, kept across sessions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub game_count: u32,
+    pub shot_count: u64,
+    pub hit_count: u64,
+    pub destroyed_count: u64,
+    pub lost_count: u64,
+    pub captured_count: u64,
+    pub rescued_count: u64,
+}
+
+impl LifetimeStats {
+    // Broken saved data starts over.
+    pub fn load<S: SystemTrait>(system: &S) -> Self {
+        system.get_json(KEY_LIFETIME_STATS).unwrap_or_default()
+    }
+
+    pub fn save<S: SystemTrait>(&self, system: &mut S) -> serde_json::Result<()> {
+        system.set_json(KEY_LIFETIME_STATS, self)
+    }
+
+    // Adds the records of all players in the game, which counts as one game.
+    pub fn add_game(&mut self, score_holder: &ScoreHolder) {
+        self.game_count = self.game_count.saturating_add(1);
+        let player_count = if score_holder.is_two_players() { 2 } else { 1 };
+        for player in 0..player_count {
+            self.add_record(&score_holder.player_record(player));
+        }
+    }
+
+    fn add_record(&mut self, record: &PlayRecord) {
+        self.shot_count += record.shot_count as u64;
+        self.hit_count += record.hit_count as u64;
+        self.destroyed_count += record.destroyed_count as u64;
+        self.lost_count += record.lost_count as u64;
+        self.captured_count += record.captured_count as u64;
+        self.rescued_count += record.rescued_count as u64;
+    }
+
+    // Ratio of shots which hit enemies, in 0.1%.
+    pub fn hit_ratio(&self) -> u32 {
+        (self.hit_count * 1000).checked_div(self.shot_count).unwrap_or(0) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+
+    #[test]
+    fn test_add_and_save() {
+        let mut system = NullSystem::default();
+        let mut stats = LifetimeStats::load(&system);
+        assert_eq!(LifetimeStats::default(), stats);
+
+        let mut score_holder = ScoreHolder::new(0);
+        score_holder.set_two_players(true);
+        score_holder.record = PlayRecord { shot_count: 30, hit_count: 10, lost_count: 3, ..Default::default() };
+        score_holder.switch_player();
+        score_holder.record = PlayRecord { shot_count: 10, hit_count: 10, captured_count: 1, ..Default::default() };
+        stats.add_game(&score_holder);
+        assert_eq!((1, 40, 20, 3, 1), (stats.game_count, stats.shot_count, stats.hit_count, stats.lost_count, stats.captured_count));
+        assert_eq!(500, stats.hit_ratio());
+
+        stats.add_game(&ScoreHolder::new(0));
+        assert_eq!(2, stats.game_count);
+
+        stats.save(&mut system).unwrap();
+        assert_eq!(stats, LifetimeStats::load(&system));
+    }
+}
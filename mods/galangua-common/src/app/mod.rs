@@ -1,4 +1,16 @@
+pub mod attract_mode;
+pub mod audio_volume;
+pub mod bot;
 pub mod consts;
 pub mod game;
+pub mod game_config;
+pub mod high_score_table;
+pub mod lifetime_stats;
+pub mod name_entry;
+pub mod options_screen;
+pub mod pause_menu;
+pub mod results_screen;
+pub mod scene;
 pub mod score_holder;
+pub mod sound_patch;
 pub mod util;
@@ -0,0 +1,156 @@
+use super::high_score_table::{format_row, rank_name, HighScoreEntry, HighScoreTable};
+use super::score_holder::{PlayRecord, ScoreHolder};
+
+use crate::framework::RendererTrait;
+use crate::util::pad::{Pad, PadBit};
+
+const INITIALS_LEN: usize = 3;
+const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ. ";
+
+struct Candidate {
+    player: usize,
+    score: u32,
+    record: PlayRecord,
+}
+
+// Initials entry after game over, for each player whose score ranks in the high score table.
+pub struct NameEntry {
+    candidates: Vec<Candidate>,  // Waiting players, in reverse order.
+    current: Candidate,
+    two_players: bool,
+    initials: Vec<u8>,
+    char_index: usize,
+    rank: usize,
+    last_rank: Option<usize>,
+    frame_count: u32,
+}
+
+impl NameEntry {
+    // Returns `None` if no player ranks in.
+    pub fn new(score_holder: &ScoreHolder, table: &HighScoreTable) -> Option<Self> {
+        let player_count = if score_holder.is_two_players() { 2 } else { 1 };
+        let mut candidates: Vec<Candidate> = (0..player_count).rev()
+            .map(|player| Candidate { player, score: score_holder.player_score(player), record: score_holder.player_record(player) })
+            .filter(|candidate| table.rank(candidate.score).is_some())
+            .collect();
+        let current = candidates.pop()?;
+        let rank = table.rank(current.score)?;
+        Some(Self {
+            candidates,
+            current,
+            two_players: score_holder.is_two_players(),
+            initials: Vec::new(),
+            char_index: 0,
+            rank,
+            last_rank: None,
+            frame_count: 0,
+        })
+    }
+
+    // Rank of the entry inserted last.
+    pub fn last_rank(&self) -> Option<usize> {
+        self.last_rank
+    }
+
+    // Returns `false` when all players have entered.
+    pub fn update(&mut self, pad: &Pad, table: &mut HighScoreTable) -> bool {
+        self.frame_count = self.frame_count.wrapping_add(1);
+
+        if pad.is_trigger(PadBit::L) {
+            self.char_index = (self.char_index + CHARS.len() - 1) % CHARS.len();
+        }
+        if pad.is_trigger(PadBit::R) {
+            self.char_index = (self.char_index + 1) % CHARS.len();
+        }
+        if !pad.is_trigger(PadBit::A) {
+            return true;
+        }
+
+        self.initials.push(CHARS[self.char_index]);
+        if self.initials.len() < INITIALS_LEN {
+            return true;
+        }
+
+        let initials = String::from_utf8_lossy(&self.initials).into_owned();
+        self.last_rank = table.insert(HighScoreEntry::new(&initials, self.current.score, &self.current.record));
+        self.initials.clear();
+        self.char_index = 0;
+        while let Some(next) = self.candidates.pop() {
+            // The other player might be pushed out.
+            if let Some(rank) = table.rank(next.score) {
+                self.current = next;
+                self.rank = rank;
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R) {
+        renderer.set_texture_color_mod("font", 255, 0, 0);
+        renderer.draw_str("font", 4 * 8, 8 * 8, "ENTER YOUR INITIALS !");
+        if self.two_players {
+            renderer.set_texture_color_mod("font", 0, 255, 255);
+            renderer.draw_str("font", 10 * 8, 11 * 8, &format!("PLAYER {}", self.current.player + 1));
+        }
+
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", 0, 14 * 8, &format_row("RANK", "SCORE", "NAME", "STG", "HIT%"));
+
+        let mut initials = self.initials.clone();
+        if self.frame_count & 16 == 0 {
+            initials.push(CHARS[self.char_index]);
+        } else {
+            initials.push(b' ');
+        }
+        let record = HighScoreEntry::new("", self.current.score, &self.current.record);
+        let text = format_row(
+            &rank_name(self.rank), &std::cmp::min(record.score, 9999999).to_string(),
+            &String::from_utf8_lossy(&initials), &record.stage.to_string(),
+            &format!("{}.{}", record.hit_ratio / 10, record.hit_ratio % 10));
+        renderer.set_texture_color_mod("font", 255, 255, 0);
+        renderer.draw_str("font", 0, 16 * 8, &text);
+
+        renderer.set_texture_color_mod("font", 255, 255, 255);
+        renderer.draw_str("font", 3 * 8, 22 * 8, "LEFT/RIGHT : SELECT");
+        renderer.draw_str("font", 3 * 8, 24 * 8, "SHOT       : ENTER");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::VKey;
+
+    fn press(pad: &mut Pad, vkey: VKey) {
+        pad.on_key(vkey, true);
+        pad.update();
+        pad.on_key(vkey, false);
+    }
+
+    #[test]
+    fn test_enter_initials() {
+        let mut table = HighScoreTable::default();
+        let mut score_holder = ScoreHolder::new(0);
+        score_holder.add_score(3000);
+        let mut name_entry = NameEntry::new(&score_holder, &table).unwrap();
+
+        let mut pad = Pad::default();
+        let keys = [VKey::Right, VKey::Space, VKey::Left, VKey::Space, VKey::Right, VKey::Right, VKey::Space];
+        let results: Vec<bool> = keys.iter().map(|vkey| {
+            press(&mut pad, *vkey);
+            let result = name_entry.update(&pad, &mut table);
+            pad.update();
+            result
+        }).collect();
+        assert_eq!(vec![true, true, true, true, true, true, false], results);
+        assert_eq!("BAC", table.entries()[0].initials);
+        assert_eq!(Some(0), name_entry.last_rank());
+    }
+
+    #[test]
+    fn test_no_rank() {
+        let score_holder = ScoreHolder::new(0);
+        assert!(NameEntry::new(&score_holder, &HighScoreTable::default()).is_none());
+    }
+}
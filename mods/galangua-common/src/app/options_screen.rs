@@ -0,0 +1,244 @@
+use crate::app::audio_volume::{AudioVolume, MAX_VOLUME};
+use crate::app::game_config::{GameConfig, DIFFICULTIES, EXTEND_PRESETS, MAX_START_SHIPS, MIN_START_SHIPS};
+use crate::framework::RendererTrait;
+use crate::util::pad::{Pad, PadBit};
+use crate::util::pad_binding::{PadBinding, RawInput, BINDABLE_BUTTONS};
+
+const ITEM_SHIPS: usize = 0;
+const ITEM_EXTEND: usize = 1;
+const ITEM_DIFFICULTY: usize = 2;
+const ITEM_MASTER: usize = 3;
+const ITEM_SE: usize = 4;
+const ITEM_BGM: usize = 5;
+const ITEM_CONTROL: usize = 6;
+const ITEM_RESET: usize = ITEM_CONTROL + BINDABLE_BUTTONS.len();
+const ITEM_EXIT: usize = ITEM_RESET + 1;
+const ITEM_COUNT: usize = ITEM_EXIT + 1;
+
+const ITEM_Y: [i32; ITEM_COUNT] = [7, 8, 9, 13, 14, 15, 19, 20, 21, 22, 23, 26, 28];
+
+// Options screen for game settings, sound and controls, the menu itself is operated with the current binding.
+pub struct OptionsScreen {
+    config: GameConfig,
+    volume: AudioVolume,
+    binding: PadBinding,
+    cursor: usize,
+    waiting_input: bool,
+    frame_count: u32,
+}
+
+impl OptionsScreen {
+    pub fn new(config: &GameConfig, volume: &AudioVolume, binding: &PadBinding) -> Self {
+        Self {
+            config: *config,
+            volume: *volume,
+            binding: binding.clone(),
+            cursor: 0,
+            waiting_input: false,
+            frame_count: 0,
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn volume(&self) -> &AudioVolume {
+        &self.volume
+    }
+
+    pub fn binding(&self) -> &PadBinding {
+        &self.binding
+    }
+
+    // Returns `false` when the screen is closed.
+    pub fn update(&mut self, pad: &Pad) -> bool {
+        self.frame_count = self.frame_count.wrapping_add(1);
+
+        if self.waiting_input {
+            if let Some(input) = pad.raw_trigger() {
+                self.binding.bind(self.cursor - ITEM_CONTROL, input);
+                self.waiting_input = false;
+            }
+            return true;
+        }
+
+        if pad.is_trigger(PadBit::U) {
+            self.cursor = (self.cursor + ITEM_COUNT - 1) % ITEM_COUNT;
+        }
+        if pad.is_trigger(PadBit::D) {
+            self.cursor = (self.cursor + 1) % ITEM_COUNT;
+        }
+        if pad.is_trigger(PadBit::L) {
+            self.change_value(-1);
+        }
+        if pad.is_trigger(PadBit::R) {
+            self.change_value(1);
+        }
+        if pad.is_trigger(PadBit::A) {
+            match self.cursor {
+                ITEM_SHIPS | ITEM_EXTEND | ITEM_DIFFICULTY |
+                ITEM_MASTER | ITEM_SE | ITEM_BGM => self.change_value(1),
+                ITEM_RESET => {
+                    self.config = GameConfig::default();
+                    self.volume = AudioVolume::default();
+                    self.binding = PadBinding::default();
+                }
+                ITEM_EXIT => return false,
+                _ => self.waiting_input = true,
+            }
+        }
+        true
+    }
+
+    fn change_value(&mut self, d: i32) {
+        match self.cursor {
+            ITEM_SHIPS => {
+                let range = (MAX_START_SHIPS - MIN_START_SHIPS + 1) as i32;
+                let index = (self.config.start_ships - MIN_START_SHIPS) as i32;
+                self.config.start_ships = MIN_START_SHIPS + cycle(index, d, range) as u32;
+            }
+            ITEM_EXTEND => {
+                let index = EXTEND_PRESETS.iter().position(|x| *x == self.config.extend).unwrap_or(0);
+                self.config.extend = EXTEND_PRESETS[cycle(index as i32, d, EXTEND_PRESETS.len() as i32)];
+            }
+            ITEM_DIFFICULTY => {
+                let index = DIFFICULTIES.iter().position(|x| *x == self.config.difficulty).unwrap_or(0);
+                self.config.difficulty = DIFFICULTIES[cycle(index as i32, d, DIFFICULTIES.len() as i32)];
+            }
+            ITEM_MASTER => self.volume.master = cycle(self.volume.master as i32, d, MAX_VOLUME as i32 + 1) as u32,
+            ITEM_SE => self.volume.se = cycle(self.volume.se as i32, d, MAX_VOLUME as i32 + 1) as u32,
+            ITEM_BGM => self.volume.bgm = cycle(self.volume.bgm as i32, d, MAX_VOLUME as i32 + 1) as u32,
+            _ => {}
+        }
+    }
+
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R) {
+        renderer.set_texture_color_mod("font", 255, 0, 0);
+        renderer.draw_str("font", 10 * 8, 3 * 8, "OPTIONS");
+
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", 2 * 8, 5 * 8, "GAME");
+        renderer.draw_str("font", 2 * 8, 11 * 8, "SOUND");
+        let values = [
+            ("SHIPS", self.config.start_ships.to_string()),
+            ("BONUS", self.config.extend.name()),
+            ("RANK", String::from(self.config.difficulty.name())),
+            ("MASTER", self.volume.master.to_string()),
+            ("SE", self.volume.se.to_string()),
+            ("BGM", self.volume.bgm.to_string()),
+        ];
+        for (i, (name, value)) in values.iter().enumerate() {
+            self.set_item_color(renderer, i);
+            renderer.draw_str("font", 2 * 8, ITEM_Y[i] * 8, name);
+            renderer.draw_str("font", 9 * 8, ITEM_Y[i] * 8, value);
+        }
+
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", 2 * 8, 17 * 8, "CONTROL  KEY     JOYSTICK");
+        for (i, (_, name)) in BINDABLE_BUTTONS.iter().enumerate() {
+            let item = ITEM_CONTROL + i;
+            let inputs = self.binding.inputs(i);
+            let key = inputs.iter().find(|input| matches!(input, RawInput::Key(_)));
+            let joystick = inputs.iter().find(|input| !matches!(input, RawInput::Key(_)));
+            let y = ITEM_Y[item] * 8;
+            self.set_item_color(renderer, item);
+            renderer.draw_str("font", 2 * 8, y, name);
+            if self.waiting_input && self.cursor == item {
+                if self.frame_count & 16 == 0 {
+                    renderer.draw_str("font", 11 * 8, y, "PRESS ANY");
+                }
+                continue;
+            }
+            renderer.draw_str("font", 11 * 8, y, &input_name(key));
+            renderer.draw_str("font", 19 * 8, y, &input_name(joystick));
+        }
+
+        self.set_item_color(renderer, ITEM_RESET);
+        renderer.draw_str("font", 2 * 8, ITEM_Y[ITEM_RESET] * 8, "RESET TO DEFAULT");
+        self.set_item_color(renderer, ITEM_EXIT);
+        renderer.draw_str("font", 2 * 8, ITEM_Y[ITEM_EXIT] * 8, "EXIT");
+
+        renderer.set_texture_color_mod("font", 255, 255, 255);
+        renderer.draw_str("font", 0, ITEM_Y[self.cursor] * 8, ">");
+        renderer.set_texture_color_mod("font", 128, 128, 128);
+        renderer.draw_str("font", 3 * 8, 33 * 8, "SHOT : SELECT/REBIND");
+        renderer.draw_str("font", 3 * 8, 34 * 8, "ESC  : CANCEL");
+    }
+
+    fn set_item_color<R: RendererTrait>(&self, renderer: &mut R, item: usize) {
+        if item == self.cursor {
+            renderer.set_texture_color_mod("font", 255, 255, 0);
+        } else {
+            renderer.set_texture_color_mod("font", 255, 255, 255);
+        }
+    }
+}
+
+fn cycle(index: i32, d: i32, count: i32) -> usize {
+    (index + d).rem_euclid(count) as usize
+}
+
+fn input_name(input: Option<&RawInput>) -> String {
+    input.map_or_else(|| String::from("-"), |input| input.name().to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::game_config::Difficulty;
+    use crate::framework::VKey;
+
+    fn press(screen: &mut OptionsScreen, pad: &mut Pad, vkey: VKey) -> bool {
+        pad.on_key(vkey, true);
+        pad.update();
+        let result = screen.update(pad);
+        pad.on_key(vkey, false);
+        pad.update();
+        result
+    }
+
+    #[test]
+    fn test_rebind() {
+        let mut pad = Pad::default();
+        let mut screen = OptionsScreen::new(&GameConfig::default(), &AudioVolume::default(), pad.binding());
+        for _ in 0..ITEM_CONTROL {
+            press(&mut screen, &mut pad, VKey::Down);
+        }
+        press(&mut screen, &mut pad, VKey::Space);
+        press(&mut screen, &mut pad, VKey::A);
+        assert_eq!(PadBit::L, screen.binding().bits(RawInput::Key(VKey::A)));
+        assert_eq!(PadBit::empty(), pad.binding().bits(RawInput::Key(VKey::A)));
+
+        press(&mut screen, &mut pad, VKey::Up);
+        for _ in 0..ITEM_CONTROL {
+            press(&mut screen, &mut pad, VKey::Up);
+        }
+        assert!(!press(&mut screen, &mut pad, VKey::Space));
+    }
+
+    #[test]
+    fn test_game_config() {
+        let mut pad = Pad::default();
+        let mut screen = OptionsScreen::new(&GameConfig::default(), &AudioVolume::default(), pad.binding());
+        press(&mut screen, &mut pad, VKey::Left);
+        assert_eq!(2, screen.config().start_ships);
+        press(&mut screen, &mut pad, VKey::Left);
+        assert_eq!(MAX_START_SHIPS, screen.config().start_ships);
+
+        press(&mut screen, &mut pad, VKey::Down);
+        press(&mut screen, &mut pad, VKey::Left);
+        assert_eq!(EXTEND_PRESETS[EXTEND_PRESETS.len() - 1], screen.config().extend);
+
+        press(&mut screen, &mut pad, VKey::Down);
+        press(&mut screen, &mut pad, VKey::Space);
+        assert_eq!(Difficulty::Hard, screen.config().difficulty);
+
+        press(&mut screen, &mut pad, VKey::Down);
+        press(&mut screen, &mut pad, VKey::Left);
+        assert_eq!(MAX_VOLUME - 1, screen.volume().master);
+        press(&mut screen, &mut pad, VKey::Down);
+        press(&mut screen, &mut pad, VKey::Right);
+        assert_eq!(0, screen.volume().se);
+    }
+}
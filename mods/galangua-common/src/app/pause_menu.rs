@@ -0,0 +1,85 @@
+use crate::framework::types::Vec2I;
+use crate::framework::RendererTrait;
+use crate::util::pad::{Pad, PadBit};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseCommand {
+    Resume,
+    Restart,
+    Quit,
+}
+
+const ITEMS: [(PauseCommand, &str); 3] = [
+    (PauseCommand::Resume, "RESUME"),
+    (PauseCommand::Restart, "RESTART"),
+    (PauseCommand::Quit, "QUIT"),
+];
+
+// Menu drawn over the frozen game while it is paused.
+#[derive(Default)]
+pub struct PauseMenu {
+    cursor: usize,
+}
+
+impl PauseMenu {
+    // Returns the command selected.
+    pub fn update(&mut self, pad: &Pad) -> Option<PauseCommand> {
+        if pad.is_trigger(PadBit::U) {
+            self.cursor = (self.cursor + ITEMS.len() - 1) % ITEMS.len();
+        }
+        if pad.is_trigger(PadBit::D) {
+            self.cursor = (self.cursor + 1) % ITEMS.len();
+        }
+        if pad.is_trigger(PadBit::A) {
+            return Some(ITEMS[self.cursor].0);
+        }
+        None
+    }
+
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R) {
+        renderer.set_draw_color(0, 0, 0);
+        renderer.fill_rect(Some([&Vec2I::new(7 * 8, 13 * 8), &Vec2I::new(14 * 8, 11 * 8)]));
+
+        renderer.set_texture_color_mod("font", 255, 0, 0);
+        renderer.draw_str("font", 11 * 8, 14 * 8, "PAUSE");
+        for (i, (_, name)) in ITEMS.iter().enumerate() {
+            if i == self.cursor {
+                renderer.set_texture_color_mod("font", 255, 255, 0);
+            } else {
+                renderer.set_texture_color_mod("font", 255, 255, 255);
+            }
+            renderer.draw_str("font", 10 * 8, (17 + i as i32 * 2) * 8, name);
+        }
+        renderer.set_texture_color_mod("font", 255, 255, 255);
+        renderer.draw_str("font", 8 * 8, (17 + self.cursor as i32 * 2) * 8, ">");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::VKey;
+
+    fn press(menu: &mut PauseMenu, pad: &mut Pad, vkey: VKey) -> Option<PauseCommand> {
+        pad.on_key(vkey, true);
+        pad.update();
+        let result = menu.update(pad);
+        pad.on_key(vkey, false);
+        pad.update();
+        result
+    }
+
+    #[test]
+    fn test_select() {
+        let mut pad = Pad::default();
+        let mut menu = PauseMenu::default();
+        assert_eq!(Some(PauseCommand::Resume), press(&mut menu, &mut pad, VKey::Space));
+
+        assert_eq!(None, press(&mut menu, &mut pad, VKey::Up));
+        assert_eq!(Some(PauseCommand::Quit), press(&mut menu, &mut pad, VKey::Space));
+
+        assert_eq!(None, press(&mut menu, &mut pad, VKey::Down));
+        assert_eq!(None, press(&mut menu, &mut pad, VKey::Down));
+        assert_eq!(Some(PauseCommand::Restart), press(&mut menu, &mut pad, VKey::Space));
+    }
+}
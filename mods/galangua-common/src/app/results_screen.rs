@@ -0,0 +1,118 @@
+use super::lifetime_stats::LifetimeStats;
+use super::score_holder::{PlayRecord, ScoreHolder};
+
+use crate::framework::RendererTrait;
+use crate::util::pad::{Pad, PadBit};
+
+const RESULTS_FRAMES: u32 = 8 * 60;
+const SKIP_FRAMES: u32 = 60;  // Not to be skipped by the shot at the game over.
+
+type Row = (&'static str, fn(&PlayRecord) -> String);
+
+// Statistics shown after game over, like "SHOTS FIRED / NUMBER OF HITS / HIT-MISS RATIO" of the arcade.
+pub struct ResultsScreen {
+    records: Vec<PlayRecord>,  // For each player.
+    lifetime: LifetimeStats,
+    frame_count: u32,
+}
+
+impl ResultsScreen {
+    pub fn new(score_holder: &ScoreHolder, lifetime: &LifetimeStats) -> Self {
+        let player_count = if score_holder.is_two_players() { 2 } else { 1 };
+        Self {
+            records: (0..player_count).map(|player| score_holder.player_record(player)).collect(),
+            lifetime: *lifetime,
+            frame_count: 0,
+        }
+    }
+
+    // Returns `false` when the time is up or skipped.
+    pub fn update(&mut self, pad: &Pad) -> bool {
+        self.frame_count = self.frame_count.saturating_add(1);
+        if self.frame_count >= SKIP_FRAMES && pad.is_trigger(PadBit::A) {
+            return false;
+        }
+        self.frame_count < RESULTS_FRAMES
+    }
+
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R) {
+        renderer.set_texture_color_mod("font", 255, 0, 0);
+        renderer.draw_str("font", 8 * 8, 6 * 8, "- RESULTS -");
+
+        let xs: &[i32] = if self.records.len() == 1 { &[20] } else { &[14, 21] };
+        if self.records.len() > 1 {
+            for (i, x) in xs.iter().enumerate() {
+                renderer.draw_str("font", (x + 4) * 8, 9 * 8, &format!("{}UP", i + 1));
+            }
+        }
+        let rows: [Row; 7] = [
+            ("SHOTS FIRED", |record| record.shot_count.to_string()),
+            ("NUMBER OF HITS", |record| record.hit_count.to_string()),
+            ("HIT-MISS RATIO", |record| format_ratio(record.hit_ratio())),
+            ("ENEMIES DOWN", |record| record.destroyed_count.to_string()),
+            ("FIGHTERS LOST", |record| record.lost_count.to_string()),
+            ("CAPTURED", |record| record.captured_count.to_string()),
+            ("RESCUED", |record| record.rescued_count.to_string()),
+        ];
+        for (i, (label, value)) in rows.iter().enumerate() {
+            let y = (11 + i as i32 * 2) * 8;
+            renderer.set_texture_color_mod("font", 255, 255, 0);
+            renderer.draw_str("font", 0, y, label);
+            renderer.set_texture_color_mod("font", 255, 255, 255);
+            for (record, x) in self.records.iter().zip(xs.iter()) {
+                renderer.draw_str("font", x * 8, y, &format!("{:>7}", value(record)));
+            }
+        }
+
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", 0, 27 * 8, &format!("TOTAL OF {} GAMES", self.lifetime.game_count));
+        renderer.set_texture_color_mod("font", 255, 255, 0);
+        renderer.draw_str("font", 0, 29 * 8, "SHOTS FIRED");
+        renderer.draw_str("font", 0, 31 * 8, "HIT-MISS RATIO");
+        renderer.set_texture_color_mod("font", 255, 255, 255);
+        renderer.draw_str("font", 17 * 8, 29 * 8, &format!("{:>10}", self.lifetime.shot_count));
+        renderer.draw_str("font", 20 * 8, 31 * 8, &format!("{:>7}", format_ratio(self.lifetime.hit_ratio())));
+    }
+}
+
+// Ratio in 0.1%.
+fn format_ratio(ratio: u32) -> String {
+    format!("{}.{}%", ratio / 10, ratio % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::VKey;
+
+    #[test]
+    fn test_skip() {
+        let mut results_screen = ResultsScreen::new(&ScoreHolder::new(0), &LifetimeStats::default());
+        let mut pad = Pad::default();
+        pad.on_key(VKey::Space, true);
+        pad.update();
+        assert!(results_screen.update(&pad));  // Too early to skip.
+
+        pad.on_key(VKey::Space, false);
+        for _ in 1..SKIP_FRAMES {
+            pad.update();
+            assert!(results_screen.update(&pad));
+        }
+        pad.on_key(VKey::Space, true);
+        pad.update();
+        assert!(!results_screen.update(&pad));
+    }
+
+    #[test]
+    fn test_time_up() {
+        let mut results_screen = ResultsScreen::new(&ScoreHolder::new(0), &LifetimeStats::default());
+        let pad = Pad::default();
+        assert_eq!(RESULTS_FRAMES - 1, (0..RESULTS_FRAMES * 2).take_while(|_| results_screen.update(&pad)).count() as u32);
+    }
+
+    #[test]
+    fn test_format_ratio() {
+        assert_eq!("33.3%", format_ratio(333));
+        assert_eq!("100.0%", format_ratio(1000));
+    }
+}
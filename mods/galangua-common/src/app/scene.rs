@@ -0,0 +1,11 @@
+// Screen shown by the app, same for both versions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scene {
+    Title,
+    Demo,
+    Game,
+    Results,
+    NameEntry,
+    Options,
+    EditTraj,  // Only in the debug build of OO version.
+}
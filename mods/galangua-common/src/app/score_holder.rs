@@ -1,9 +1,41 @@
+use serde::{Deserialize, Serialize};
+
 use crate::framework::RendererTrait;
 
-#[derive(Clone)]
+// Play record of a player, for the high score table and the results screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayRecord {
+    pub stage: u16,
+    pub shot_count: u32,
+    pub hit_count: u32,
+    pub destroyed_count: u32,  // Enemies, excluding the captured fighter.
+    pub lost_count: u32,  // Fighters crashed, excluding captured ones.
+    pub captured_count: u32,
+    pub rescued_count: u32,
+}
+
+impl PlayRecord {
+    // Ratio of shots which hit enemies, in 0.1%.
+    pub fn hit_ratio(&self) -> u32 {
+        if self.shot_count == 0 {
+            0
+        } else {
+            (self.hit_count as u64 * 1000 / self.shot_count as u64) as u32
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ScoreHolder {
-    pub score: u32,
+    pub score: u32,  // Current player's.
     pub high_score: u32,
+    #[serde(default)]
+    pub record: PlayRecord,  // Current player's.
+    other_score: Option<u32>,  // Waiting player's, in 2 players mode.
+    #[serde(default)]
+    other_record: PlayRecord,
+    player: usize,
 }
 
 impl ScoreHolder {
@@ -11,11 +43,56 @@ impl ScoreHolder {
         Self {
             score: 0,
             high_score,
+            record: PlayRecord::default(),
+            other_score: None,
+            other_record: PlayRecord::default(),
+            player: 0,
         }
     }
 
     pub fn reset_score(&mut self) {
         self.score = 0;
+        self.other_score = self.other_score.map(|_| 0);
+        self.record = PlayRecord::default();
+        self.other_record = PlayRecord::default();
+        self.player = 0;
+    }
+
+    pub fn set_two_players(&mut self, value: bool) {
+        self.other_score = if value { Some(0) } else { None };
+        self.player = 0;
+    }
+
+    pub fn is_two_players(&self) -> bool {
+        self.other_score.is_some()
+    }
+
+    pub fn current_player(&self) -> usize {
+        self.player
+    }
+
+    pub fn switch_player(&mut self) {
+        if let Some(other_score) = self.other_score.as_mut() {
+            std::mem::swap(&mut self.score, other_score);
+            std::mem::swap(&mut self.record, &mut self.other_record);
+            self.player = 1 - self.player;
+        }
+    }
+
+    pub fn player_score(&self, player: usize) -> u32 {
+        if player == self.player {
+            self.score
+        } else {
+            self.other_score.unwrap_or(0)
+        }
+    }
+
+    pub fn player_record(&self, player: usize) -> PlayRecord {
+        if player == self.player {
+            self.record
+        } else {
+            self.other_record
+        }
     }
 
     pub fn add_score(&mut self, add: u32) {
@@ -25,18 +102,43 @@ impl ScoreHolder {
         }
     }
 
-    pub fn draw<R: RendererTrait>(&self, renderer: &mut R, show_1up: bool) {
+    // `show_current` blinks the label of the current player.
+    pub fn draw<R: RendererTrait>(&self, renderer: &mut R, show_current: bool) {
         renderer.set_texture_color_mod("font", 255, 0, 0);
-        if show_1up {
+        if show_current || self.player != 0 {
             renderer.draw_str("font", 2 * 8, 0 * 8, "1UP");
         }
+        if self.is_two_players() && (show_current || self.player != 1) {
+            renderer.draw_str("font", 21 * 8, 0 * 8, "2UP");
+        }
         renderer.draw_str("font", 9 * 8, 0 * 8, "HIGH SCORE");
         renderer.set_texture_color_mod("font", 255, 255, 255);
 
         const MAX_DISP_SCORE: u32 = 9999999;
-        let score = std::cmp::min(self.score, MAX_DISP_SCORE);
+        let score = std::cmp::min(self.player_score(0), MAX_DISP_SCORE);
         renderer.draw_str("font", 0 * 8, 1 * 8, &format!("{:6}0", score / 10));
         let high_score = std::cmp::min(self.high_score, MAX_DISP_SCORE);
         renderer.draw_str("font", 10 * 8, 1 * 8, &format!("{:6}0", high_score / 10));
+        if self.is_two_players() {
+            let score = std::cmp::min(self.player_score(1), MAX_DISP_SCORE);
+            renderer.draw_str("font", 19 * 8, 1 * 8, &format!("{:6}0", score / 10));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_player() {
+        let mut score_holder = ScoreHolder::new(1000);
+        score_holder.set_two_players(true);
+        score_holder.add_score(300);
+        score_holder.switch_player();
+        score_holder.add_score(1500);
+        assert_eq!((1, 300, 1500, 1500), (score_holder.current_player(), score_holder.player_score(0), score_holder.player_score(1), score_holder.high_score));
+        score_holder.switch_player();
+        assert_eq!((0, 300), (score_holder.current_player(), score_holder.score));
     }
 }
@@ -0,0 +1,112 @@
+use crate::app::consts::*;
+use crate::util::synth::{note, rest, sweep, Patch, Tone, Voice, WAVE_SAW, WAVE_SQUARE, WAVE_TRIANGLE};
+
+// Note frequencies (Hz).
+const C5: f32 = 523.25;
+const E5: f32 = 659.26;
+const G5: f32 = 783.99;
+const C6: f32 = 1046.50;
+const D6: f32 = 1174.66;
+const E6: f32 = 1318.51;
+const G6: f32 = 1567.98;
+const A6: f32 = 1760.00;
+const C7: f32 = 2093.00;
+
+const COUNT_STAGE: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[note(60, E6, 12), note(120, C7, 12), sweep(60, C7, C7, 12, 0)] },
+]};
+
+const MYSHOT: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_SAW), segments: &[sweep(60, 1600.0, 400.0, 15, 6), sweep(30, 400.0, 300.0, 6, 0)] },
+]};
+
+const DAMAGE: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[sweep(50, 900.0, 600.0, 14, 0)] },
+    Voice { tone: Tone::Noise, segments: &[sweep(50, 8000.0, 4000.0, 8, 0)] },
+]};
+
+const BOMB: Patch = Patch { voices: &[
+    Voice { tone: Tone::Noise, segments: &[sweep(300, 4000.0, 500.0, 15, 0)] },
+    Voice { tone: Tone::Wave(&WAVE_TRIANGLE), segments: &[sweep(200, 220.0, 55.0, 10, 0)] },
+]};
+
+const BOMB_CAPTURED: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_SAW), segments: &[sweep(250, 300.0, 1800.0, 14, 10), sweep(150, 1800.0, 2400.0, 10, 0)] },
+    Voice { tone: Tone::Noise, segments: &[sweep(200, 2000.0, 500.0, 8, 0)] },
+]};
+
+const ATTACK_START: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[sweep(150, 880.0, 440.0, 12, 4), sweep(100, 440.0, 330.0, 4, 0)] },
+]};
+
+// Loops while the beam is open.
+const TRACTOR_BEAM1: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_TRIANGLE), segments: &[
+        sweep(40, 600.0, 900.0, 12, 12), sweep(40, 900.0, 600.0, 12, 12),
+        sweep(40, 650.0, 950.0, 12, 12), sweep(40, 950.0, 650.0, 12, 12),
+    ] },
+]};
+
+const TRACTOR_BEAM2: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_TRIANGLE), segments: &[
+        sweep(300, 1200.0, 300.0, 13, 13), sweep(300, 1200.0, 300.0, 13, 13),
+        sweep(300, 1200.0, 300.0, 13, 13), sweep(400, 1200.0, 200.0, 13, 0),
+    ] },
+    Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[
+        note(200, C5, 6), note(200, E5, 6), note(200, G5, 6), note(200, E5, 6),
+        note(200, C5, 6), note(300, G5, 6), sweep(200, C5, C5, 6, 0),
+    ] },
+]};
+
+const EXTEND_SHIP: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_TRIANGLE), segments: &[
+        note(100, C6, 14), note(100, E6, 14), note(100, G6, 14), sweep(400, C7, C7, 14, 0),
+    ] },
+    Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[
+        rest(100), note(100, C6, 6), note(100, E6, 6), sweep(400, G6, G6, 6, 0),
+    ] },
+]};
+
+const RECAPTURE: Patch = Patch { voices: &[
+    Voice { tone: Tone::Wave(&WAVE_TRIANGLE), segments: &[
+        note(120, G5, 14), note(120, C6, 14), note(120, E6, 14), note(120, D6, 14),
+        note(120, G6, 14), note(120, A6, 14), sweep(500, C7, C7, 14, 0),
+    ] },
+    Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[
+        note(240, C5, 5), note(240, G5, 5), note(240, G5, 5), sweep(500, C6, C6, 5, 0),
+    ] },
+]};
+
+const PATCHES: [(&str, &Patch); 10] = [
+    (SE_COUNT_STAGE, &COUNT_STAGE),
+    (SE_MYSHOT, &MYSHOT),
+    (SE_DAMAGE, &DAMAGE),
+    (SE_BOMB_ZAKO, &BOMB),
+    (SE_BOMB_CAPTURED, &BOMB_CAPTURED),
+    (SE_ATTACK_START, &ATTACK_START),
+    (SE_TRACTOR_BEAM1, &TRACTOR_BEAM1),
+    (SE_TRACTOR_BEAM2, &TRACTOR_BEAM2),
+    (SE_EXTEND_SHIP, &EXTEND_SHIP),
+    (SE_RECAPTURE, &RECAPTURE),
+];
+
+// Synthesized substitute for the sound file.
+pub fn find_patch(filename: &str) -> Option<&'static Patch> {
+    PATCHES.iter().find(|(name, _)| *name == filename).map(|(_, patch)| *patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::synth::render;
+
+    #[test]
+    fn test_all_sounds() {
+        for filename in SOUND_ASSETS.iter().chain([SE_BOMB_PLAYER].iter()) {
+            let patch = find_patch(filename).unwrap_or_else(|| panic!("{}", filename));
+            let pcm = render(patch, 22050);
+            assert!(pcm.iter().any(|&sample| sample != 0), "{}", filename);
+        }
+        assert!(find_patch("unknown").is_none());
+    }
+}
@@ -0,0 +1,49 @@
+use crate::framework::SystemTrait;
+
+// Keeps looping sounds being played, to restart them after all sounds are stopped by the pause.
+#[derive(Clone, Default)]
+pub struct LoopingSe {
+    loops: Vec<(u32, &'static str)>,
+}
+
+impl LoopingSe {
+    pub fn play<S: SystemTrait>(&mut self, channel: u32, filename: &'static str, system: &mut S) {
+        self.loops.retain(|(ch, _)| *ch != channel);
+        self.loops.push((channel, filename));
+        system.play_loop(channel, filename);
+    }
+
+    pub fn stop<S: SystemTrait>(&mut self, channel: u32, fade_ms: u32, system: &mut S) {
+        self.loops.retain(|(ch, _)| *ch != channel);
+        system.stop_se(channel, fade_ms);
+    }
+
+    pub fn resume<S: SystemTrait>(&self, system: &mut S) {
+        for &(channel, filename) in self.loops.iter() {
+            system.play_loop(channel, filename);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+    use crate::framework::recording_audio::RecordingAudio;
+
+    #[test]
+    fn test_resume() {
+        let audio = RecordingAudio::default();
+        let mut system = NullSystem::with_audio(audio.clone());
+        let mut looping_se = LoopingSe::default();
+        looping_se.play(1, "a", &mut system);
+        looping_se.play(2, "b", &mut system);
+        looping_se.play(1, "c", &mut system);
+        looping_se.stop(2, 0, &mut system);
+        audio.next_frame();
+        looping_se.resume(&mut system);
+        assert_eq!(
+            vec![(0, 1, String::from("a")), (0, 2, String::from("b")), (0, 1, String::from("c")), (1, 1, String::from("c"))],
+            audio.played());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod collision;
+pub mod looping_se;
+pub mod snapshot;
 
 pub fn hsv(h: u32, s: u8, v: u8) -> (u8, u8, u8) {
     let h = h % (256 * 6);
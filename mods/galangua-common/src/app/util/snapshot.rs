@@ -0,0 +1,162 @@
+// Serde helpers for save states, for fields which can't be derived directly.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+pub fn from_json<T: DeserializeOwned>(snapshot: &Value, key: &str) -> Result<T, String> {
+    T::deserialize(&snapshot[key]).map_err(|e| format!("{}: {}", key, e))
+}
+
+// Serde borrows `&str` fields implicitly and requires `'de: 'static` for `&'static str`,
+// so declare such fields with this alias together with `#[serde(with = "sprite_name")]`.
+pub type StaticStr = &'static str;
+
+// Sprite name is restored from the table of the sprite sheet, e.g. `"gopher1"`.
+// `#[serde(with = "sprite_name")]`
+pub mod sprite_name {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::app::game::sprite_table::find_sprite_name;
+
+    pub fn serialize<S: Serializer>(s: &&'static str, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<&'static str, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        find_sprite_name(&s).ok_or_else(|| D::Error::custom(format!("unknown sprite `{}`", s)))
+    }
+}
+
+// Sprite animation is saved as the rest of the names, e.g. `["ene_exp4", "ene_exp5"]`.
+// `#[serde(with = "sprite_anime")]`
+pub mod sprite_anime {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::app::game::sprite_table::find_sprite_anime;
+
+    pub fn serialize<S: Serializer>(strs: &&'static [&'static str], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(strs.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<&'static [&'static str], D::Error> {
+        let strs = Vec::<String>::deserialize(deserializer)?;
+        find_sprite_anime(&strs).ok_or_else(|| D::Error::custom(format!("unknown sprite animation {:?}", strs)))
+    }
+}
+
+// Trajectory table is saved as its name and the index of the next command, e.g. `["COMMAND_TABLE1", 3]`.
+pub mod traj_table {
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::app::game::traj_command::TrajCommand;
+    use crate::app::game::traj_command_table::{find_traj_table, find_traj_table_position};
+
+    pub fn serialize<S: Serializer>(table: &&'static [TrajCommand], serializer: S) -> Result<S::Ok, S::Error> {
+        let position = find_traj_table_position(table)
+            .ok_or_else(|| S::Error::custom("trajectory table is not a named one"))?;
+        position.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<&'static [TrajCommand], D::Error> {
+        let (name, index) = <(String, usize)>::deserialize(deserializer)?;
+        match find_traj_table(&name) {
+            Some(table) if index <= table.len() => Ok(&table[index..]),
+            Some(_) => Err(D::Error::custom(format!("index out of range for `{}`: {}", name, index))),
+            None => Err(D::Error::custom(format!("unknown traj `{}`", name))),
+        }
+    }
+}
+
+// For arrays longer than 32, which serde doesn't support.
+pub mod array {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::convert::TryInto;
+
+    pub fn serialize<S: Serializer, T: Serialize, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(array.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>, const N: usize>(deserializer: D) -> Result<[T; N], D::Error> {
+        let vec = Vec::<T>::deserialize(deserializer)?;
+        let len = vec.len();
+        vec.try_into().map_err(|_| D::Error::invalid_length(len, &format!("{} elements", N).as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::game::effect_table::ENEMY_EXPLOSION_SPRITE_TABLE;
+    use crate::app::game::traj::Traj;
+    use crate::app::game::traj_command_table::*;
+    use crate::app::game::FormationIndex;
+    use crate::framework::types::ZERO_VEC;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct SpriteHolder {
+        #[serde(with = "sprite_name")]
+        sprite_name: StaticStr,
+        #[serde(with = "sprite_anime")]
+        sprites: &'static [&'static str],
+    }
+
+    #[test]
+    fn test_sprite() {
+        let holder = SpriteHolder { sprite_name: "gopher1", sprites: &ENEMY_EXPLOSION_SPRITE_TABLE[3..] };
+        let json = serde_json::to_string(&holder).unwrap();
+        assert_eq!(r#"{"sprite_name":"gopher1","sprites":["ene_exp4","ene_exp5"]}"#, json);
+        let restored: SpriteHolder = serde_json::from_str(&json).unwrap();
+        assert_eq!((holder.sprite_name, holder.sprites), (restored.sprite_name, restored.sprites));
+
+        assert!(serde_json::from_str::<SpriteHolder>(r#"{"sprite_name":"no_such_sprite","sprites":[]}"#).is_err());
+        assert!(serde_json::from_str::<SpriteHolder>(r#"{"sprite_name":"gopher1","sprites":["gopher1"]}"#).is_err());
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Holder {
+        #[serde(with = "traj_table")]
+        table: &'static [crate::app::game::traj_command::TrajCommand],
+    }
+
+    #[test]
+    fn test_traj_table() {
+        let holder = Holder { table: &OWL_ATTACK_TABLE[3..] };
+        let json = serde_json::to_string(&holder).unwrap();
+        assert_eq!(r#"{"table":["OWL_ATTACK_TABLE",3]}"#, json);
+        let restored: Holder = serde_json::from_str(&json).unwrap();
+        assert_eq!(holder.table, restored.table);
+
+        assert!(serde_json::from_str::<Holder>(r#"{"table":["NO_SUCH_TABLE",0]}"#).is_err());
+    }
+
+    #[test]
+    fn test_traj_round_trip() {
+        struct DummyAccessor;
+        impl crate::app::game::traj::Accessor for DummyAccessor {
+            fn get_formation_pos(&self, _formation_index: &FormationIndex) -> crate::framework::types::Vec2I { ZERO_VEC }
+            fn get_stage_no(&self) -> u16 { 0 }
+        }
+
+        let mut traj = Traj::new(&COMMAND_TABLE1, &ZERO_VEC, true, FormationIndex(1, 2));
+        for _ in 0..30 {
+            traj.update(&DummyAccessor);
+        }
+        let mut restored: Traj = serde_json::from_str(&serde_json::to_string(&traj).unwrap()).unwrap();
+        while traj.update(&DummyAccessor) {
+            assert!(restored.update(&DummyAccessor));
+            assert_eq!(traj.pos(), restored.pos());
+        }
+    }
+}
@@ -8,4 +8,7 @@ pub trait AppTrait<R: RendererTrait> {
     fn on_key(&mut self, keycode: VKey, down: bool);
     fn on_joystick_axis(&mut self, axis_index: u8, dir: i8);
     fn on_joystick_button(&mut self, button_index: u8, down: bool);
+
+    // Window or page gets or loses the focus.
+    fn on_focus(&mut self, focused: bool);
 }
@@ -0,0 +1,12 @@
+pub trait AudioTrait {
+    // Sounds are loaded beforehand, not to be read from the file on playing.
+    fn load_sounds(&mut self, filenames: &[&str]);
+    fn play_se(&mut self, channel: u32, filename: &str);
+    // Repeats until stopped.
+    fn play_loop(&mut self, channel: u32, filename: &str);
+    // Fades out in `fade_ms`, or stops at once with 0.
+    fn stop_se(&mut self, channel: u32, fade_ms: u32);
+    fn stop_all_se(&mut self);
+    // Volume in 0.0 - 1.0.
+    fn set_volume(&mut self, channel: u32, volume: f32);
+}
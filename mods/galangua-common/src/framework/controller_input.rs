@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use super::input_log::InputEvent;
+
+pub const AXIS_COUNT: usize = 6;
+pub const DEFAULT_AXIS_DEADZONE: i16 = 10_000;
+
+// Inputs held on one controller.
+#[derive(Default)]
+struct ControllerState {
+    stick_dirs: [i8; AXIS_COUNT],
+    dpad: [[bool; 2]; 2],  // [axis][negative, positive]
+    buttons: Vec<u8>,
+}
+
+impl ControllerState {
+    // D-pad takes priority over the left stick.
+    fn axis_dir(&self, axis: usize) -> i8 {
+        let dpad_dir = self.dpad.get(axis).map_or(0, |dpad| dpad[1] as i8 - dpad[0] as i8);
+        if dpad_dir != 0 { dpad_dir } else { self.stick_dirs[axis] }
+    }
+}
+
+// Merges inputs from hot-plugged controllers into joystick events for the app.
+// D-pad is reported as the same axes as the left stick.
+pub struct ControllerInput {
+    deadzone: i16,
+    controllers: HashMap<u32, ControllerState>,  // Keyed by instance id.
+    axis_dirs: [i8; AXIS_COUNT],  // Last notified.
+}
+
+impl Default for ControllerInput {
+    fn default() -> Self {
+        Self::new(DEFAULT_AXIS_DEADZONE)
+    }
+}
+
+impl ControllerInput {
+    pub fn new(deadzone: i16) -> Self {
+        Self {
+            deadzone,
+            controllers: HashMap::new(),
+            axis_dirs: [0; AXIS_COUNT],
+        }
+    }
+
+    // Analog axis values within the deadzone are taken as neutral.
+    pub fn set_deadzone(&mut self, deadzone: i16) {
+        self.deadzone = deadzone;
+    }
+
+    pub fn add_controller(&mut self, instance_id: u32) {
+        self.controllers.entry(instance_id).or_default();
+    }
+
+    // Releases inputs which are held only on the removed controller.
+    pub fn remove_controller(&mut self, instance_id: u32) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        if let Some(state) = self.controllers.remove(&instance_id) {
+            for &button in state.buttons.iter() {
+                if !self.is_button_held(button) {
+                    events.push(InputEvent::JoystickButton(button, false));
+                }
+            }
+            self.push_axis_events(&mut events);
+        }
+        events
+    }
+
+    pub fn on_axis(&mut self, instance_id: u32, axis: u8, value: i16) -> Vec<InputEvent> {
+        let dir = if value > self.deadzone { 1 } else if value < -self.deadzone { -1 } else { 0 };
+        match self.controllers.get_mut(&instance_id) {
+            Some(state) if (axis as usize) < AXIS_COUNT => state.stick_dirs[axis as usize] = dir,
+            _ => return Vec::new(),
+        }
+        let mut events = Vec::new();
+        self.push_axis_events(&mut events);
+        events
+    }
+
+    pub fn on_dpad(&mut self, instance_id: u32, axis: u8, positive: bool, down: bool) -> Vec<InputEvent> {
+        match self.controllers.get_mut(&instance_id) {
+            Some(state) if (axis as usize) < state.dpad.len() => state.dpad[axis as usize][positive as usize] = down,
+            _ => return Vec::new(),
+        }
+        let mut events = Vec::new();
+        self.push_axis_events(&mut events);
+        events
+    }
+
+    // Notifies only the first press and the last release among the controllers.
+    pub fn on_button(&mut self, instance_id: u32, button: u8, down: bool) -> Vec<InputEvent> {
+        let was_held = self.is_button_held(button);
+        let state = match self.controllers.get_mut(&instance_id) {
+            Some(state) => state,
+            None => return Vec::new(),
+        };
+        state.buttons.retain(|&b| b != button);
+        if down {
+            state.buttons.push(button);
+        }
+        if self.is_button_held(button) != was_held {
+            vec![InputEvent::JoystickButton(button, down)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn is_button_held(&self, button: u8) -> bool {
+        self.controllers.values().any(|state| state.buttons.contains(&button))
+    }
+
+    // Notifies only the axes whose direction changes.
+    fn push_axis_events(&mut self, events: &mut Vec<InputEvent>) {
+        for axis in 0..AXIS_COUNT {
+            let sum: i8 = self.controllers.values().map(|state| state.axis_dir(axis)).sum();
+            let dir = sum.signum();
+            if self.axis_dirs[axis] != dir {
+                self.axis_dirs[axis] = dir;
+                events.push(InputEvent::JoystickAxis(axis as u8, dir));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadzone() {
+        let mut input = ControllerInput::new(1000);
+        input.add_controller(1);
+        assert_eq!(Vec::<InputEvent>::new(), input.on_axis(1, 0, 1000));
+        assert_eq!(vec![InputEvent::JoystickAxis(0, 1)], input.on_axis(1, 0, 1001));
+        assert_eq!(Vec::<InputEvent>::new(), input.on_axis(1, 0, 20000));
+        assert_eq!(vec![InputEvent::JoystickAxis(0, -1)], input.on_axis(1, 0, -1001));
+        assert_eq!(vec![InputEvent::JoystickAxis(0, 0)], input.on_axis(1, 0, -1000));
+
+        input.set_deadzone(2000);
+        assert_eq!(Vec::<InputEvent>::new(), input.on_axis(1, 1, 1500));
+        assert_eq!(vec![InputEvent::JoystickAxis(1, 1)], input.on_axis(1, 1, 2500));
+
+        // Unknown controller, or axis.
+        assert_eq!(Vec::<InputEvent>::new(), input.on_axis(2, 0, 20000));
+        assert_eq!(Vec::<InputEvent>::new(), input.on_axis(1, AXIS_COUNT as u8, 20000));
+    }
+
+    #[test]
+    fn test_stick_and_dpad() {
+        let mut input = ControllerInput::default();
+        input.add_controller(1);
+        assert_eq!(vec![InputEvent::JoystickAxis(0, -1)], input.on_axis(1, 0, -20000));
+        // D-pad overrides the stick, and the stick comes back after releasing it.
+        assert_eq!(vec![InputEvent::JoystickAxis(0, 1)], input.on_dpad(1, 0, true, true));
+        assert_eq!(Vec::<InputEvent>::new(), input.on_axis(1, 0, 0));
+        // Opposite directions cancel each other.
+        assert_eq!(vec![InputEvent::JoystickAxis(0, 0)], input.on_dpad(1, 0, false, true));
+        assert_eq!(vec![InputEvent::JoystickAxis(0, -1)], input.on_dpad(1, 0, true, false));
+        assert_eq!(vec![InputEvent::JoystickAxis(0, 0)], input.on_dpad(1, 0, false, false));
+        assert_eq!(vec![InputEvent::JoystickAxis(1, 1)], input.on_dpad(1, 1, true, true));
+    }
+
+    #[test]
+    fn test_hot_plug() {
+        let mut input = ControllerInput::default();
+        input.add_controller(1);
+        input.add_controller(2);
+        assert_eq!(vec![InputEvent::JoystickButton(0, true)], input.on_button(1, 0, true));
+        assert_eq!(Vec::<InputEvent>::new(), input.on_button(2, 0, true));
+        assert_eq!(vec![InputEvent::JoystickButton(3, true)], input.on_button(1, 3, true));
+        assert_eq!(vec![InputEvent::JoystickAxis(0, 1)], input.on_axis(1, 0, 20000));
+        assert_eq!(Vec::<InputEvent>::new(), input.on_dpad(2, 0, true, true));
+
+        // Inputs held on the other controller are kept.
+        assert_eq!(vec![InputEvent::JoystickButton(3, false)], input.remove_controller(1));
+        assert_eq!(vec![InputEvent::JoystickButton(0, false), InputEvent::JoystickAxis(0, 0)], input.remove_controller(2));
+        assert_eq!(Vec::<InputEvent>::new(), input.remove_controller(2));
+        assert_eq!(Vec::<InputEvent>::new(), input.on_button(1, 0, true));
+    }
+}
@@ -0,0 +1,141 @@
+use serde_json::Value;
+
+use crate::framework::key_value_store::KeyValueStore;
+use crate::framework::recording_audio::NullAudio;
+use crate::framework::{AppTrait, AudioTrait, RendererTrait, SystemTrait};
+use crate::util::fps_calc::TimerTrait;
+
+// Timer which never ticks, FPS is not measured in headless run.
+#[derive(Default)]
+pub struct NullTimer;
+
+impl TimerTrait for NullTimer {
+    fn passed_one_second(&mut self) -> bool { false }
+}
+
+// System which keeps values in memory, and plays no sound unless given an audio.
+pub struct NullSystem<A: AudioTrait = NullAudio> {
+    store: KeyValueStore,
+    audio: A,
+}
+
+impl Default for NullSystem {
+    fn default() -> Self {
+        Self::with_audio(NullAudio)
+    }
+}
+
+impl<A: AudioTrait> NullSystem<A> {
+    pub fn with_audio(audio: A) -> Self {
+        Self {
+            store: KeyValueStore::default(),
+            audio,
+        }
+    }
+
+    pub fn audio(&self) -> &A {
+        &self.audio
+    }
+}
+
+impl<A: AudioTrait> SystemTrait for NullSystem<A> {
+    fn get_value(&self, key: &str) -> Option<Value> {
+        self.store.get(key).cloned()
+    }
+
+    fn set_value(&mut self, key: &str, value: Value) {
+        self.store.set(key, value);
+    }
+
+    fn begin_transaction(&mut self) {
+        self.store.begin_transaction();
+    }
+
+    fn commit_transaction(&mut self) {
+        self.store.commit_transaction();
+    }
+
+    fn is_touch_device(&self) -> bool { false }
+
+    fn load_sounds(&mut self, filenames: &[&str]) {
+        self.audio.load_sounds(filenames);
+    }
+
+    fn play_se(&mut self, channel: u32, filename: &str) {
+        self.audio.play_se(channel, filename);
+    }
+
+    fn play_loop(&mut self, channel: u32, filename: &str) {
+        self.audio.play_loop(channel, filename);
+    }
+
+    fn stop_se(&mut self, channel: u32, fade_ms: u32) {
+        self.audio.stop_se(channel, fade_ms);
+    }
+
+    fn stop_all_se(&mut self) {
+        self.audio.stop_all_se();
+    }
+
+    fn set_volume(&mut self, channel: u32, volume: f32) {
+        self.audio.set_volume(channel, volume);
+    }
+}
+
+// Steps the app without waiting, calls `on_frame` after each frame is drawn.
+// Returns the number of frames run, which is less than `frames` if the app quits.
+pub fn run_frames<R, A, F>(app: &mut A, renderer: &mut R, frames: u32, mut on_frame: F) -> u32
+where
+    R: RendererTrait,
+    A: AppTrait<R>,
+    F: FnMut(u32, &mut A, &mut R),
+{
+    for frame in 0..frames {
+        if !app.update() {
+            return frame;
+        }
+        app.draw(renderer);
+        on_frame(frame, app, renderer);
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::recording_renderer::{DrawCall, RecordingRenderer};
+    use crate::framework::VKey;
+
+    struct CountApp {
+        count: i32,
+        limit: i32,
+    }
+
+    impl<R: RendererTrait> AppTrait<R> for CountApp {
+        fn init(&mut self, _renderer: &mut R) {}
+        fn update(&mut self) -> bool { self.count += 1; self.count <= self.limit }
+        fn draw(&mut self, renderer: &mut R) { renderer.draw_str("font", self.count, 0, "X"); }
+        fn on_key(&mut self, _vkey: VKey, _down: bool) {}
+        fn on_joystick_axis(&mut self, _axis_index: u8, _dir: i8) {}
+        fn on_joystick_button(&mut self, _button_index: u8, _down: bool) {}
+        fn on_focus(&mut self, _focused: bool) {}
+    }
+
+    #[test]
+    fn test_run_frames() {
+        let mut app = CountApp { count: 0, limit: 100 };
+        let mut renderer = RecordingRenderer::default();
+        let mut frame_calls = Vec::new();
+        let n = run_frames(&mut app, &mut renderer, 3, |_frame, _app, renderer| frame_calls.push(renderer.take_calls()));
+        assert_eq!(3, n);
+        assert_eq!(vec![DrawCall::DrawStr(String::from("font"), 2, 0, String::from("X"))], frame_calls[1]);
+    }
+
+    #[test]
+    fn test_quit() {
+        let mut app = CountApp { count: 0, limit: 5 };
+        let mut renderer = RecordingRenderer::default();
+        assert_eq!(5, run_frames(&mut app, &mut renderer, 10, |_, _, _| {}));
+        assert_eq!(5, renderer.calls().len());
+    }
+}
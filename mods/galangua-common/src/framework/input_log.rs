@@ -0,0 +1,352 @@
+use serde_json::{json, Map, Value};
+
+use crate::framework::{AppTrait, RendererTrait, SystemTrait, VKey};
+
+const LOG_VERSION: u64 = 2;
+const MIN_LOG_VERSION: u64 = 1;  // Version 1 has no settings, replayed on the default ones.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    Key(VKey, bool),
+    JoystickAxis(u8, i8),
+    JoystickButton(u8, bool),
+    Focus(bool),
+}
+
+// Inputs of a play, with the settings it depends on, to be replayed the same anywhere.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputLog {
+    pub seed: u64,
+    pub oo: bool,  // Played on the OO version.
+    pub stage: Option<String>,  // Stage definition JSON given instead of the default.
+    pub saved: Map<String, Value>,  // Saved values read by the game, at the start of the recording.
+    pub events: Vec<(u32, InputEvent)>,  // (frame, event)
+}
+
+impl InputLog {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            oo: false,
+            stage: None,
+            saved: Map::new(),
+            events: Vec::new(),
+        }
+    }
+
+    // Takes the values of `keys` which are saved in `system`.
+    pub fn take_saved_values<S: SystemTrait>(&mut self, system: &S, keys: &[&str]) {
+        for &key in keys {
+            if let Some(value) = system.get_value(key) {
+                self.saved.insert(String::from(key), value);
+            }
+        }
+    }
+
+    // Puts the taken values to `system`, which should start empty for the replay.
+    pub fn restore_saved_values<S: SystemTrait>(&self, system: &mut S) {
+        system.transaction(|system| {
+            for (key, value) in self.saved.iter() {
+                system.set_value(key, value.clone());
+            }
+        });
+    }
+
+    pub fn to_json_string(&self) -> String {
+        let events: Vec<Value> = self.events.iter().map(|(frame, event)| {
+            match *event {
+                InputEvent::Key(vkey, down) => json!([frame, "key", vkey.name(), down]),
+                InputEvent::JoystickAxis(axis, dir) => json!([frame, "axis", axis, dir]),
+                InputEvent::JoystickButton(button, down) => json!([frame, "button", button, down]),
+                InputEvent::Focus(focused) => json!([frame, "focus", focused]),
+            }
+        }).collect();
+        json!({
+            "version": LOG_VERSION,
+            "seed": self.seed,
+            "oo": self.oo,
+            "stage": self.stage,
+            "saved": self.saved,
+            "events": events,
+        }).to_string()
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let version = value["version"].as_u64().ok_or("version not found")?;
+        if !(MIN_LOG_VERSION..=LOG_VERSION).contains(&version) {
+            return Err(format!("Unsupported version: {}", version));
+        }
+        let seed = value["seed"].as_u64().ok_or("seed not found")?;
+        let oo = match &value["oo"] {
+            Value::Null => false,
+            value => value.as_bool().ok_or("oo must be a bool")?,
+        };
+        let stage = match &value["stage"] {
+            Value::Null => None,
+            value => Some(String::from(value.as_str().ok_or("stage must be a string")?)),
+        };
+        let saved = match &value["saved"] {
+            Value::Null => Map::new(),
+            value => value.as_object().ok_or("saved must be an object")?.clone(),
+        };
+        let array = value["events"].as_array().ok_or("events not found")?;
+        let mut events = Vec::with_capacity(array.len());
+        let mut last_frame = 0;
+        for (i, entry) in array.iter().enumerate() {
+            let event = parse_event(entry).ok_or_else(|| format!("Illegal event at {}: {}", i, entry))?;
+            if event.0 < last_frame {
+                return Err(format!("Frame goes backward at {}: {}", i, entry));
+            }
+            last_frame = event.0;
+            events.push(event);
+        }
+        Ok(Self { seed, oo, stage, saved, events })
+    }
+}
+
+fn parse_event(entry: &Value) -> Option<(u32, InputEvent)> {
+    let frame = entry[0].as_u64()? as u32;
+    let event = match entry[1].as_str()? {
+        "key" => InputEvent::Key(VKey::from_name(entry[2].as_str()?)?, entry[3].as_bool()?),
+        "axis" => InputEvent::JoystickAxis(entry[2].as_u64()? as u8, entry[3].as_i64()? as i8),
+        "button" => InputEvent::JoystickButton(entry[2].as_u64()? as u8, entry[3].as_bool()?),
+        "focus" => InputEvent::Focus(entry[2].as_bool()?),
+        _ => return None,
+    };
+    Some((frame, event))
+}
+
+pub fn dispatch_event<R: RendererTrait, A: AppTrait<R>>(app: &mut A, event: &InputEvent) {
+    match *event {
+        InputEvent::Key(vkey, down) => app.on_key(vkey, down),
+        InputEvent::JoystickAxis(axis, dir) => app.on_joystick_axis(axis, dir),
+        InputEvent::JoystickButton(button, down) => app.on_joystick_button(button, down),
+        InputEvent::Focus(focused) => app.on_focus(focused),
+    }
+}
+
+// Wraps an app and logs every input, tagged with the frame it is applied to.
+pub struct InputRecorder<A> {
+    app: A,
+    log: InputLog,
+    frame: u32,
+}
+
+impl<A> InputRecorder<A> {
+    // Events are added to `log`, which holds the settings of the play.
+    pub fn new(app: A, log: InputLog) -> Self {
+        Self {
+            app,
+            log,
+            frame: 0,
+        }
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn log(&self) -> &InputLog {
+        &self.log
+    }
+
+    fn push(&mut self, event: InputEvent) {
+        self.log.events.push((self.frame, event));
+    }
+}
+
+impl<R: RendererTrait, A: AppTrait<R>> AppTrait<R> for InputRecorder<A> {
+    fn init(&mut self, renderer: &mut R) { self.app.init(renderer); }
+
+    fn update(&mut self) -> bool {
+        let result = self.app.update();
+        self.frame += 1;
+        result
+    }
+
+    fn draw(&mut self, renderer: &mut R) { self.app.draw(renderer); }
+
+    fn on_key(&mut self, vkey: VKey, down: bool) {
+        self.push(InputEvent::Key(vkey, down));
+        self.app.on_key(vkey, down);
+    }
+
+    fn on_joystick_axis(&mut self, axis_index: u8, dir: i8) {
+        self.push(InputEvent::JoystickAxis(axis_index, dir));
+        self.app.on_joystick_axis(axis_index, dir);
+    }
+
+    fn on_joystick_button(&mut self, button_index: u8, down: bool) {
+        self.push(InputEvent::JoystickButton(button_index, down));
+        self.app.on_joystick_button(button_index, down);
+    }
+
+    fn on_focus(&mut self, focused: bool) {
+        self.push(InputEvent::Focus(focused));
+        self.app.on_focus(focused);
+    }
+}
+
+// Wraps an app and feeds a recorded log instead of live input.
+// Live input is ignored until the log runs out, except Escape to abort.
+pub struct InputReplayer<A> {
+    app: A,
+    log: InputLog,
+    frame: u32,
+    index: usize,
+}
+
+impl<A> InputReplayer<A> {
+    pub fn new(app: A, log: InputLog) -> Self {
+        Self {
+            app,
+            log,
+            frame: 0,
+            index: 0,
+        }
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.log.events.len()
+    }
+}
+
+impl<R: RendererTrait, A: AppTrait<R>> AppTrait<R> for InputReplayer<A> {
+    fn init(&mut self, renderer: &mut R) { self.app.init(renderer); }
+
+    fn update(&mut self) -> bool {
+        while let Some((frame, event)) = self.log.events.get(self.index) {
+            if *frame > self.frame {
+                break;
+            }
+            dispatch_event(&mut self.app, event);
+            self.index += 1;
+        }
+
+        let result = self.app.update();
+        self.frame += 1;
+        result
+    }
+
+    fn draw(&mut self, renderer: &mut R) { self.app.draw(renderer); }
+
+    fn on_key(&mut self, vkey: VKey, down: bool) {
+        if self.is_finished() || vkey == VKey::Escape {
+            self.app.on_key(vkey, down);
+        }
+    }
+
+    fn on_joystick_axis(&mut self, axis_index: u8, dir: i8) {
+        if self.is_finished() {
+            self.app.on_joystick_axis(axis_index, dir);
+        }
+    }
+
+    fn on_joystick_button(&mut self, button_index: u8, down: bool) {
+        if self.is_finished() {
+            self.app.on_joystick_button(button_index, down);
+        }
+    }
+
+    fn on_focus(&mut self, focused: bool) {
+        if self.is_finished() {
+            self.app.on_focus(focused);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+    use crate::framework::recording_renderer::NullRenderer;
+
+    #[derive(Default)]
+    struct DummyApp {
+        frame: u32,
+        received: Vec<(u32, InputEvent)>,
+    }
+
+    impl AppTrait<NullRenderer> for DummyApp {
+        fn init(&mut self, _renderer: &mut NullRenderer) {}
+        fn update(&mut self) -> bool { self.frame += 1; true }
+        fn draw(&mut self, _renderer: &mut NullRenderer) {}
+        fn on_key(&mut self, vkey: VKey, down: bool) { self.received.push((self.frame, InputEvent::Key(vkey, down))); }
+        fn on_joystick_axis(&mut self, axis_index: u8, dir: i8) { self.received.push((self.frame, InputEvent::JoystickAxis(axis_index, dir))); }
+        fn on_joystick_button(&mut self, button_index: u8, down: bool) { self.received.push((self.frame, InputEvent::JoystickButton(button_index, down))); }
+        fn on_focus(&mut self, focused: bool) { self.received.push((self.frame, InputEvent::Focus(focused))); }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut saved = Map::new();
+        saved.insert(String::from("gameConfig"), json!({"start_ships": 5}));
+        let log = InputLog {
+            seed: 0x1234_5678_9abc_def0,
+            oo: true,
+            stage: Some(String::from("{}")),
+            saved,
+            events: vec![
+                (0, InputEvent::Key(VKey::Space, true)),
+                (3, InputEvent::JoystickAxis(0, -1)),
+                (3, InputEvent::JoystickButton(2, false)),
+                (5, InputEvent::Focus(false)),
+            ],
+        };
+        assert_eq!(Ok(log.clone()), InputLog::from_json_str(&log.to_json_string()));
+    }
+
+    #[test]
+    fn test_illegal_json() {
+        assert!(InputLog::from_json_str(r#"{"version":1,"seed":0,"events":[[0,"key","Foo",true]]}"#).is_err());
+        assert!(InputLog::from_json_str(r#"{"version":1,"seed":0,"events":[[5,"axis",0,1],[4,"axis",0,0]]}"#).is_err());
+        assert!(InputLog::from_json_str(r#"{"version":2,"seed":0,"saved":[],"events":[]}"#).is_err());
+        assert!(InputLog::from_json_str(r#"{"version":3,"seed":0,"events":[]}"#).is_err());
+    }
+
+    #[test]
+    fn test_version1() {
+        let log = InputLog::from_json_str(r#"{"version":1,"seed":3,"events":[[0,"focus",true]]}"#).unwrap();
+        assert_eq!(InputLog { events: vec![(0, InputEvent::Focus(true))], ..InputLog::new(3) }, log);
+    }
+
+    #[test]
+    fn test_saved_values() {
+        let mut system = NullSystem::default();
+        system.set_u32("a", 1);
+        system.set_u32("b", 2);
+        let mut log = InputLog::new(0);
+        log.take_saved_values(&system, &["a", "c"]);
+
+        let mut replay_system = NullSystem::default();
+        log.restore_saved_values(&mut replay_system);
+        assert_eq!((Some(1), None, None), (replay_system.get_u32("a"), replay_system.get_u32("b"), replay_system.get_u32("c")));
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut recorder = InputRecorder::new(DummyApp::default(), InputLog::new(0));
+        recorder.update();
+        AppTrait::<NullRenderer>::on_key(&mut recorder, VKey::Left, true);
+        recorder.update();
+        recorder.update();
+        AppTrait::<NullRenderer>::on_key(&mut recorder, VKey::Left, false);
+        AppTrait::<NullRenderer>::on_joystick_button(&mut recorder, 0, true);
+        AppTrait::<NullRenderer>::on_focus(&mut recorder, false);
+        recorder.update();
+        let log = recorder.log().clone();
+
+        let mut replayer = InputReplayer::new(DummyApp::default(), log);
+        for _ in 0..4 {
+            AppTrait::<NullRenderer>::on_key(&mut replayer, VKey::Right, true);  // Ignored.
+            replayer.update();
+        }
+        assert!(replayer.is_finished());
+        assert_eq!(recorder.app.received, replayer.app.received);
+    }
+}
@@ -0,0 +1,109 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Key/value map which keeps track of keys to be written out to a backing storage.
+#[derive(Default)]
+pub struct KeyValueStore {
+    map: HashMap<String, Value>,
+    dirty_keys: Vec<String>,
+    transaction_depth: u32,
+}
+
+impl KeyValueStore {
+    pub fn new(map: HashMap<String, Value>) -> Self {
+        Self {
+            map,
+            dirty_keys: Vec::new(),
+            transaction_depth: 0,
+        }
+    }
+
+    pub fn map(&self) -> &HashMap<String, Value> {
+        &self.map
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.map.get(key)
+    }
+
+    pub fn set(&mut self, key: &str, value: Value) {
+        if self.map.get(key) == Some(&value) {
+            return;
+        }
+        self.map.insert(String::from(key), value);
+        if !self.dirty_keys.iter().any(|k| k == key) {
+            self.dirty_keys.push(String::from(key));
+        }
+    }
+
+    pub fn begin_transaction(&mut self) {
+        self.transaction_depth += 1;
+    }
+
+    pub fn commit_transaction(&mut self) {
+        self.transaction_depth = self.transaction_depth.saturating_sub(1);
+    }
+
+    // Returns keys changed since the last call, or nothing while in a transaction.
+    pub fn take_dirty_keys(&mut self) -> Vec<String> {
+        if self.transaction_depth > 0 {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.dirty_keys)
+    }
+
+    // Puts back keys taken but failed to be written out, before the ones changed since.
+    pub fn restore_dirty_keys(&mut self, keys: Vec<String>) {
+        let changed = std::mem::replace(&mut self.dirty_keys, keys);
+        for key in changed {
+            if !self.dirty_keys.contains(&key) {
+                self.dirty_keys.push(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction() {
+        let mut store = KeyValueStore::default();
+        store.set("a", Value::from(1));
+        assert_eq!(vec![String::from("a")], store.take_dirty_keys());
+        assert!(store.take_dirty_keys().is_empty());
+
+        store.begin_transaction();
+        store.set("b", Value::from("x"));
+        store.begin_transaction();
+        store.set("a", Value::from(2));
+        store.set("b", Value::from("y"));
+        store.commit_transaction();
+        assert!(store.take_dirty_keys().is_empty());
+        store.commit_transaction();
+        assert_eq!(vec![String::from("b"), String::from("a")], store.take_dirty_keys());
+        assert_eq!(Some(&Value::from("y")), store.get("b"));
+    }
+
+    #[test]
+    fn test_restore_dirty_keys() {
+        let mut store = KeyValueStore::default();
+        store.set("a", Value::from(1));
+        store.set("b", Value::from(1));
+        let keys = store.take_dirty_keys();
+        store.set("c", Value::from(1));
+        store.set("a", Value::from(2));
+        store.restore_dirty_keys(keys);
+        assert_eq!(vec![String::from("a"), String::from("b"), String::from("c")], store.take_dirty_keys());
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let mut store = KeyValueStore::default();
+        store.set("a", Value::from(true));
+        store.take_dirty_keys();
+        store.set("a", Value::from(true));
+        assert!(store.take_dirty_keys().is_empty());
+    }
+}
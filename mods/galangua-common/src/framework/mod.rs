@@ -1,11 +1,21 @@
 mod app_trait;
+mod audio_trait;
+pub mod controller_input;
+pub mod headless;
+pub mod input_log;
+pub mod key_value_store;
+pub mod recording_audio;
+pub mod recording_renderer;
 mod renderer_trait;
+pub mod soft_renderer;
 pub mod sprite_sheet;
+pub mod std_system;
 mod system_trait;
 pub mod types;
 mod vkey;
 
 pub use self::app_trait::AppTrait;
+pub use self::audio_trait::AudioTrait;
 pub use self::renderer_trait::RendererTrait;
 pub use self::system_trait::SystemTrait;
 pub use self::vkey::VKey;
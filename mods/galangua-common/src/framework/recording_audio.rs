@@ -0,0 +1,99 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::framework::AudioTrait;
+
+// Audio which plays nothing.
+#[derive(Default)]
+pub struct NullAudio;
+
+impl AudioTrait for NullAudio {
+    fn load_sounds(&mut self, _filenames: &[&str]) {}
+    fn play_se(&mut self, _channel: u32, _filename: &str) {}
+    fn play_loop(&mut self, _channel: u32, _filename: &str) {}
+    fn stop_se(&mut self, _channel: u32, _fade_ms: u32) {}
+    fn stop_all_se(&mut self) {}
+    fn set_volume(&mut self, _channel: u32, _volume: f32) {}
+}
+
+#[derive(Default)]
+struct Record {
+    frame: u32,
+    played: Vec<(u32, u32, String)>,
+    stopped: Vec<(u32, Option<u32>)>,
+}
+
+// Audio which keeps sounds played as `(frame, channel, filename)`, and stopped as `(frame, channel)`
+// where `None` stands for all channels, to be inspected later.
+// Clones share the record, so that a test can hold one while the system owns another.
+#[derive(Clone, Default)]
+pub struct RecordingAudio {
+    record: Rc<RefCell<Record>>,
+}
+
+impl RecordingAudio {
+    // Counts up the frame recorded with sounds played after this.
+    pub fn next_frame(&self) {
+        self.record.borrow_mut().frame += 1;
+    }
+
+    pub fn played(&self) -> Vec<(u32, u32, String)> {
+        self.record.borrow().played.clone()
+    }
+
+    pub fn stopped(&self) -> Vec<(u32, Option<u32>)> {
+        self.record.borrow().stopped.clone()
+    }
+
+    fn push(&mut self, channel: u32, filename: &str) {
+        let mut record = self.record.borrow_mut();
+        let frame = record.frame;
+        record.played.push((frame, channel, String::from(filename)));
+    }
+
+    fn push_stop(&mut self, channel: Option<u32>) {
+        let mut record = self.record.borrow_mut();
+        let frame = record.frame;
+        record.stopped.push((frame, channel));
+    }
+}
+
+impl AudioTrait for RecordingAudio {
+    fn load_sounds(&mut self, _filenames: &[&str]) {}
+
+    fn play_se(&mut self, channel: u32, filename: &str) {
+        self.push(channel, filename);
+    }
+
+    fn play_loop(&mut self, channel: u32, filename: &str) {
+        self.push(channel, filename);
+    }
+
+    fn stop_se(&mut self, channel: u32, _fade_ms: u32) {
+        self.push_stop(Some(channel));
+    }
+
+    fn stop_all_se(&mut self) {
+        self.push_stop(None);
+    }
+
+    fn set_volume(&mut self, _channel: u32, _volume: f32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_record() {
+        let audio = RecordingAudio::default();
+        let mut owned = audio.clone();
+        owned.play_se(1, "a");
+        audio.next_frame();
+        owned.play_loop(2, "b");
+        owned.stop_se(2, 100);
+        owned.stop_all_se();
+        assert_eq!(vec![(0, 1, String::from("a")), (1, 2, String::from("b"))], audio.played());
+        assert_eq!(vec![(1, Some(2)), (1, None)], audio.stopped());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::framework::types::Vec2I;
+use crate::framework::RendererTrait;
+
+// Renderer which draws nothing.
+#[derive(Default)]
+pub struct NullRenderer;
+
+impl RendererTrait for NullRenderer {
+    fn load_textures(&mut self, _base_path: &str, _filenames: &[&str]) {}
+    fn load_sprite_sheet(&mut self, _filename: &str) {}
+    fn clear(&mut self) {}
+    fn set_texture_color_mod(&mut self, _tex_name: &str, _r: u8, _g: u8, _b: u8) {}
+    fn set_sprite_texture_color_mod(&mut self, _sprite_name: &str, _r: u8, _g: u8, _b: u8) {}
+    fn draw_str(&mut self, _tex_name: &str, _x: i32, _y: i32, _text: &str) {}
+    fn draw_sprite(&mut self, _sprite_name: &str, _pos: &Vec2I) {}
+    fn draw_sprite_rot(&mut self, _sprite_name: &str, _pos: &Vec2I, _angle: u8, _center: Option<&Vec2I>) {}
+    fn set_draw_color(&mut self, _r: u8, _g: u8, _b: u8) {}
+    fn fill_rect(&mut self, _dst: Option<[&Vec2I; 2]>) {}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCall {
+    Clear,
+    SetTextureColorMod(String, u8, u8, u8),
+    SetSpriteTextureColorMod(String, u8, u8, u8),
+    DrawStr(String, i32, i32, String),
+    DrawSprite(String, Vec2I),
+    DrawSpriteRot(String, Vec2I, u8, Option<Vec2I>),
+    SetDrawColor(u8, u8, u8),
+    FillRect(Option<[Vec2I; 2]>),
+}
+
+// Renderer which keeps draw calls, to be inspected later.
+#[derive(Default)]
+pub struct RecordingRenderer {
+    calls: Vec<DrawCall>,
+}
+
+impl RecordingRenderer {
+    pub fn calls(&self) -> &[DrawCall] {
+        &self.calls
+    }
+
+    pub fn take_calls(&mut self) -> Vec<DrawCall> {
+        std::mem::take(&mut self.calls)
+    }
+}
+
+impl RendererTrait for RecordingRenderer {
+    fn load_textures(&mut self, _base_path: &str, _filenames: &[&str]) {}
+    fn load_sprite_sheet(&mut self, _filename: &str) {}
+
+    fn clear(&mut self) {
+        self.calls.push(DrawCall::Clear);
+    }
+
+    fn set_texture_color_mod(&mut self, tex_name: &str, r: u8, g: u8, b: u8) {
+        self.calls.push(DrawCall::SetTextureColorMod(String::from(tex_name), r, g, b));
+    }
+
+    fn set_sprite_texture_color_mod(&mut self, sprite_name: &str, r: u8, g: u8, b: u8) {
+        self.calls.push(DrawCall::SetSpriteTextureColorMod(String::from(sprite_name), r, g, b));
+    }
+
+    fn draw_str(&mut self, tex_name: &str, x: i32, y: i32, text: &str) {
+        self.calls.push(DrawCall::DrawStr(String::from(tex_name), x, y, String::from(text)));
+    }
+
+    fn draw_sprite(&mut self, sprite_name: &str, pos: &Vec2I) {
+        self.calls.push(DrawCall::DrawSprite(String::from(sprite_name), *pos));
+    }
+
+    fn draw_sprite_rot(&mut self, sprite_name: &str, pos: &Vec2I, angle: u8, center: Option<&Vec2I>) {
+        self.calls.push(DrawCall::DrawSpriteRot(String::from(sprite_name), *pos, angle, center.copied()));
+    }
+
+    fn set_draw_color(&mut self, r: u8, g: u8, b: u8) {
+        self.calls.push(DrawCall::SetDrawColor(r, g, b));
+    }
+
+    fn fill_rect(&mut self, dst: Option<[&Vec2I; 2]>) {
+        self.calls.push(DrawCall::FillRect(dst.map(|[pos, size]| [*pos, *size])));
+    }
+}
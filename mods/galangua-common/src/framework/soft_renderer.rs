@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::framework::sprite_sheet::SpriteSheet;
+use crate::framework::types::Vec2I;
+use crate::framework::RendererTrait;
+use crate::util::math::{COS_TABLE, ONE, SIN_TABLE};
+
+struct Texture {
+    width: i32,
+    height: i32,
+    pixels: Vec<u8>,  // RGBA
+    color_mod: (u8, u8, u8),
+}
+
+impl Texture {
+    fn from_png(bytes: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let (info, mut reader) = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+
+        let (color_type, bit_depth) = reader.output_color_type();
+        if bit_depth != png::BitDepth::Eight {
+            return Err(format!("Unsupported bit depth: {:?}", bit_depth));
+        }
+        let pixel_count = (info.width * info.height) as usize;
+        let mut pixels = Vec::with_capacity(pixel_count * 4);
+        for i in 0..pixel_count {
+            let rgba = match color_type {
+                png::ColorType::RGBA => [buf[i * 4], buf[i * 4 + 1], buf[i * 4 + 2], buf[i * 4 + 3]],
+                png::ColorType::RGB => [buf[i * 3], buf[i * 3 + 1], buf[i * 3 + 2], 255],
+                png::ColorType::GrayscaleAlpha => [buf[i * 2], buf[i * 2], buf[i * 2], buf[i * 2 + 1]],
+                png::ColorType::Grayscale => [buf[i], buf[i], buf[i], 255],
+                png::ColorType::Indexed => return Err(String::from("Palette not expanded")),
+            };
+            pixels.extend_from_slice(&rgba);
+        }
+
+        Ok(Self {
+            width: info.width as i32,
+            height: info.height as i32,
+            pixels,
+            color_mod: (255, 255, 255),
+        })
+    }
+}
+
+// Renderer which rasterizes into an RGBA framebuffer on CPU.
+pub struct SoftRenderer {
+    width: i32,
+    height: i32,
+    pixels: Vec<u8>,  // RGBA
+    textures: HashMap<String, Texture>,
+    sprite_sheet: SpriteSheet,
+    draw_color: (u8, u8, u8),
+}
+
+impl SoftRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        for i in 0..(width * height) as usize {
+            pixels[i * 4 + 3] = 255;
+        }
+        Self {
+            width: width as i32,
+            height: height as i32,
+            pixels,
+            textures: HashMap::new(),
+            sprite_sheet: SpriteSheet::default(),
+            draw_color: (0, 0, 0),
+        }
+    }
+
+    pub fn width(&self) -> u32 { self.width as u32 }
+    pub fn height(&self) -> u32 { self.height as u32 }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: i32, y: i32) -> Option<[u8; 4]> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = ((y * self.width + x) * 4) as usize;
+        Some([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]])
+    }
+
+    pub fn load_texture_png(&mut self, tex_name: &str, bytes: &[u8]) -> Result<(), String> {
+        let texture = Texture::from_png(bytes)?;
+        self.textures.insert(String::from(tex_name), texture);
+        Ok(())
+    }
+
+    pub fn load_sprite_sheet_json(&mut self, text: &str) -> bool {
+        self.sprite_sheet.load_sprite_sheet(text)
+    }
+
+    pub fn write_png<W: Write>(&self, w: W) -> Result<(), String> {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&self.pixels).map_err(|e| e.to_string())
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        self.write_png(BufWriter::new(file))
+    }
+
+    fn blit(&mut self, tex_name: &str, src: (i32, i32, i32, i32), dst: &Vec2I) {
+        let texture = self.textures.get(tex_name).expect("No texture");
+        let (sx, sy, w, h) = src;
+        for y in 0..h {
+            let py = dst.y + y;
+            if py < 0 || py >= self.height || sy + y >= texture.height {
+                continue;
+            }
+            for x in 0..w {
+                let px = dst.x + x;
+                if px < 0 || px >= self.width || sx + x >= texture.width {
+                    continue;
+                }
+                let rgba = texel(texture, sx + x, sy + y);
+                blend_pixel(&mut self.pixels, ((py * self.width + px) * 4) as usize, rgba, texture.color_mod);
+            }
+        }
+    }
+
+    // Rotates clockwise around `center` (relative to `dst`), same as SDL's `copy_ex`.
+    fn blit_rot(&mut self, tex_name: &str, src: (i32, i32, i32, i32), dst: &Vec2I, angle: u8, center: &Vec2I) {
+        let texture = self.textures.get(tex_name).expect("No texture");
+        let (sx, sy, w, h) = src;
+        let cs = COS_TABLE[angle as usize];
+        let sn = SIN_TABLE[angle as usize];
+        let pivot = dst + center;
+
+        // Bounding box of rotated rectangle.
+        let corners = [(0, 0), (w, 0), (0, h), (w, h)];
+        let (mut x0, mut y0, mut x1, mut y1) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+        for &(cx, cy) in corners.iter() {
+            let dx = cx - center.x;
+            let dy = cy - center.y;
+            let rx = (dx * cs - dy * sn).div_euclid(ONE);
+            let ry = (dx * sn + dy * cs).div_euclid(ONE);
+            x0 = x0.min(rx - 1);
+            y0 = y0.min(ry - 1);
+            x1 = x1.max(rx + 1);
+            y1 = y1.max(ry + 1);
+        }
+
+        for ry in y0..=y1 {
+            let py = pivot.y + ry;
+            if py < 0 || py >= self.height {
+                continue;
+            }
+            for rx in x0..=x1 {
+                let px = pivot.x + rx;
+                if px < 0 || px >= self.width {
+                    continue;
+                }
+                // Inverse rotation from pixel center, in half pixel unit.
+                let dx2 = rx * 2 + 1;
+                let dy2 = ry * 2 + 1;
+                let u2 = (dx2 * cs + dy2 * sn).div_euclid(ONE) + center.x * 2;
+                let v2 = (-dx2 * sn + dy2 * cs).div_euclid(ONE) + center.y * 2;
+                let u = u2.div_euclid(2);
+                let v = v2.div_euclid(2);
+                if u < 0 || v < 0 || u >= w || v >= h || sx + u >= texture.width || sy + v >= texture.height {
+                    continue;
+                }
+                let rgba = texel(texture, sx + u, sy + v);
+                blend_pixel(&mut self.pixels, ((py * self.width + px) * 4) as usize, rgba, texture.color_mod);
+            }
+        }
+    }
+}
+
+fn blend_pixel(pixels: &mut [u8], i: usize, rgba: [u8; 4], color_mod: (u8, u8, u8)) {
+    let a = rgba[3] as u32;
+    if a == 0 {
+        return;
+    }
+    let src = [
+        rgba[0] as u32 * color_mod.0 as u32 / 255,
+        rgba[1] as u32 * color_mod.1 as u32 / 255,
+        rgba[2] as u32 * color_mod.2 as u32 / 255,
+    ];
+    for c in 0..3 {
+        let dst = pixels[i + c] as u32;
+        pixels[i + c] = ((src[c] * a + dst * (255 - a)) / 255) as u8;
+    }
+}
+
+fn texel(texture: &Texture, x: i32, y: i32) -> [u8; 4] {
+    let i = ((y * texture.width + x) * 4) as usize;
+    let p = &texture.pixels;
+    [p[i], p[i + 1], p[i + 2], p[i + 3]]
+}
+
+// Darkens the bottom of each of `rows` rows in RGBA `pixels`, like a CRT monitor.
+// Left as is when a row is less than 2 pixels high.
+pub fn darken_scanlines(pixels: &mut [u8], width: u32, height: u32, rows: u32, alpha: u8) {
+    let pitch = height as f32 / rows as f32;
+    if pitch < 2.0 {
+        return;
+    }
+    let thickness = ((pitch / 3.0) as u32).max(1);
+    let stride = (width * 4) as usize;
+    for row in 1..=rows {
+        let bottom = ((row as f32 * pitch) as u32).min(height);
+        for y in bottom.saturating_sub(thickness)..bottom {
+            let line = &mut pixels[y as usize * stride..(y as usize + 1) * stride];
+            for (i, c) in line.iter_mut().enumerate() {
+                if i % 4 != 3 {
+                    *c = (*c as u32 * (255 - alpha as u32) / 255) as u8;
+                }
+            }
+        }
+    }
+}
+
+impl RendererTrait for SoftRenderer {
+    fn load_textures(&mut self, base_path: &str, filenames: &[&str]) {
+        for filename in filenames {
+            let bytes = std::fs::read(format!("{}/{}", base_path, filename))
+                .expect("load_textures failed");
+            let key = Path::new(filename).file_stem().unwrap().to_str().unwrap();
+            self.load_texture_png(key, &bytes)
+                .expect("load_textures failed");
+        }
+    }
+
+    fn load_sprite_sheet(&mut self, filename: &str) {
+        let text = std::fs::read_to_string(filename)
+            .expect("load sprite sheet failed");
+        self.sprite_sheet.load_sprite_sheet(&text);
+    }
+
+    fn clear(&mut self) {
+        self.fill_rect(None);
+    }
+
+    fn set_texture_color_mod(&mut self, tex_name: &str, r: u8, g: u8, b: u8) {
+        if let Some(texture) = self.textures.get_mut(tex_name) {
+            texture.color_mod = (r, g, b);
+        }
+    }
+
+    fn set_sprite_texture_color_mod(&mut self, sprite_name: &str, r: u8, g: u8, b: u8) {
+        if let Some((_sheet, tex_name)) = self.sprite_sheet.get(sprite_name) {
+            if let Some(texture) = self.textures.get_mut(tex_name) {
+                texture.color_mod = (r, g, b);
+            }
+        }
+    }
+
+    fn draw_str(&mut self, tex_name: &str, x: i32, y: i32, text: &str) {
+        let mut x = x;
+        for c in text.chars() {
+            let u: i32 = ((c as i32) - (' ' as i32)) % 16 * 8;
+            let v: i32 = ((c as i32) - (' ' as i32)) / 16 * 8;
+            self.blit(tex_name, (u, v, 8, 8), &Vec2I::new(x, y));
+            x += 8;
+        }
+    }
+
+    fn draw_sprite(&mut self, sprite_name: &str, pos: &Vec2I) {
+        let (sheet, tex_name) = self.sprite_sheet.get(sprite_name)
+            .expect("No sprite");
+        let pos = sheet.trim_pos(pos);
+        let src = (sheet.frame.x, sheet.frame.y, sheet.frame.w as i32, sheet.frame.h as i32);
+        let tex_name = String::from(tex_name);
+        self.blit(&tex_name, src, &pos);
+    }
+
+    fn draw_sprite_rot(&mut self, sprite_name: &str, pos: &Vec2I, angle: u8,
+                       center: Option<&Vec2I>) {
+        let (sheet, tex_name) = self.sprite_sheet.get(sprite_name)
+            .expect("No sprite");
+        let pos = sheet.trim_pos(pos);
+        let (w, h) = (sheet.frame.w as i32, sheet.frame.h as i32);
+        let src = (sheet.frame.x, sheet.frame.y, w, h);
+        let center = center.copied().unwrap_or_else(|| Vec2I::new(w / 2, h / 2));
+        let tex_name = String::from(tex_name);
+        self.blit_rot(&tex_name, src, &pos, angle, &center);
+    }
+
+    fn set_draw_color(&mut self, r: u8, g: u8, b: u8) {
+        self.draw_color = (r, g, b);
+    }
+
+    fn fill_rect(&mut self, dst: Option<[&Vec2I; 2]>) {
+        let (x0, y0, x1, y1) = if let Some(rect) = dst {
+            (rect[0].x.max(0), rect[0].y.max(0),
+             (rect[0].x + rect[1].x).min(self.width), (rect[0].y + rect[1].y).min(self.height))
+        } else {
+            (0, 0, self.width, self.height)
+        };
+        let (r, g, b) = self.draw_color;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let i = ((y * self.width + x) * 4) as usize;
+                self.pixels[i..i + 4].copy_from_slice(&[r, g, b, 255]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates PNG with `w`x`h` pixels, top-left pixel is red and others are transparent.
+    fn make_png(w: u32, h: u32) -> Vec<u8> {
+        let mut src = SoftRenderer::new(w, h);
+        src.pixels.iter_mut().for_each(|p| *p = 0);
+        src.pixels[0..4].copy_from_slice(&[255, 0, 0, 255]);
+        let mut bytes = Vec::new();
+        src.write_png(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut renderer = SoftRenderer::new(8, 8);
+        renderer.set_draw_color(10, 20, 30);
+        renderer.fill_rect(Some([&Vec2I::new(6, -2), &Vec2I::new(4, 4)]));
+        assert_eq!(Some([10, 20, 30, 255]), renderer.get_pixel(7, 1));
+        assert_eq!(Some([0, 0, 0, 255]), renderer.get_pixel(5, 1));
+        assert_eq!(Some([0, 0, 0, 255]), renderer.get_pixel(7, 2));
+    }
+
+    #[test]
+    fn test_color_mod() {
+        let mut renderer = SoftRenderer::new(4, 4);
+        renderer.load_texture_png("tex", &make_png(2, 2)).unwrap();
+        renderer.set_texture_color_mod("tex", 128, 255, 255);
+        renderer.blit("tex", (0, 0, 2, 2), &Vec2I::new(1, 1));
+        assert_eq!(Some([128, 0, 0, 255]), renderer.get_pixel(1, 1));
+        assert_eq!(Some([0, 0, 0, 255]), renderer.get_pixel(2, 1));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut renderer = SoftRenderer::new(8, 8);
+        renderer.load_texture_png("tex", &make_png(4, 4)).unwrap();
+        // 90 degrees clockwise: top-left goes to top-right.
+        renderer.blit_rot("tex", (0, 0, 4, 4), &Vec2I::new(2, 2), 64, &Vec2I::new(2, 2));
+        assert_eq!(Some([255, 0, 0, 255]), renderer.get_pixel(5, 2));
+        assert_eq!(Some([0, 0, 0, 255]), renderer.get_pixel(2, 2));
+
+        // 180 degrees: goes to bottom-right.
+        renderer.set_draw_color(0, 0, 0);
+        renderer.clear();
+        renderer.blit_rot("tex", (0, 0, 4, 4), &Vec2I::new(2, 2), 128, &Vec2I::new(2, 2));
+        assert_eq!(Some([255, 0, 0, 255]), renderer.get_pixel(5, 5));
+    }
+
+    #[test]
+    fn test_darken_scanlines() {
+        let mut pixels = vec![200; 2 * 6 * 4];
+        darken_scanlines(&mut pixels, 2, 6, 2, 128);
+        let rows: Vec<&[u8]> = pixels.chunks(2 * 4).map(|row| &row[..4]).collect();
+        assert_eq!(vec![&[200, 200, 200, 200][..], &[200; 4], &[99, 99, 99, 200],
+                        &[200; 4], &[200; 4], &[99, 99, 99, 200]], rows);
+
+        // Too thin to show.
+        let mut pixels = vec![200; 2 * 3 * 4];
+        darken_scanlines(&mut pixels, 2, 3, 2, 128);
+        assert!(pixels.iter().all(|&c| c == 200));
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut renderer = SoftRenderer::new(3, 2);
+        renderer.set_draw_color(1, 2, 3);
+        renderer.fill_rect(Some([&Vec2I::new(1, 1), &Vec2I::new(1, 1)]));
+        let mut bytes = Vec::new();
+        renderer.write_png(&mut bytes).unwrap();
+
+        let texture = Texture::from_png(&bytes).unwrap();
+        assert_eq!((3, 2), (texture.width, texture.height));
+        assert_eq!(renderer.pixels(), &texture.pixels[..]);
+    }
+
+    #[test]
+    fn test_draw_assets() {
+        let mut renderer = SoftRenderer::new(224, 288);
+        renderer.load_textures("../../assets", &["chr.png", "font.png"]);
+        renderer.load_sprite_sheet("../../assets/chr.json");
+        renderer.set_texture_color_mod("font", 255, 255, 255);
+        renderer.draw_str("font", 0, 0, "A");
+        renderer.draw_sprite_rot("rustacean", &Vec2I::new(100, 100), 32, None);
+        assert!(renderer.pixels()[..8 * 8 * 4].chunks(4).any(|p| p[0] > 0));
+    }
+}
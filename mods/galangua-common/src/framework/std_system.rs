@@ -0,0 +1,216 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::framework::key_value_store::KeyValueStore;
+use crate::framework::{AudioTrait, SystemTrait};
+
+// System which saves values to a file.
+pub struct StdSystem<A: AudioTrait> {
+    store: KeyValueStore,
+    save_path: PathBuf,
+    read_only: bool,  // The save file couldn't be read, and must not be overwritten.
+    audio: A,
+}
+
+impl<A: AudioTrait> StdSystem<A> {
+    // Values are loaded from `legacy_path` until the save file is written once.
+    // Nothing is saved if the file can't be read, to keep the data in it.
+    pub fn new(audio: A, save_path: PathBuf, legacy_path: Option<&Path>) -> Self {
+        let load_path = match legacy_path {
+            Some(legacy_path) if !save_path.exists() && legacy_path.exists() => legacy_path,
+            _ => &save_path,
+        };
+        let (map, read_only) = match load_map(load_path) {
+            Ok(map) => (map, false),
+            Err(err) => {
+                eprintln!("{}: {}, nothing is saved", load_path.display(), err);
+                (HashMap::new(), true)
+            }
+        };
+        StdSystem {
+            store: KeyValueStore::new(map),
+            save_path,
+            read_only,
+            audio,
+        }
+    }
+
+    pub fn audio(&self) -> &A {
+        &self.audio
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    // Keeps the values, to play sounds with `audio`.
+    pub fn replace_audio<B: AudioTrait>(self, audio: B) -> StdSystem<B> {
+        StdSystem {
+            store: self.store,
+            save_path: self.save_path,
+            read_only: self.read_only,
+            audio,
+        }
+    }
+
+    // Keys stay dirty after a failed save, to be saved again on the next flush.
+    fn flush(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let keys = self.store.take_dirty_keys();
+        if keys.is_empty() {
+            return;
+        }
+        if let Err(err) = save_map(&self.save_path, self.store.map()) {
+            eprintln!("{}: {}", self.save_path.display(), err);
+            self.store.restore_dirty_keys(keys);
+        }
+    }
+}
+
+impl<A: AudioTrait> SystemTrait for StdSystem<A> {
+    fn get_value(&self, key: &str) -> Option<Value> {
+        self.store.get(key).cloned()
+    }
+
+    fn set_value(&mut self, key: &str, value: Value) {
+        self.store.set(key, value);
+        self.flush();
+    }
+
+    fn begin_transaction(&mut self) {
+        self.store.begin_transaction();
+    }
+
+    fn commit_transaction(&mut self) {
+        self.store.commit_transaction();
+        self.flush();
+    }
+
+    fn is_touch_device(&self) -> bool { false }
+
+    fn load_sounds(&mut self, filenames: &[&str]) {
+        self.audio.load_sounds(filenames);
+    }
+
+    fn play_se(&mut self, channel: u32, filename: &str) {
+        self.audio.play_se(channel, filename);
+    }
+
+    fn play_loop(&mut self, channel: u32, filename: &str) {
+        self.audio.play_loop(channel, filename);
+    }
+
+    fn stop_se(&mut self, channel: u32, fade_ms: u32) {
+        self.audio.stop_se(channel, fade_ms);
+    }
+
+    fn stop_all_se(&mut self) {
+        self.audio.stop_all_se();
+    }
+
+    fn set_volume(&mut self, channel: u32, volume: f32) {
+        self.audio.set_volume(channel, volume);
+    }
+}
+
+// A corrupt save file is moved aside, and the game starts with empty data.
+// Other read errors are returned, as the file may be intact.
+fn load_map(path: &Path) -> std::io::Result<HashMap<String, Value>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    match serde_json::from_str::<HashMap<String, Value>>(&text) {
+        Ok(deserialized) => Ok(deserialized),
+        Err(err) => {
+            let broken_path = path.with_extension("json.broken");
+            eprintln!("{}: {}, moved to {}", path.display(), err, broken_path.display());
+            if let Err(err) = std::fs::rename(path, &broken_path) {
+                eprintln!("{}", err);
+            }
+            Ok(HashMap::new())
+        }
+    }
+}
+
+// Writes to a temporary file and renames it, so that the save file is never left half written.
+fn save_map(path: &Path, map: &HashMap<String, Value>) -> std::io::Result<()> {
+    let serialized = serde_json::to_string(map)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    {
+        let mut f = File::create(&tmp_path)?;
+        f.write_all(serialized.as_bytes())?;
+        f.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::recording_audio::NullAudio;
+
+    #[test]
+    fn test_save_load() {
+        let dir = std::env::temp_dir().join(format!("galangua-test-{}", std::process::id()));
+        let path = dir.join("savedata.json");
+
+        let mut map = HashMap::new();
+        map.insert(String::from("highScore"), Value::from(1234));
+        assert!(save_map(&path, &map).is_ok());
+        assert!(!path.with_extension("json.tmp").exists());
+        assert_eq!(map, load_map(&path).unwrap());
+
+        std::fs::write(&path, "{broken").unwrap();
+        assert!(load_map(&path).unwrap().is_empty());
+        assert!(!path.exists());
+        assert!(path.with_extension("json.broken").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_retry_failed_save() {
+        let dir = std::env::temp_dir().join(format!("galangua-test-retry-{}", std::process::id()));
+        let path = dir.join("save").join("savedata.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut system = StdSystem::new(NullAudio, path.clone(), None);
+        // A file in the place of the directory makes saving fail.
+        std::fs::write(dir.join("save"), "").unwrap();
+        system.set_u32("highScore", 1234);
+        assert!(!path.exists());
+
+        std::fs::remove_file(dir.join("save")).unwrap();
+        system.set_u32("highScore", 1234);  // Unchanged, but saved this time.
+        assert_eq!(Some(&Value::from(1234)), load_map(&path).unwrap().get("highScore"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_file_is_kept() {
+        let dir = std::env::temp_dir().join(format!("galangua-test-unreadable-{}", std::process::id()));
+        let path = dir.join("savedata.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Not UTF-8.
+        std::fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+
+        let mut system = StdSystem::new(NullAudio, path.clone(), None);
+        assert!(system.is_read_only());
+        system.set_u32("highScore", 1234);
+        assert_eq!(Some(1234), system.get_u32("highScore"));
+        assert_eq!(vec![0xff, 0xfe, 0x00], std::fs::read(&path).unwrap());
+        assert!(!path.with_extension("json.broken").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,89 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::convert::TryFrom;
+
 pub trait SystemTrait {
-    fn get_u32(&self, key: &str) -> Option<u32>;
-    fn set_u32(&mut self, key: &str, value: u32);
+    fn get_value(&self, key: &str) -> Option<Value>;
+    fn set_value(&mut self, key: &str, value: Value);
+
+    // Values set in a transaction are written out together on the outermost commit.
+    fn begin_transaction(&mut self);
+    fn commit_transaction(&mut self);
 
     fn is_touch_device(&self) -> bool;
 
+    // Sounds, same as `AudioTrait`.
+    fn load_sounds(&mut self, filenames: &[&str]);
     fn play_se(&mut self, channel: u32, filename: &str);
+    fn play_loop(&mut self, channel: u32, filename: &str);
+    fn stop_se(&mut self, channel: u32, fade_ms: u32);
+    fn stop_all_se(&mut self);
+    fn set_volume(&mut self, channel: u32, volume: f32);
+
+    fn get_u32(&self, key: &str) -> Option<u32> {
+        self.get_value(key)
+            .and_then(|value| value.as_u64())
+            .and_then(|value| u32::try_from(value).ok())
+    }
+
+    fn set_u32(&mut self, key: &str, value: u32) {
+        self.set_value(key, Value::from(value));
+    }
+
+    fn get_str(&self, key: &str) -> Option<String> {
+        match self.get_value(key) {
+            Some(Value::String(string)) => Some(string),
+            _ => None,
+        }
+    }
+
+    fn set_str(&mut self, key: &str, value: &str) {
+        self.set_value(key, Value::from(value));
+    }
+
+    fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get_value(key).and_then(|value| value.as_bool())
+    }
+
+    fn set_bool(&mut self, key: &str, value: bool) {
+        self.set_value(key, Value::from(value));
+    }
+
+    // Returns `None` also if the saved value doesn't match the type.
+    fn get_json<T: DeserializeOwned>(&self, key: &str) -> Option<T> where Self: Sized {
+        self.get_value(key)
+            .and_then(|value| serde_json::from_value(value).ok())
+    }
+
+    // Nothing is set if `value` can't be serialized.
+    fn set_json<T: Serialize>(&mut self, key: &str, value: &T) -> serde_json::Result<()> where Self: Sized {
+        self.set_value(key, serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    fn transaction<F: FnOnce(&mut Self)>(&mut self, f: F) where Self: Sized {
+        self.begin_transaction();
+        f(self);
+        self.commit_transaction();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+
+    #[test]
+    fn test_get_u32() {
+        let mut system = NullSystem::default();
+        system.set_u32("a", u32::MAX);
+        assert_eq!(Some(u32::MAX), system.get_u32("a"));
+
+        // Out of range, not truncated.
+        system.set_value("a", Value::from(u32::MAX as u64 + 1));
+        assert_eq!(None, system.get_u32("a"));
+        system.set_value("a", Value::from(-1));
+        assert_eq!(None, system.get_u32("a"));
+    }
 }
@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, PartialEq)]
+use counted_array::counted_array;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum VKey {
     Space,
     Return,
@@ -12,4 +14,41 @@ pub enum VKey {
     Q, R, S, T, U, V, W, X, Y, Z,
     Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7,
     Num8, Num9,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+}
+
+impl VKey {
+    pub fn name(self) -> &'static str {
+        VKEY_NAME_TABLE.iter().find(|(vkey, _)| *vkey == self).unwrap().1
+    }
+
+    pub fn from_name(name: &str) -> Option<VKey> {
+        VKEY_NAME_TABLE.iter().find(|(_, s)| *s == name).map(|(vkey, _)| *vkey)
+    }
 }
+
+counted_array!(const VKEY_NAME_TABLE: [(VKey, &str); _] = [
+    (VKey::Space,  "Space"),
+    (VKey::Return, "Return"),
+    (VKey::Escape, "Escape"),
+    (VKey::Left,   "Left"),
+    (VKey::Right,  "Right"),
+    (VKey::Up,     "Up"),
+    (VKey::Down,   "Down"),
+
+    (VKey::A, "A"), (VKey::B, "B"), (VKey::C, "C"), (VKey::D, "D"),
+    (VKey::E, "E"), (VKey::F, "F"), (VKey::G, "G"), (VKey::H, "H"),
+    (VKey::I, "I"), (VKey::J, "J"), (VKey::K, "K"), (VKey::L, "L"),
+    (VKey::M, "M"), (VKey::N, "N"), (VKey::O, "O"), (VKey::P, "P"),
+    (VKey::Q, "Q"), (VKey::R, "R"), (VKey::S, "S"), (VKey::T, "T"),
+    (VKey::U, "U"), (VKey::V, "V"), (VKey::W, "W"), (VKey::X, "X"),
+    (VKey::Y, "Y"), (VKey::Z, "Z"),
+
+    (VKey::Num0, "0"), (VKey::Num1, "1"), (VKey::Num2, "2"), (VKey::Num3, "3"),
+    (VKey::Num4, "4"), (VKey::Num5, "5"), (VKey::Num6, "6"), (VKey::Num7, "7"),
+    (VKey::Num8, "8"), (VKey::Num9, "9"),
+
+    (VKey::F1, "F1"), (VKey::F2, "F2"), (VKey::F3, "F3"), (VKey::F4, "F4"),
+    (VKey::F5, "F5"), (VKey::F6, "F6"), (VKey::F7, "F7"), (VKey::F8, "F8"),
+    (VKey::F9, "F9"), (VKey::F10, "F10"), (VKey::F11, "F11"), (VKey::F12, "F12"),
+]);
@@ -1,3 +1,6 @@
 pub mod fps_calc;
 pub mod math;
 pub mod pad;
+pub mod pad_binding;
+pub mod rng;
+pub mod synth;
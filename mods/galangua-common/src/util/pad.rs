@@ -1,6 +1,7 @@
 use bitflags::bitflags;
 
 use crate::framework::VKey;
+use crate::util::pad_binding::{PadBinding, RawInput};
 
 bitflags! {
     #[derive(Default)]
@@ -20,6 +21,9 @@ pub struct Pad {
     last_pad: PadBit,
     key: PadBit,
     joy: PadBit,
+    binding: PadBinding,
+    raw_input: Option<RawInput>,  // Pressed last, since the last update.
+    raw_trg: Option<RawInput>,
 }
 
 impl Pad {
@@ -27,6 +31,17 @@ impl Pad {
         self.pad = self.key | self.joy;
         self.trg = self.pad & !self.last_pad;
         self.last_pad = self.pad;
+        self.raw_trg = self.raw_input.take();
+    }
+
+    pub fn binding(&self) -> &PadBinding {
+        &self.binding
+    }
+
+    pub fn set_binding(&mut self, binding: PadBinding) {
+        self.binding = binding;
+        self.key = PadBit::empty();
+        self.joy = PadBit::empty();
     }
 
     pub fn is_pressed(&self, btn: PadBit) -> bool {
@@ -37,58 +52,52 @@ impl Pad {
         self.trg.contains(btn)
     }
 
+    // Input from a program instead of the devices, for bots.
+    pub fn set_bits(&mut self, bits: PadBit) {
+        self.key = bits;
+        self.joy = PadBit::empty();
+    }
+
+    // Key or joystick input pressed in this frame, regardless of the binding.
+    pub fn raw_trigger(&self) -> Option<RawInput> {
+        self.raw_trg
+    }
+
     pub fn on_key(&mut self, keycode: VKey, down: bool) {
-        let bit = get_key_bit(keycode);
+        let input = RawInput::Key(keycode);
+        let bit = self.binding.bits(input);
         if down {
             self.key |= bit;
+            self.raw_input = Some(input);
         } else {
             self.key &= !bit;
         }
     }
 
     pub fn on_joystick_axis(&mut self, axis_index: u8, dir: i8) {
-        match axis_index {
-            0 => {
-                let lr = match dir {
-                    dir if dir < 0 => PadBit::L,
-                    dir if dir > 0 => PadBit::R,
-                    _              => PadBit::empty(),
-                };
-                self.joy = (self.joy & !(PadBit::L | PadBit::R)) | lr;
-            }
-            1 => {
-                let ud = match dir {
-                    dir if dir < 0 => PadBit::U,
-                    dir if dir > 0 => PadBit::D,
-                    _              => PadBit::empty(),
-                };
-                self.joy = (self.joy & !(PadBit::U | PadBit::D)) | ud;
+        let mut bit = PadBit::empty();
+        if dir != 0 {
+            let input = RawInput::JoystickAxis(axis_index, dir.signum());
+            bit = self.binding.bits(input);
+            if !self.binding.axis_bits(axis_index).intersects(self.joy) {
+                self.raw_input = Some(input);
             }
-            _ => {}
         }
+        self.joy = (self.joy & !self.binding.axis_bits(axis_index)) | bit;
     }
 
-    pub fn on_joystick_button(&mut self, _button_index: u8, down: bool) {
-        let bit = PadBit::A;
+    pub fn on_joystick_button(&mut self, button_index: u8, down: bool) {
+        let input = RawInput::JoystickButton(button_index);
+        let bit = self.binding.bits(input);
         if down {
             self.joy |= bit;
+            self.raw_input = Some(input);
         } else {
             self.joy &= !bit;
         }
     }
 }
 
-fn get_key_bit(key: VKey) -> PadBit {
-    match key {
-        VKey::Left => PadBit::L,
-        VKey::Right => PadBit::R,
-        VKey::Up => PadBit::U,
-        VKey::Down => PadBit::D,
-        VKey::Space => PadBit::A,
-        _ => PadBit::empty(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +115,31 @@ mod tests {
         assert_eq!(true, pad.is_pressed(PadBit::A));
         assert_eq!(false, pad.is_trigger(PadBit::A));
     }
+
+    #[test]
+    fn test_binding() {
+        let mut binding = PadBinding::default();
+        binding.bind(0, RawInput::Key(VKey::A));
+        binding.bind(4, RawInput::JoystickButton(7));
+        let mut pad = Pad::default();
+        pad.set_binding(binding);
+
+        pad.on_key(VKey::A, true);
+        pad.on_joystick_button(7, true);
+        pad.update();
+        assert_eq!(true, pad.is_pressed(PadBit::L));
+        assert_eq!(true, pad.is_trigger(PadBit::A));
+        assert_eq!(Some(RawInput::JoystickButton(7)), pad.raw_trigger());
+
+        pad.on_key(VKey::A, false);
+        pad.on_key(VKey::Left, true);  // Unbound.
+        pad.on_joystick_axis(1, 1);
+        pad.update();
+        assert_eq!(false, pad.is_pressed(PadBit::L));
+        assert_eq!(true, pad.is_pressed(PadBit::D));
+        assert_eq!(Some(RawInput::JoystickAxis(1, 1)), pad.raw_trigger());
+
+        pad.update();
+        assert_eq!(None, pad.raw_trigger());
+    }
 }
@@ -0,0 +1,165 @@
+use serde_json::{Map, Value};
+
+use crate::app::consts::*;
+use crate::framework::{SystemTrait, VKey};
+use crate::util::pad::PadBit;
+
+// Bindable pad buttons, in the order shown on the options screen.
+pub const BINDABLE_BUTTONS: [(PadBit, &str); 5] = [
+    (PadBit::L, "LEFT"),
+    (PadBit::R, "RIGHT"),
+    (PadBit::U, "UP"),
+    (PadBit::D, "DOWN"),
+    (PadBit::A, "SHOT"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RawInput {
+    Key(VKey),
+    JoystickButton(u8),
+    JoystickAxis(u8, i8),  // (axis, -1 or 1)
+}
+
+impl RawInput {
+    pub fn name(self) -> String {
+        match self {
+            RawInput::Key(vkey) => String::from(vkey.name()),
+            RawInput::JoystickButton(button) => format!("BUTTON{}", button),
+            RawInput::JoystickAxis(axis, dir) => format!("AXIS{}{}", axis, if dir < 0 { '-' } else { '+' }),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(button) = name.strip_prefix("BUTTON") {
+            return button.parse().ok().map(RawInput::JoystickButton);
+        }
+        if let Some(axis) = name.strip_prefix("AXIS") {
+            let dir = match axis.chars().last()? {
+                '-' => -1,
+                '+' => 1,
+                _ => return None,
+            };
+            return axis[..axis.len() - 1].parse().ok().map(|axis| RawInput::JoystickAxis(axis, dir));
+        }
+        VKey::from_name(name).map(RawInput::Key)
+    }
+
+    fn is_joystick(self) -> bool {
+        !matches!(self, RawInput::Key(_))
+    }
+}
+
+// Maps keys and joystick inputs to pad buttons.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PadBinding {
+    inputs: [Vec<RawInput>; BINDABLE_BUTTONS.len()],
+}
+
+impl Default for PadBinding {
+    fn default() -> Self {
+        Self {
+            inputs: [
+                vec![RawInput::Key(VKey::Left), RawInput::JoystickAxis(0, -1)],
+                vec![RawInput::Key(VKey::Right), RawInput::JoystickAxis(0, 1)],
+                vec![RawInput::Key(VKey::Up), RawInput::JoystickAxis(1, -1)],
+                vec![RawInput::Key(VKey::Down), RawInput::JoystickAxis(1, 1)],
+                vec![RawInput::Key(VKey::Space), RawInput::JoystickButton(0), RawInput::JoystickButton(1)],
+            ],
+        }
+    }
+}
+
+impl PadBinding {
+    // Falls back to the default for buttons which are missing in saved data.
+    pub fn load<S: SystemTrait>(system: &S) -> Self {
+        let mut binding = Self::default();
+        if let Some(Value::Object(map)) = system.get_value(KEY_PAD_BINDING) {
+            for (i, (_, name)) in BINDABLE_BUTTONS.iter().enumerate() {
+                if let Some(Value::Array(array)) = map.get(*name) {
+                    binding.inputs[i] = array.iter()
+                        .filter_map(|value| value.as_str().and_then(RawInput::from_name))
+                        .collect();
+                }
+            }
+        }
+        binding
+    }
+
+    pub fn save<S: SystemTrait>(&self, system: &mut S) {
+        let mut map = Map::new();
+        for (i, (_, name)) in BINDABLE_BUTTONS.iter().enumerate() {
+            let names = self.inputs[i].iter().map(|input| Value::from(input.name())).collect();
+            map.insert(String::from(*name), Value::Array(names));
+        }
+        system.set_value(KEY_PAD_BINDING, Value::Object(map));
+    }
+
+    pub fn inputs(&self, index: usize) -> &[RawInput] {
+        &self.inputs[index]
+    }
+
+    // Returns buttons bound to the input.
+    pub fn bits(&self, input: RawInput) -> PadBit {
+        BINDABLE_BUTTONS.iter().zip(self.inputs.iter())
+            .filter(|(_, inputs)| inputs.contains(&input))
+            .fold(PadBit::empty(), |bits, ((bit, _), _)| bits | *bit)
+    }
+
+    // Returns buttons bound to the axis in either direction.
+    pub fn axis_bits(&self, axis: u8) -> PadBit {
+        self.bits(RawInput::JoystickAxis(axis, -1)) | self.bits(RawInput::JoystickAxis(axis, 1))
+    }
+
+    // Binds the input to the button, replacing the same kind of inputs (key or joystick) of it.
+    // The input is unbound from other buttons.
+    pub fn bind(&mut self, index: usize, input: RawInput) {
+        for inputs in self.inputs.iter_mut() {
+            inputs.retain(|x| *x != input);
+        }
+        let inputs = &mut self.inputs[index];
+        inputs.retain(|x| x.is_joystick() != input.is_joystick());
+        inputs.push(input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::headless::NullSystem;
+
+    #[test]
+    fn test_name() {
+        for input in &[RawInput::Key(VKey::Num2), RawInput::JoystickButton(12), RawInput::JoystickAxis(3, -1), RawInput::JoystickAxis(0, 1)] {
+            assert_eq!(Some(*input), RawInput::from_name(&input.name()));
+        }
+        assert_eq!(None, RawInput::from_name("AXIS1"));
+        assert_eq!(None, RawInput::from_name("BUTTONX"));
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut binding = PadBinding::default();
+        assert_eq!(PadBit::A, binding.bits(RawInput::JoystickButton(1)));
+        assert_eq!(PadBit::L | PadBit::R, binding.axis_bits(0));
+
+        binding.bind(0, RawInput::Key(VKey::A));
+        binding.bind(4, RawInput::Key(VKey::Left));
+        assert_eq!(&[RawInput::JoystickAxis(0, -1), RawInput::Key(VKey::A)], binding.inputs(0));
+        assert_eq!(&[RawInput::JoystickButton(0), RawInput::JoystickButton(1), RawInput::Key(VKey::Left)], binding.inputs(4));
+        assert_eq!(PadBit::A, binding.bits(RawInput::Key(VKey::Left)));
+        assert_eq!(PadBit::empty(), binding.bits(RawInput::Key(VKey::Space)));
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut system = NullSystem::default();
+        let mut binding = PadBinding::default();
+        binding.bind(2, RawInput::Key(VKey::W));
+        binding.bind(4, RawInput::JoystickButton(5));
+        binding.save(&mut system);
+        assert_eq!(binding, PadBinding::load(&system));
+
+        system.set_str(KEY_PAD_BINDING, "broken");
+        assert_eq!(PadBinding::default(), PadBinding::load(&system));
+    }
+}
@@ -0,0 +1,32 @@
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro128Plus;
+
+pub fn create_rng(seed: u64) -> Xoshiro128Plus {
+    Xoshiro128Plus::seed_from_u64(seed)
+}
+
+// Derive an independent generator, so each consumer keeps its own sequence.
+pub fn derive_rng<R: Rng>(rng: &mut R) -> Xoshiro128Plus {
+    Xoshiro128Plus::from_seed(rng.gen())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed() {
+        let mut rng1 = create_rng(12345);
+        let mut rng2 = create_rng(12345);
+        for _ in 0..16 {
+            assert_eq!(rng1.gen::<u32>(), rng2.gen::<u32>());
+        }
+    }
+
+    #[test]
+    fn test_derive_rng() {
+        let mut derived1 = derive_rng(&mut create_rng(1));
+        let mut derived2 = derive_rng(&mut create_rng(1));
+        assert_eq!(derived1.gen::<u64>(), derived2.gen::<u64>());
+    }
+}
@@ -0,0 +1,141 @@
+// Sound synthesizer like the Namco WSG: each voice plays a 32 step, 4 bit waveform
+// (or a noise), sweeping the frequency and the volume.
+
+pub type Wavetable = [u8; 32];
+
+pub const WAVE_SQUARE: Wavetable = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+pub const WAVE_TRIANGLE: Wavetable = [
+    8, 9, 10, 11, 12, 13, 14, 15, 15, 14, 13, 12, 11, 10, 9, 8,
+    7, 6, 5, 4, 3, 2, 1, 0, 0, 1, 2, 3, 4, 5, 6, 7,
+];
+pub const WAVE_SAW: Wavetable = [
+    0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7,
+    8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13, 14, 14, 15, 15,
+];
+
+pub const MAX_LEVEL: u8 = 15;
+
+const VOICE_SCALE: i32 = 80;  // Up to 3 voices fit in 16 bits.
+
+#[derive(Clone, Copy, Debug)]
+pub enum Tone {
+    Wave(&'static Wavetable),
+    Noise,  // Frequency is the rate of the noise generator.
+}
+
+// Sweeps the frequency (Hz) and the volume (0 - `MAX_LEVEL`) linearly.
+#[derive(Clone, Copy, Debug)]
+pub struct Segment {
+    pub duration_ms: u32,
+    pub freq: (f32, f32),
+    pub level: (u8, u8),
+}
+
+pub const fn sweep(duration_ms: u32, freq0: f32, freq1: f32, level0: u8, level1: u8) -> Segment {
+    Segment { duration_ms, freq: (freq0, freq1), level: (level0, level1) }
+}
+
+pub const fn note(duration_ms: u32, freq: f32, level: u8) -> Segment {
+    sweep(duration_ms, freq, freq, level, level)
+}
+
+pub const fn rest(duration_ms: u32) -> Segment {
+    sweep(duration_ms, 0.0, 0.0, 0, 0)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Voice {
+    pub tone: Tone,
+    pub segments: &'static [Segment],
+}
+
+// Voices played at once.
+#[derive(Clone, Copy, Debug)]
+pub struct Patch {
+    pub voices: &'static [Voice],
+}
+
+impl Patch {
+    pub fn duration_ms(&self) -> u32 {
+        self.voices.iter().map(|voice| voice.segments.iter().map(|seg| seg.duration_ms).sum()).max().unwrap_or(0)
+    }
+}
+
+// Renders into signed 16 bit mono PCM.
+pub fn render(patch: &Patch, sample_rate: u32) -> Vec<i16> {
+    let len = (patch.duration_ms() as u64 * sample_rate as u64 / 1000) as usize;
+    let mut buffer = vec![0i32; len];
+    for voice in patch.voices {
+        render_voice(voice, sample_rate, &mut buffer);
+    }
+    buffer.iter().map(|&sample| sample.clamp(i16::MIN as i32, i16::MAX as i32) as i16).collect()
+}
+
+fn render_voice(voice: &Voice, sample_rate: u32, buffer: &mut [i32]) {
+    let mut phase = 0.0;  // In waveform steps.
+    let mut lfsr: u16 = 1;
+    let mut noise = 0;
+    let mut start_ms = 0;
+    for seg in voice.segments {
+        let start = (start_ms as u64 * sample_rate as u64 / 1000) as usize;
+        start_ms += seg.duration_ms;
+        let end = std::cmp::min((start_ms as u64 * sample_rate as u64 / 1000) as usize, buffer.len());
+        let count = (end - start).max(1) as f32;
+        for (i, sample) in buffer[start..end].iter_mut().enumerate() {
+            let t = i as f32 / count;
+            let freq = seg.freq.0 + (seg.freq.1 - seg.freq.0) * t;
+            let level = seg.level.0 as f32 + (seg.level.1 as f32 - seg.level.0 as f32) * t;
+
+            let next = phase + freq * 32.0 / sample_rate as f32;
+            let steps = next as u32 - phase as u32;
+            phase = next % 32.0;
+            let value = match voice.tone {
+                Tone::Wave(table) => table[phase as usize] as i32 - 8,
+                Tone::Noise => {
+                    // Clocked on each step, with the 15 bit LFSR.
+                    for _ in 0..steps {
+                        let bit = (lfsr ^ (lfsr >> 1)) & 1;
+                        lfsr = (lfsr >> 1) | (bit << 14);
+                        noise = if lfsr & 1 != 0 { 7 } else { -8 };
+                    }
+                    noise
+                }
+            };
+            *sample += value * (level.round() as i32) * VOICE_SCALE;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length() {
+        const VOICES: [Voice; 2] = [
+            Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[note(100, 440.0, 15), rest(50)] },
+            Voice { tone: Tone::Noise, segments: &[sweep(120, 4000.0, 100.0, 15, 0)] },
+        ];
+        let patch = Patch { voices: &VOICES };
+        assert_eq!(150, patch.duration_ms());
+
+        let pcm = render(&patch, 8000);
+        assert_eq!(1200, pcm.len());
+        assert!(pcm[..800].iter().any(|&sample| sample > 0));
+        assert!(pcm[960..].iter().all(|&sample| sample == 0));
+    }
+
+    #[test]
+    fn test_square() {
+        const VOICES: [Voice; 1] = [
+            Voice { tone: Tone::Wave(&WAVE_SQUARE), segments: &[note(10, 100.0, MAX_LEVEL)] },
+        ];
+        let pcm = render(&Patch { voices: &VOICES }, 3200);
+        // 32 samples per cycle, high in the first half.
+        assert_eq!(7 * MAX_LEVEL as i32 * VOICE_SCALE, pcm[0] as i32);
+        assert_eq!(-8 * MAX_LEVEL as i32 * VOICE_SCALE, pcm[16] as i32);
+    }
+}
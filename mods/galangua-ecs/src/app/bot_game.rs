@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use super::galangua_ecs_app::Game;
+
+use galangua_common::app::bot::BotEnv;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::app::game::star_manager::StarManager;
+use galangua_common::app::game_config::GameConfig;
+use galangua_common::app::score_holder::ScoreHolder;
+use galangua_common::framework::SystemTrait;
+use galangua_common::util::pad::{Pad, PadBit};
+use galangua_common::util::rng::{create_rng, derive_rng};
+
+// Single game without the title and the rendering, to be played by a bot.
+pub struct BotGame<S: SystemTrait> {
+    game: Game,
+    pad: Pad,
+    system: S,
+}
+
+impl<S: SystemTrait> BotGame<S> {
+    pub fn new(seed: u64, config: GameConfig, two_players: bool, system: S) -> Self {
+        let mut rng = create_rng(seed);
+        let star_manager = StarManager::new(derive_rng(&mut rng));
+        let stage_definition = Arc::new(StageDefinition::default());
+
+        Self {
+            game: Game::new(&star_manager, 0, two_players, config, &stage_definition, seed),
+            pad: Pad::default(),
+            system,
+        }
+    }
+
+    pub fn score_holder(&self) -> ScoreHolder {
+        self.game.get_score_holder().unwrap()
+    }
+
+    // Rank of the current player.
+    pub fn rank(&self) -> DifficultyRank {
+        self.game.get_rank().unwrap()
+    }
+
+    pub fn system(&self) -> &S {
+        &self.system
+    }
+}
+
+impl<S: SystemTrait> BotEnv for BotGame<S> {
+    fn observe(&self) -> Observation {
+        self.game.observe()
+    }
+
+    fn step(&mut self, bits: PadBit) -> bool {
+        self.pad.set_bits(bits);
+        self.pad.update();
+
+        self.game.update(&self.pad, &mut self.system)
+    }
+}
@@ -1,24 +1,28 @@
 use legion::*;
+use serde::{Deserialize, Serialize};
 
 use galangua_common::app::game::traj::Traj;
 use galangua_common::app::game::{EnemyType, FormationIndex};
+use galangua_common::app::util::snapshot;
 use galangua_common::framework::types::Vec2I;
 
 //
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Posture(pub Vec2I, pub i32);
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct Speed(pub i32, pub i32);
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct CollRect {
     pub offset: Vec2I,
     pub size: Vec2I,
 }
 
 //
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PlayerState {
     Normal,
     Dead,
@@ -27,6 +31,7 @@ pub enum PlayerState {
     EscapeCapturing,
     MoveHomePos,
 }
+// Components referring to other entities are saved through `snapshot`.
 pub struct Player {
     pub state: PlayerState,
     pub count: u32,
@@ -35,6 +40,7 @@ pub struct Player {
 }
 
 //
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum RecapturedFighterState {
     Rotate,
     SlideHorz,
@@ -54,12 +60,14 @@ pub struct MyShot {
 }
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct Enemy {
     pub enemy_type: EnemyType,
     pub formation_index: FormationIndex,
     pub is_formation: bool,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct EnemyBase {
     pub traj: Option<Traj>,
     pub shot_wait: Option<u32>,
@@ -69,12 +77,12 @@ pub struct EnemyBase {
 }
 
 //
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ZakoAttackType {
     BeeAttack,
     Traj,
 }
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub enum ZakoState {
     Appearance,
     MoveToFormation,
@@ -83,6 +91,7 @@ pub enum ZakoState {
     Attack(ZakoAttackType),
     Troop,
 }
+#[derive(Serialize, Deserialize)]
 pub struct Zako {
     pub base: EnemyBase,
     pub state: ZakoState,
@@ -91,7 +100,7 @@ pub struct Zako {
 }
 
 //
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum OwlCaptureAttackPhase {
     Capture,
     CaptureBeam,
@@ -101,7 +110,7 @@ pub enum OwlCaptureAttackPhase {
     CaptureDoneBack,
     CaptureDonePushUp,
 }
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum OwlState {
     Appearance,
     MoveToFormation,
@@ -110,7 +119,7 @@ pub enum OwlState {
     TrajAttack,
     CaptureAttack(OwlCaptureAttackPhase),
 }
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub enum OwlCapturingState {
     None,
     Attacking,
@@ -118,6 +127,7 @@ pub enum OwlCapturingState {
     //Captured,
     Failed,
 }
+#[derive(Serialize, Deserialize)]
 pub struct Owl {
     pub base: EnemyBase,
     pub state: OwlState,
@@ -130,7 +140,7 @@ pub struct Owl {
 
 //
 pub const TRACTOR_BEAM_SPRITE_COUNT: usize = 29;
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TractorBeamState {
     Opening,
     Full,
@@ -149,7 +159,7 @@ pub struct TractorBeam {
 }
 
 //
-const MAX_TROOPS: usize = 3;
+pub const MAX_TROOPS: usize = 3;
 pub struct Troops {
     pub members: [Option<Troop>; MAX_TROOPS],
     pub copy_angle_to_troops: bool,
@@ -161,10 +171,13 @@ pub struct Troop {
 }
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct EneShot(pub Vec2I);
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct SequentialSpriteAnime {
+    #[serde(with = "snapshot::sprite_anime")]
     pub sprites: &'static [&'static str],
     pub frame_wait: u32,
     pub delay: u32,
@@ -173,10 +186,13 @@ pub struct SequentialSpriteAnime {
 }
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct SpriteDrawable {
-    pub sprite_name: &'static str,
+    #[serde(with = "snapshot::sprite_name")]
+    pub sprite_name: snapshot::StaticStr,
     pub offset: Vec2I,
 }
 
 //
+#[derive(Serialize, Deserialize)]
 pub struct SpriteColor(pub u8, pub u8, pub u8);
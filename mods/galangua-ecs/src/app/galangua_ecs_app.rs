@@ -1,36 +1,69 @@
 use legion::*;
+use rand::Rng;
+use rand_xoshiro::Xoshiro128Plus;
+use serde_json::json;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
+use galangua_common::app::attract_mode::{attract_phase, ranking_start_frame, AttractPhase, DemoPlay, DEMO_SEED};
+use galangua_common::app::audio_volume::AudioVolume;
 use galangua_common::app::consts::*;
 use galangua_common::app::game::appearance_manager::AppearanceManager;
 use galangua_common::app::game::attack_manager::AttackManager;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
 use galangua_common::app::game::formation::Formation;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
 use galangua_common::app::game::stage_indicator::StageIndicator;
 use galangua_common::app::game::star_manager::StarManager;
+use galangua_common::app::game_config::GameConfig;
+use galangua_common::app::high_score_table::HighScoreTable;
+use galangua_common::app::lifetime_stats::LifetimeStats;
+use galangua_common::app::name_entry::NameEntry;
+use galangua_common::app::options_screen::OptionsScreen;
+use galangua_common::app::pause_menu::{PauseCommand, PauseMenu};
+use galangua_common::app::results_screen::ResultsScreen;
+use galangua_common::app::scene::Scene;
 use galangua_common::app::score_holder::ScoreHolder;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::{AppTrait, RendererTrait, SystemTrait, VKey};
 use galangua_common::util::fps_calc::{FpsCalc, TimerTrait};
 use galangua_common::util::pad::{Pad, PadBit};
+use galangua_common::util::pad_binding::PadBinding;
+use galangua_common::util::rng::{create_rng, derive_rng};
 
 use super::components::*;
 use super::resources::*;
+use super::snapshot::{load_resources, load_world, save_resources, save_world};
 use super::system::system_player::*;
 use super::system::*;
 
 enum AppState {
     Title(Title),
+    Demo(Demo),
     Game(Game),
+    Results(ResultsScreen),
+    NameEntry(NameEntry),
+    Options(OptionsScreen),
 }
 
 pub struct GalanguaEcsApp<T: TimerTrait, S: SystemTrait> {
     system: S,
     pressed_key: Option<VKey>,
     state: AppState,
+    pause_menu: Option<PauseMenu>,
     pad: Pad,
     star_manager: StarManager,
     score_holder: ScoreHolder,
+    high_score_table: HighScoreTable,
+    lifetime_stats: LifetimeStats,
+    game_config: GameConfig,
+    audio_volume: AudioVolume,
     fps_calc: FpsCalc<T>,
+    stage_definition: Arc<StageDefinition>,
+    quick_save: Option<String>,
+    snapshot_error: Option<String>,  // Last failure of quick save or load.
+    rng: Xoshiro128Plus,
 
     #[cfg(debug_assertions)]
     paused: bool,
@@ -39,19 +72,36 @@ pub struct GalanguaEcsApp<T: TimerTrait, S: SystemTrait> {
 }
 
 impl<T: TimerTrait, S: SystemTrait> GalanguaEcsApp<T, S> {
-    pub fn new(timer: T, system: S) -> Self {
-        let high_score = system.get_u32(&KEY_HIGH_SCORE)
-                .or(Some(DEFAULT_HIGH_SCORE))
-                .unwrap();
+    pub fn new(timer: T, system: S, seed: u64) -> Self {
+        let high_score_table = HighScoreTable::load(&system);
+        let high_score = std::cmp::max(
+            system.get_u32(&KEY_HIGH_SCORE).unwrap_or(DEFAULT_HIGH_SCORE),
+            high_score_table.top_score().unwrap_or(0));
+        let mut rng = create_rng(seed);
+        let star_manager = StarManager::new(derive_rng(&mut rng));
+        let mut pad = Pad::default();
+        pad.set_binding(PadBinding::load(&system));
+        let game_config = GameConfig::load(&system);
+        let audio_volume = AudioVolume::load(&system);
+        let lifetime_stats = LifetimeStats::load(&system);
 
         Self {
             system,
             pressed_key: None,
             state: AppState::Title(Title::new()),
-            pad: Pad::default(),
-            star_manager: StarManager::default(),
+            pause_menu: None,
+            pad,
+            star_manager,
             score_holder: ScoreHolder::new(high_score),
+            high_score_table,
+            lifetime_stats,
+            game_config,
+            audio_volume,
             fps_calc: FpsCalc::new(timer),
+            stage_definition: Arc::new(StageDefinition::default()),
+            quick_save: None,
+            snapshot_error: None,
+            rng,
 
             #[cfg(debug_assertions)]
             paused: false,
@@ -60,11 +110,167 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaEcsApp<T, S> {
         }
     }
 
-    fn start_game(&mut self) {
-        self.state = AppState::Game(Game::new(&self.star_manager, self.score_holder.high_score));
+    pub fn set_stage_definition(&mut self, definition: StageDefinition) {
+        self.stage_definition = Arc::new(definition);
+    }
+
+    pub fn observe(&self) -> Option<Observation> {
+        match &self.state {
+            AppState::Game(game) => Some(game.observe()),
+            AppState::Demo(demo) => Some(demo.game.observe()),
+            _ => None,
+        }
+    }
+
+    // Score of the running game, `None` in the demo.
+    pub fn score_holder(&self) -> Option<ScoreHolder> {
+        match &self.state {
+            AppState::Game(game) => game.get_score_holder(),
+            _ => None,
+        }
+    }
+
+    // Save state of the running game, in JSON.
+    pub fn save_snapshot(&self) -> Result<String, String> {
+        match &self.state {
+            AppState::Game(game) => game.save_snapshot().map(|snapshot| snapshot.to_string()),
+            _ => Err(String::from("Not in game")),
+        }
+    }
+
+    pub fn load_snapshot(&mut self, text: &str) -> Result<(), String> {
+        let snapshot: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if snapshot["app"] != "ecs" {
+            return Err(format!("Not a snapshot for ECS version: {}", snapshot["app"]));
+        }
+        let game = Game::from_snapshot(&snapshot, self.score_holder.high_score, &self.stage_definition)?;
+        self.state = AppState::Game(game);
+        self.pause_menu = None;
+        Ok(())
+    }
+
+    pub fn scene(&self) -> Scene {
+        match self.state {
+            AppState::Title(_) => Scene::Title,
+            AppState::Demo(_) => Scene::Demo,
+            AppState::Game(_) => Scene::Game,
+            AppState::Results(_) => Scene::Results,
+            AppState::NameEntry(_) => Scene::NameEntry,
+            AppState::Options(_) => Scene::Options,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_menu.is_some()
+    }
+
+    pub fn quick_save_slot(&self) -> Option<&str> {
+        self.quick_save.as_deref()
+    }
+
+    // Replaces the slot loaded by the key, e.g. with a snapshot kept in a file.
+    pub fn set_quick_save_slot(&mut self, snapshot: Option<String>) {
+        self.quick_save = snapshot;
+    }
+
+    // Error of the last quick save or load by the keys, which doesn't stop the app.
+    pub fn snapshot_error(&self) -> Option<&str> {
+        self.snapshot_error.as_deref()
+    }
+
+    pub fn quick_save(&mut self) -> Result<(), String> {
+        let snapshot = self.save_snapshot().map_err(|err| format!("Quick save failed: {}", err))?;
+        self.quick_save = Some(snapshot);
+        Ok(())
+    }
+
+    // Does nothing without a quick save, and keeps it even if loading fails.
+    pub fn quick_load(&mut self) -> Result<(), String> {
+        if let Some(snapshot) = self.quick_save.take() {
+            let result = self.load_snapshot(&snapshot);
+            self.quick_save = Some(snapshot);
+            result.map_err(|err| format!("Quick load failed: {}", err))?;
+            self.system.stop_all_se();  // Looping sounds must not go on.
+        }
+        Ok(())
+    }
+
+    fn start_game(&mut self, two_players: bool) {
+        self.state = AppState::Game(Game::new(&self.star_manager, self.score_holder.high_score, two_players, self.game_config, &self.stage_definition, self.rng.gen()));
+        self.pause_menu = None;
+    }
+
+    fn restart_game(&mut self) {
+        self.leave_game();
+        self.start_game(self.score_holder.is_two_players());
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.pause_menu.is_some() {
+            self.resume();
+        } else {
+            self.pause_menu = Some(PauseMenu::default());
+            self.system.stop_all_se();
+        }
+    }
+
+    fn resume(&mut self) {
+        self.pause_menu = None;
+        if let AppState::Game(game) = &self.state {
+            game.resume_se(&mut self.system);
+        }
+    }
+
+    // Plays the same game every time, not to affect the score.
+    fn start_demo(&mut self) {
+        let game = Game::new(&self.star_manager, self.score_holder.high_score, false, GameConfig::default(), &self.stage_definition, DEMO_SEED);
+        self.state = AppState::Demo(Demo { game, play: DemoPlay::default() });
+    }
+
+    // Shows the results of the game at first.
+    fn end_game(&mut self) {
+        self.leave_game();
+        self.lifetime_stats.add_game(&self.score_holder);
+        self.lifetime_stats.save(&mut self.system).expect("lifetime stats");
+        self.state = AppState::Results(ResultsScreen::new(&self.score_holder, &self.lifetime_stats));
+    }
+
+    // Enters initials if the score ranks in, otherwise goes back to the title.
+    fn enter_name_entry(&mut self) {
+        self.state = AppState::Title(Title::new());
+        if let Some(name_entry) = NameEntry::new(&self.score_holder, &self.high_score_table) {
+            self.state = AppState::NameEntry(name_entry);
+        }
     }
 
     fn back_to_title(&mut self) {
+        self.leave_game();
+        match &self.state {
+            AppState::NameEntry(name_entry) => {
+                // Shows the ranking at first after initials are entered.
+                let highlight = name_entry.last_rank();
+                self.high_score_table.save(&mut self.system).expect("high score table");
+                let mut title = Title::new();
+                if highlight.is_some() {
+                    title.show_ranking(highlight);
+                }
+                self.state = AppState::Title(title);
+            }
+            AppState::Options(_) => self.state = AppState::Title(Title::new()),
+            AppState::Demo(demo) => {
+                if let Some(star_manager) = demo.game.get_star_manager() {
+                    self.star_manager = star_manager.clone();  // Write back.
+                }
+                self.star_manager.set_stop(false);
+                self.state = AppState::Title(Title::new());
+            }
+            _ => {}
+        }
+    }
+
+    fn leave_game(&mut self) {
+        self.system.stop_all_se();  // Looping sounds must not go on.
+        self.pause_menu = None;
         let mut high_score_updated = false;
         if let AppState::Game(game_state) = &mut self.state {
             if let Some(score_holder) = game_state.get_score_holder() {
@@ -100,15 +306,25 @@ impl<R: RendererTrait, T: TimerTrait, S: SystemTrait> AppTrait<R> for GalanguaEc
         }
     }
 
-    fn on_joystick_axis(&mut self, _axis_index: u8, _dir: i8) {
+    fn on_joystick_axis(&mut self, axis_index: u8, dir: i8) {
+        self.pad.on_joystick_axis(axis_index, dir);
+    }
+
+    fn on_joystick_button(&mut self, button_index: u8, down: bool) {
+        self.pad.on_joystick_button(button_index, down);
     }
 
-    fn on_joystick_button(&mut self, _button_index: u8, _down: bool) {
+    fn on_focus(&mut self, focused: bool) {
+        if !focused && matches!(self.state, AppState::Game(_)) && self.pause_menu.is_none() {
+            self.toggle_pause();
+        }
     }
 
     fn init(&mut self, renderer: &mut R) {
         renderer.load_textures("assets", &["chr.png", "font.png"]);
         renderer.load_sprite_sheet("assets/chr.json");
+        self.system.load_sounds(&SOUND_ASSETS);
+        self.audio_volume.apply(&mut self.system);
     }
 
     fn update(&mut self) -> bool {
@@ -120,10 +336,22 @@ impl<R: RendererTrait, T: TimerTrait, S: SystemTrait> AppTrait<R> for GalanguaEc
                     self.pressed_key = None;
                     return false;
                 }
+                AppState::Game(_game) => self.toggle_pause(),
+                // Skipping the results must not skip entering initials.
+                AppState::Results(_) => self.enter_name_entry(),
                 _ => self.back_to_title(),
             }
         }
 
+        let snapshot_result = match self.pressed_key {
+            Some(VKey::F5) => self.quick_save(),
+            Some(VKey::F9) => self.quick_load(),
+            _ => Ok(()),
+        };
+        if let Err(err) = snapshot_result {
+            self.snapshot_error = Some(err);
+        }
+
         #[cfg(debug_assertions)]
         {
             if self.pressed_key == Some(VKey::Return) {
@@ -135,18 +363,63 @@ impl<R: RendererTrait, T: TimerTrait, S: SystemTrait> AppTrait<R> for GalanguaEc
             }
         }
 
+        // The game and the stars are frozen while paused.
+        if let Some(pause_menu) = &mut self.pause_menu {
+            match pause_menu.update(&self.pad) {
+                Some(PauseCommand::Resume) => self.resume(),
+                Some(PauseCommand::Restart) => self.restart_game(),
+                Some(PauseCommand::Quit) => self.back_to_title(),
+                None => {}
+            }
+            self.pressed_key = None;
+            return true;
+        }
+
         match &mut self.state {
             AppState::Title(title) => {
-                if let Some(value) = title.update(&self.pad, &mut self.star_manager) {
-                    if value {
-                        self.start_game();
-                    } else {
-                        return false;
-                    }
+                if let Some(player_count) = title.update(&self.pad, self.pressed_key, &mut self.star_manager) {
+                    self.start_game(player_count == 2);
+                } else if self.pressed_key == Some(VKey::O) {
+                    self.state = AppState::Options(OptionsScreen::new(&self.game_config, &self.audio_volume, self.pad.binding()));
+                } else if title.is_demo_time() {
+                    self.start_demo();
+                }
+            }
+            AppState::Demo(demo) => {
+                // Any key exits the demo.
+                if self.pressed_key.is_some() || self.pad.raw_trigger().is_some() || !demo.update(&mut self.system) {
+                    self.back_to_title();
                 }
             }
             AppState::Game(game) => {
                 if !game.update(&self.pad, &mut self.system) {
+                    self.end_game();
+                }
+            }
+            AppState::Results(results_screen) => {
+                self.star_manager.update();
+                if !results_screen.update(&self.pad) {
+                    self.enter_name_entry();
+                }
+            }
+            AppState::NameEntry(name_entry) => {
+                self.star_manager.update();
+                if !name_entry.update(&self.pad, &mut self.high_score_table) {
+                    self.back_to_title();
+                }
+            }
+            AppState::Options(options_screen) => {
+                self.star_manager.update();
+                if !options_screen.update(&self.pad) {
+                    self.game_config = *options_screen.config();
+                    self.audio_volume = *options_screen.volume();
+                    self.pad.set_binding(options_screen.binding().clone());
+                    self.audio_volume.apply(&mut self.system);
+                    self.system.begin_transaction();
+                    self.game_config.save(&mut self.system).expect("game config");
+                    self.audio_volume.save(&mut self.system).expect("audio volume");
+                    self.pad.binding().save(&mut self.system);
+                    self.system.commit_transaction();
                     self.back_to_title();
                 }
             }
@@ -157,8 +430,37 @@ impl<R: RendererTrait, T: TimerTrait, S: SystemTrait> AppTrait<R> for GalanguaEc
 
     fn draw(&mut self, renderer: &mut R) {
         match &self.state {
-            AppState::Title(title) => title.draw(&self.star_manager, &self.score_holder, self.system.is_touch_device(), renderer),
-            AppState::Game(game) => game.draw(renderer),
+            AppState::Title(title) => title.draw(&self.star_manager, &self.score_holder, &self.high_score_table, self.system.is_touch_device(), renderer),
+            AppState::Demo(demo) => {
+                demo.game.draw(renderer);
+                demo.play.draw(renderer);
+            }
+            AppState::Game(game) => {
+                game.draw(renderer);
+                if let Some(pause_menu) = &self.pause_menu {
+                    pause_menu.draw(renderer);
+                }
+            }
+            AppState::Results(results_screen) => {
+                renderer.set_draw_color(0, 0, 0);
+                renderer.clear();
+                self.star_manager.draw(renderer);
+                results_screen.draw(renderer);
+                self.score_holder.draw(renderer, true);
+            }
+            AppState::NameEntry(name_entry) => {
+                renderer.set_draw_color(0, 0, 0);
+                renderer.clear();
+                self.star_manager.draw(renderer);
+                name_entry.draw(renderer);
+                self.score_holder.draw(renderer, true);
+            }
+            AppState::Options(options_screen) => {
+                renderer.set_draw_color(0, 0, 0);
+                renderer.clear();
+                self.star_manager.draw(renderer);
+                options_screen.draw(renderer);
+            }
         }
 
         self.fps_calc.update();
@@ -173,32 +475,53 @@ impl<R: RendererTrait, T: TimerTrait, S: SystemTrait> AppTrait<R> for GalanguaEc
 
 struct Title {
     frame_count: u32,
+    ranking_highlight: Option<usize>,
 }
 
 impl Title {
     fn new() -> Self {
         Self {
             frame_count: 0,
+            ranking_highlight: None,
         }
     }
 
-    fn update(&mut self, pad: &Pad, star_manager: &mut StarManager) -> Option<bool> {
+    fn show_ranking(&mut self, highlight: Option<usize>) {
+        self.frame_count = ranking_start_frame();
+        self.ranking_highlight = highlight;
+    }
+
+    fn is_demo_time(&self) -> bool {
+        attract_phase(self.frame_count) == AttractPhase::Demo
+    }
+
+    // Returns player count to start.
+    fn update(&mut self, pad: &Pad, pressed_key: Option<VKey>, star_manager: &mut StarManager) -> Option<u32> {
         self.frame_count = self.frame_count.wrapping_add(1);
 
         star_manager.update();
 
         if pad.is_trigger(PadBit::A) {
-            return Some(true);
+            return Some(1);
+        }
+        if pressed_key == Some(VKey::Num2) {
+            return Some(2);
         }
         None
     }
 
-    fn draw<R: RendererTrait>(&self, star_manager: &StarManager, score_holder: &ScoreHolder, is_touch_device: bool, renderer: &mut R) {
+    fn draw<R: RendererTrait>(&self, star_manager: &StarManager, score_holder: &ScoreHolder, high_score_table: &HighScoreTable, is_touch_device: bool, renderer: &mut R) {
         renderer.set_draw_color(0, 0, 0);
         renderer.clear();
 
         star_manager.draw(renderer);
 
+        if attract_phase(self.frame_count) == AttractPhase::Ranking {
+            high_score_table.draw(renderer, self.ranking_highlight);
+            score_holder.draw(renderer, true);
+            return;
+        }
+
         renderer.set_texture_color_mod("font", 255, 255, 255);
         renderer.draw_str("font", 10 * 8, 8 * 8, "GALANGUA");
 
@@ -209,6 +532,12 @@ impl Title {
                 "PRESS SPACE KEY TO START"
             };
             renderer.draw_str("font", (28 - msg.len() as i32) / 2 * 8, 25 * 8, msg);
+            if !is_touch_device {
+                let msg = "PRESS 2 KEY FOR 2 PLAYERS";
+                renderer.draw_str("font", (28 - msg.len() as i32) / 2 * 8, 27 * 8, msg);
+                let msg = "PRESS O KEY FOR OPTIONS";
+                renderer.draw_str("font", (28 - msg.len() as i32) / 2 * 8, 29 * 8, msg);
+            }
         }
         score_holder.draw(renderer, true);
 
@@ -217,47 +546,45 @@ impl Title {
     }
 }
 
-struct Game {
+struct Demo {
+    game: Game,
+    play: DemoPlay,
+}
+
+impl Demo {
+    // Returns `false` when the demo ends.
+    fn update<S: SystemTrait>(&mut self, system: &mut S) -> bool {
+        let observation = self.game.observe();
+        let pad = self.play.update(&observation);
+        self.game.update(pad, system) && !self.play.is_over()
+    }
+}
+
+pub(super) struct Game {
     world: World,
     resources: Resources,
     schedule: Schedule,
 }
 
 impl Game {
-    fn new(star_manager: &StarManager, high_score: u32) -> Self {
-        let schedule = Schedule::builder()
-            .add_system(update_game_controller_system())
-            .add_system(move_star_system())
-            .add_system(move_player_system())
-            .add_system(fire_myshot_system())
-            .add_system(move_myshot_system())
-            .add_system(move_formation_system())
-            .add_system(run_appearance_manager_system())
-            .flush()
-            .add_system(run_attack_manager_system())
-            .add_system(move_zako_system())
-            .add_system(animate_zako_system())
-            .add_system(move_owl_system())
-            .add_system(animate_owl_system())
-            .add_system(move_troops_system())
-            .add_system(move_tractor_beam_system())
-            .add_system(spawn_eneshot_system())
-            .add_system(move_eneshot_system())
-            .add_system(coll_check_myshot_enemy_system())
-            .add_system(coll_check_player_enemy_system())
-            .add_system(coll_check_player_eneshot_system())
-            .add_system(recapture_fighter_system())
-            .add_system(move_sequential_anime_system())
-            .build();
+    pub(super) fn new(star_manager: &StarManager, high_score: u32, two_players: bool, config: GameConfig, stage_definition: &Arc<StageDefinition>, seed: u64) -> Self {
+        let schedule = create_schedule();
+
+        // Derive in the same order as the OO version, to share random sequences.
+        let mut rng = create_rng(seed);
+        let mut appearance_manager = AppearanceManager::new(derive_rng(&mut rng));
+        appearance_manager.set_stage_definition(stage_definition.clone());
+        let attack_manager = AttackManager::new(derive_rng(&mut rng));
+        let eneshot_spawner = EneShotSpawner::new(derive_rng(&mut rng));
 
         let mut resources = Resources::default();
         resources.insert(star_manager.clone());
         resources.insert(StageIndicator::default());
         resources.insert(Formation::default());
-        resources.insert(AppearanceManager::default());
-        resources.insert(AttackManager::default());
-        resources.insert(EneShotSpawner::default());
-        resources.insert(GameInfo::new(high_score));
+        resources.insert(appearance_manager);
+        resources.insert(attack_manager);
+        resources.insert(eneshot_spawner);
+        resources.insert(GameInfo::new(high_score, two_players, config, rng));
         resources.insert(SoundQueue::new());
 
         let mut world = World::default();
@@ -275,7 +602,31 @@ impl Game {
         }
     }
 
-    fn update<S: SystemTrait>(&mut self, pad: &Pad, system: &mut S) -> bool {
+    fn from_snapshot(snapshot: &serde_json::Value, high_score: u32, stage_definition: &Arc<StageDefinition>) -> Result<Self, String> {
+        let resources = load_resources(&snapshot["resources"], stage_definition)?;
+        let world = load_world(&snapshot["entities"])?;
+        {
+            let mut game_info = resources.get_mut::<GameInfo>().unwrap();
+            let score_holder = &mut game_info.score_holder;
+            score_holder.high_score = std::cmp::max(score_holder.high_score, high_score);
+        }
+
+        Ok(Self {
+            world,
+            resources,
+            schedule: create_schedule(),
+        })
+    }
+
+    fn save_snapshot(&self) -> Result<serde_json::Value, String> {
+        Ok(json!({
+            "app": "ecs",
+            "resources": save_resources(&self.resources)?,
+            "entities": save_world(&self.world)?,
+        }))
+    }
+
+    pub(super) fn update<S: SystemTrait>(&mut self, pad: &Pad, system: &mut S) -> bool {
         self.resources.insert(pad.clone());
 
         self.schedule.execute(&mut self.world, &mut self.resources);
@@ -296,12 +647,58 @@ impl Game {
         draw_system(&self.world, &self.resources, renderer);
     }
 
-    fn get_score_holder(&self) -> Option<ScoreHolder> {
+    // Restarts looping sounds stopped by the pause.
+    fn resume_se<S: SystemTrait>(&self, system: &mut S) {
+        self.resources.get::<SoundQueue>().unwrap().resume(system);
+    }
+
+    pub(super) fn observe(&self) -> Observation {
+        observe(&self.world, &self.resources)
+    }
+
+    pub(super) fn get_score_holder(&self) -> Option<ScoreHolder> {
         self.resources.get::<GameInfo>()
             .map(|game_info| game_info.score_holder.clone())
     }
 
+    pub(super) fn get_rank(&self) -> Option<DifficultyRank> {
+        self.resources.get::<GameInfo>()
+            .map(|game_info| game_info.rank)
+    }
+
     fn get_star_manager<'a>(&'a self) -> Option<legion::systems::Fetch<'_, StarManager>> {
         self.resources.get::<StarManager>()
     }
 }
+
+fn create_schedule() -> Schedule {
+    Schedule::builder()
+        .add_system(move_star_system())
+        .add_system(move_player_system())
+        .add_system(recapture_fighter_system())
+        .add_system(move_myshot_system())
+        .flush()
+        .add_system(fire_myshot_system())
+        .add_system(move_formation_system())
+        .add_system(run_appearance_manager_system())
+        .flush()
+        .add_system(run_attack_manager_system())
+        .flush()
+        .add_system(move_enemy_system())
+        .add_system(animate_zako_system())
+        .add_system(animate_owl_system())
+        .flush()
+        .add_system(move_troops_system())
+        .add_system(move_tractor_beam_system())
+        .add_system(move_eneshot_system())
+        .flush()
+        .add_system(spawn_eneshot_system())
+        .flush()
+        .add_system(coll_check_myshot_enemy_system())
+        .add_system(coll_check_player_enemy_system())
+        .add_system(coll_check_player_eneshot_system())
+        .add_system(move_sequential_anime_system())
+        .flush()
+        .add_system(update_game_controller_system())
+        .build()
+}
@@ -1,6 +1,9 @@
+mod bot_game;
 mod components;
 mod galangua_ecs_app;
 mod resources;
+mod snapshot;
 mod system;
 
+pub use self::bot_game::BotGame;
 pub use self::galangua_ecs_app::GalanguaEcsApp;
@@ -1,42 +1,51 @@
 use legion::systems::CommandBuffer;
 use legion::world::SubWorld;
 use legion::*;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_xoshiro::Xoshiro128Plus;
+use serde::{Deserialize, Serialize};
 
 use galangua_common::app::consts::*;
 use galangua_common::app::game::appearance_manager::AppearanceManager;
 use galangua_common::app::game::attack_manager::AttackManager;
+use galangua_common::app::game::challenging_stage::ChallengingResult;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
 use galangua_common::app::game::formation::Formation;
+use galangua_common::app::game::player_state;
 use galangua_common::app::game::stage_indicator::StageIndicator;
 use galangua_common::app::game::star_manager::StarManager;
 use galangua_common::app::game::{CaptureState, FormationIndex};
+use galangua_common::app::game_config::GameConfig;
 use galangua_common::app::score_holder::ScoreHolder;
+use galangua_common::app::util::looping_se::LoopingSe;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::SystemTrait;
 use galangua_common::util::math::{atan2_lut, calc_velocity, clamp, ANGLE, ONE};
 
 use super::components::*;
-use super::system::system_player::{enable_player_shot, enum_player_target_pos, restart_player};
+use super::system::system_player::{delete_myshot, enable_player_shot, enum_player_target_pos, restart_player};
 
 const WAIT1: u32 = 60;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub enum GameState {
     StartStage,
     Playing,
     PlayerDead,
     WaitReady,
     WaitReady2,
+    SwitchPlayer,
     Capturing,
     Captured,
+    CaptureCompleted,
     Recapturing,
+    ChallengingResult,
     StageClear,
     GameOver,
     Finished,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum StageState {
     APPEARANCE,
     NORMAL,
@@ -44,6 +53,7 @@ pub enum StageState {
     CLEARED,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct GameInfo {
     pub stage: u16,
     pub left_ship: u32,
@@ -53,25 +63,55 @@ pub struct GameInfo {
     pub capture_state: CaptureState,
     pub capture_enemy_fi: FormationIndex,
     pub alive_enemy_count: u32,
+    pub hit_count: u32,
+    pub challenging_result: ChallengingResult,
     pub score_holder: ScoreHolder,
     pub frame_count: u32,
+    pub rng: Xoshiro128Plus,
+    other_player: Option<player_state::PlayerState>,
+    resume_state: Option<player_state::PlayerState>,
+    #[serde(default)]
+    pub config: GameConfig,
+    #[serde(default)]
+    pub rank: DifficultyRank,
+    // Resumes attacks after the attack manager of this frame, same as the event in OO version.
+    #[serde(default)]
+    resume_attack: bool,
 }
 
 impl GameInfo {
-    pub fn new(high_score: u32) -> Self {
+    pub fn new(high_score: u32, two_players: bool, config: GameConfig, rng: Xoshiro128Plus) -> Self {
         let stage = 0;
+        let mut score_holder = ScoreHolder::new(high_score);
+        score_holder.set_two_players(two_players);
 
         GameInfo {
             stage,
-            left_ship: DEFAULT_LEFT_SHIP,
+            left_ship: config.start_ships,
             game_state: GameState::StartStage,
             count: 0,
             stage_state: StageState::APPEARANCE,
             capture_state: CaptureState::NoCapture,
             capture_enemy_fi: FormationIndex(0, 0),
             alive_enemy_count: 0,
-            score_holder: ScoreHolder::new(high_score),
+            hit_count: 0,
+            challenging_result: ChallengingResult::default(),
+            score_holder,
             frame_count: 0,
+            rng,
+            other_player: if two_players {
+                Some(player_state::PlayerState {
+                    left_ship: config.start_ships,
+                    rank: DifficultyRank::new(config.difficulty),
+                    ..player_state::PlayerState::default()
+                })
+            } else {
+                None
+            },
+            resume_state: None,
+            rank: DifficultyRank::new(config.difficulty),
+            config,
+            resume_attack: false,
         }
     }
 
@@ -84,6 +124,15 @@ impl GameInfo {
         self.frame_count = self.frame_count.wrapping_add(1);
         self.check_stage_state(&appearance_manager);
 
+        // Handled before the state update, same as the events in OO version.
+        if self.resume_attack {
+            attack_manager.pause(false);
+            self.resume_attack = false;
+        }
+        if self.game_state == GameState::CaptureCompleted {
+            self.next_player(appearance_manager, attack_manager, eneshot_spawner, star_manager, world, commands);
+        }
+
         match self.game_state {
             GameState::StartStage => {
                 if self.count == 0 {
@@ -100,12 +149,23 @@ impl GameInfo {
                         None
                     };
                     self.start_next_stage(self.stage, captured_fighter, formation, appearance_manager, attack_manager, eneshot_spawner);
+                    self.score_holder.record.stage = self.stage;
+                    if let Some(state) = self.resume_state.take() {
+                        appearance_manager.set_skip(state.destroyed);
+                        self.hit_count = state.hit_count;
+                    }
                     self.game_state = GameState::Playing;
                 }
             }
             GameState::Playing => {
                 if self.stage_state == StageState::CLEARED && self.all_destroyed(world) {
-                    self.game_state = GameState::StageClear;
+                    if appearance_manager.is_challenging() {
+                        self.challenging_result = ChallengingResult::new(self.hit_count);
+                        self.add_score(self.challenging_result.bonus(), sound_queue);
+                        self.game_state = GameState::ChallengingResult;
+                    } else {
+                        self.game_state = GameState::StageClear;
+                    }
                     self.count = 0;
                 }
             }
@@ -120,7 +180,7 @@ impl GameInfo {
                 if attack_manager.is_no_attacker() {
                     self.count += 1;
                     if self.count >= WAIT1 {
-                        self.next_player(appearance_manager, attack_manager, world, commands);
+                        self.next_player(appearance_manager, attack_manager, eneshot_spawner, star_manager, world, commands);
                     }
                 }
             }
@@ -137,14 +197,27 @@ impl GameInfo {
                     self.count = 0;
                 }
             }
-            GameState::Capturing | GameState::Recapturing => {}
+            GameState::SwitchPlayer => {
+                self.count += 1;
+                if self.count >= 120 {
+                    self.game_state = GameState::StartStage;
+                    self.count = 0;
+                }
+            }
+            GameState::Capturing | GameState::CaptureCompleted | GameState::Recapturing => {}
             GameState::Captured => {
                 self.count += 1;
             }
+            GameState::ChallengingResult => {
+                if !self.challenging_result.update() {
+                    self.game_state = GameState::StageClear;
+                }
+            }
             GameState::StageClear => {
                 self.count += 1;
                 if self.count >= 60 {
                     self.stage = self.stage.saturating_add(1);
+                    self.rank.on_stage_cleared();
                     self.game_state = GameState::StartStage;
                     self.count = 0;
                 }
@@ -152,7 +225,11 @@ impl GameInfo {
             GameState::GameOver => {
                 self.count += 1;
                 if self.count >= 35 * 60 / 10 {
-                    self.game_state = GameState::Finished;
+                    if self.other_player.is_some() {
+                        self.switch_player(false, appearance_manager, attack_manager, eneshot_spawner, star_manager, world, commands);
+                    } else {
+                        self.game_state = GameState::Finished;
+                    }
                 }
             }
             GameState::Finished => {}
@@ -187,15 +264,16 @@ impl GameInfo {
     }
 
     pub fn player_captured(&mut self) {
+        self.score_holder.record.captured_count += 1;
         self.capture_state = CaptureState::Captured;
         self.game_state = GameState::Captured;
         self.count = 0;
     }
 
     pub fn capture_completed(&mut self) {
-        // Reserve calling `next_player` in next frame.
-        self.game_state = GameState::WaitReady;
-        self.count = WAIT1 - 1;
+        // Call `next_player` in the game controller of this frame.
+        self.game_state = GameState::CaptureCompleted;
+        self.count = 0;
     }
 
     pub fn start_recapturing(&mut self) {
@@ -204,7 +282,10 @@ impl GameInfo {
     }
 
     pub fn end_recapturing(&mut self, dual: bool) {
-        //self.stage_manager.pause_attack(false);
+        if dual {
+            self.score_holder.record.rescued_count += 1;
+        }
+        self.resume_attack = true;
         self.capture_state = if dual { CaptureState::Dual } else { CaptureState::NoCapture };
         self.capture_enemy_fi = FormationIndex(0, 0);
         //params.star_manager.set_stop(false);
@@ -219,7 +300,7 @@ impl GameInfo {
     }
 
     pub fn escape_ended(&mut self) {
-        //self.stage_manager.pause_attack(false);
+        self.resume_attack = true;
         self.game_state = GameState::Playing;
     }
 
@@ -229,6 +310,7 @@ impl GameInfo {
     }
 
     pub fn crash_player(&mut self, died: bool, appearance_manager: &mut AppearanceManager, attack_manager: &mut AttackManager) {
+        self.score_holder.record.lost_count += 1;
         if died {
             if self.game_state != GameState::Recapturing {
                 appearance_manager.pause(true);
@@ -243,9 +325,21 @@ impl GameInfo {
         }
     }
 
-    pub fn next_player(&mut self, appearance_manager: &mut AppearanceManager, attack_manager: &mut AttackManager, world: &mut SubWorld, commands: &mut CommandBuffer) {
+    pub fn has_other_player(&self) -> bool {
+        self.other_player.is_some()
+    }
+
+    pub fn next_player(
+        &mut self, appearance_manager: &mut AppearanceManager, attack_manager: &mut AttackManager,
+        eneshot_spawner: &mut EneShotSpawner, star_manager: &mut StarManager,
+        world: &mut SubWorld, commands: &mut CommandBuffer,
+    ) {
         self.left_ship -= 1;
-        if self.left_ship == 0 {
+        self.rank.on_player_dead();
+        attack_manager.set_rank(self.rank);
+        if self.left_ship > 0 && self.other_player.is_some() {
+            self.switch_player(true, appearance_manager, attack_manager, eneshot_spawner, star_manager, world, commands);
+        } else if self.left_ship == 0 {
             appearance_manager.pause(true);
             attack_manager.pause(true);
             self.game_state = GameState::GameOver;
@@ -260,10 +354,56 @@ impl GameInfo {
         }
     }
 
+    // Swaps the state with the waiting player, and restarts the stage of the player.
+    fn switch_player(
+        &mut self, keep_current: bool, appearance_manager: &mut AppearanceManager, attack_manager: &mut AttackManager,
+        eneshot_spawner: &mut EneShotSpawner, star_manager: &mut StarManager,
+        world: &mut SubWorld, commands: &mut CommandBuffer,
+    ) {
+        let next = self.other_player.take().unwrap();
+        if keep_current {
+            let destroyed = appearance_manager.calc_destroyed(|fi| {
+                <&Enemy>::query().iter(world).any(|enemy| enemy.formation_index == *fi)
+            });
+            self.other_player = Some(player_state::PlayerState::new(
+                self.stage, self.left_ship, self.capture_state, self.capture_enemy_fi,
+                destroyed, self.hit_count, self.rank));
+        }
+        self.score_holder.switch_player();
+
+        self.stage = next.stage;
+        self.left_ship = next.left_ship;
+        self.capture_state = next.capture_state;
+        self.capture_enemy_fi = next.capture_enemy_fi;
+        self.rank = next.rank;
+        attack_manager.set_rank(self.rank);
+        self.resume_state = Some(next);
+
+        clear_field(world, commands);
+        appearance_manager.done = true;
+        attack_manager.restart(0);
+        eneshot_spawner.restart();
+        self.alive_enemy_count = 0;
+        self.stage_state = StageState::APPEARANCE;
+
+        for (player, pos, entity) in <(&mut Player, &mut Posture, Entity)>::query().iter_mut(world) {
+            restart_player(player, *entity, pos, commands);
+            enable_player_shot(player, true);
+        }
+        star_manager.set_stop(false);
+        self.game_state = GameState::SwitchPlayer;
+        self.count = 0;
+    }
+
     pub fn decrement_alive_enemy(&mut self) {
         self.alive_enemy_count -= 1;
     }
 
+    pub fn enemy_destroyed(&mut self) {
+        self.hit_count += 1;
+        self.decrement_alive_enemy();
+    }
+
     pub fn is_rush(&self) -> bool {
         self.game_state == GameState::Playing && self.stage_state == StageState::RUSH
     }
@@ -271,12 +411,7 @@ impl GameInfo {
     pub fn add_score(&mut self, add: u32, sound_queue: &mut SoundQueue) {
         let before = self.score_holder.score;
         self.score_holder.add_score(add);
-        let ext = if before < EXTEND_FIRST_SCORE {
-            EXTEND_FIRST_SCORE
-        } else {
-            (before + EXTEND_AFTER_SCORE - 1) / EXTEND_AFTER_SCORE * EXTEND_AFTER_SCORE
-        };
-        if before + add >= ext {
+        if self.config.extend.is_extended(before, before.saturating_add(add)) {
             self.extend_ship(sound_queue);
         }
     }
@@ -292,10 +427,13 @@ impl GameInfo {
         eneshot_spawner: &mut EneShotSpawner,
     ) {
         formation.restart();
+        appearance_manager.set_rank(self.rank);
         appearance_manager.restart(stage, captured_fighter);
         attack_manager.restart(stage);
+        attack_manager.set_rank(self.rank);
         eneshot_spawner.restart();
         self.stage_state = StageState::APPEARANCE;
+        self.hit_count = 0;
     }
 
     fn check_stage_state(&mut self, appearance_manager: &AppearanceManager) {
@@ -306,9 +444,10 @@ impl GameInfo {
             self.stage_state = StageState::NORMAL;
         }
 
+        let rush_threshold = self.rank.rush_threshold();
         let new_state = match self.alive_enemy_count {
             n if n == 0               => StageState::CLEARED,
-            n if n <= RUSH_THRESHOLD  => StageState::RUSH,
+            n if n <= rush_threshold  => StageState::RUSH,
             _                         => self.stage_state,
         };
         if new_state != self.stage_state {
@@ -317,15 +456,48 @@ impl GameInfo {
     }
 }
 
+// Removes all entities except the player.
+fn clear_field(world: &SubWorld, commands: &mut CommandBuffer) {
+    for (_enemy, tractor_beam, entity) in <(&Enemy, Option<&TractorBeam>, Entity)>::query().iter(world) {
+        if let Some(tractor_beam) = tractor_beam {
+            for beam in tractor_beam.beam_sprites.iter().flatten() {
+                commands.remove(*beam);
+            }
+        }
+        commands.remove(*entity);
+    }
+    for (shot, entity) in <(&MyShot, Entity)>::query().iter(world) {
+        delete_myshot(shot, *entity, commands);
+    }
+    for (_shot, entity) in <(&EneShot, Entity)>::query().iter(world) {
+        commands.remove(*entity);
+    }
+    for (_fighter, entity) in <(&RecapturedFighter, Entity)>::query().iter(world) {
+        commands.remove(*entity);
+    }
+    for (_anime, entity) in <(&SequentialSpriteAnime, Entity)>::query().iter(world) {
+        commands.remove(*entity);
+    }
+}
+
 //
 
-#[derive(Default)]
+#[derive(Serialize, Deserialize)]
 pub struct EneShotSpawner {
     queue: Vec<Vec2I>,
     shot_paused_count: u32,
+    rng: Xoshiro128Plus,
 }
 
 impl EneShotSpawner {
+    pub fn new(rng: Xoshiro128Plus) -> Self {
+        Self {
+            queue: Vec::new(),
+            shot_paused_count: 0,
+            rng,
+        }
+    }
+
     pub fn push(&mut self, pos: &Vec2I) {
         self.queue.push(pos.clone());
     }
@@ -333,7 +505,8 @@ impl EneShotSpawner {
     pub fn update(&mut self, game_info: &GameInfo, world: &SubWorld, commands: &mut CommandBuffer) {
         if self.shot_paused_count > 0 {
             self.shot_paused_count -= 1;
-        } else {
+        }
+        if self.shot_paused_count == 0 {
             self.process_queue(game_info, world, commands);
         }
         self.queue.clear();
@@ -350,18 +523,19 @@ impl EneShotSpawner {
 
     fn process_queue(&mut self, game_info: &GameInfo, world: &SubWorld, commands: &mut CommandBuffer) {
         let shot_count = <&EneShot>::query().iter(world).count();
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
         let target_pos = enum_player_target_pos(world);
         let count = target_pos.iter().count();
-        for (pos, _i) in self.queue.iter().zip(shot_count..MAX_ENE_SHOT_COUNT) {
+        let max_count = game_info.rank.max_ene_shot_count();
+        let speed = game_info.rank.ene_shot_speed(calc_ene_shot_speed(game_info.stage));
+        for (pos, _i) in self.queue.iter().zip(shot_count..max_count) {
             let target: &Vec2I = target_pos.iter()
-                .nth(rng.gen_range(0, count)).unwrap();
+                .nth(self.rng.gen_range(0, count)).unwrap();
 
             let d = target - &pos;
             let angle = atan2_lut(d.y, -d.x);  // 0=down
             let limit = ANGLE * ONE * 30 / 360;
             let angle = clamp(angle, -limit, limit);
-            let vel = calc_velocity(angle + ANGLE * ONE / 2, calc_ene_shot_speed(game_info.stage));
+            let vel = calc_velocity(angle + ANGLE * ONE / 2, speed);
             commands.push((
                 EneShot(vel),
                 Posture(*pos, 0),
@@ -380,25 +554,50 @@ fn calc_ene_shot_speed(stage: u16) -> i32 {
 
 //
 
+enum SoundCommand {
+    Play(u32, &'static str),
+    PlayLoop(u32, &'static str),
+    Stop(u32, u32),  // (channel, fade ms)
+}
+
 pub struct SoundQueue {
-    queue: Vec<(u32, &'static str)>,
+    queue: Vec<SoundCommand>,
+    looping_se: LoopingSe,
 }
 
 impl SoundQueue {
     pub fn new() -> Self {
         Self {
             queue: Vec::new(),
+            looping_se: LoopingSe::default(),
         }
     }
 
     pub fn flush<S: SystemTrait>(&mut self, system: &mut S) {
-        for (channel, filename) in self.queue.iter() {
-            system.play_se(*channel, filename);
+        for command in self.queue.iter() {
+            match *command {
+                SoundCommand::Play(channel, filename) => system.play_se(channel, filename),
+                SoundCommand::PlayLoop(channel, filename) => self.looping_se.play(channel, filename, system),
+                SoundCommand::Stop(channel, fade_ms) => self.looping_se.stop(channel, fade_ms, system),
+            }
         }
         self.queue.clear();
     }
 
+    // Restarts looping sounds stopped by the pause.
+    pub fn resume<S: SystemTrait>(&self, system: &mut S) {
+        self.looping_se.resume(system);
+    }
+
     pub fn push_play_se(&mut self, channel: u32, filename: &'static str) {
-        self.queue.push((channel, filename));
+        self.queue.push(SoundCommand::Play(channel, filename));
+    }
+
+    pub fn push_play_loop(&mut self, channel: u32, filename: &'static str) {
+        self.queue.push(SoundCommand::PlayLoop(channel, filename));
+    }
+
+    pub fn push_stop_se(&mut self, channel: u32, fade_ms: u32) {
+        self.queue.push(SoundCommand::Stop(channel, fade_ms));
     }
 }
@@ -0,0 +1,338 @@
+// Save state of the world and the resources.
+//
+// Entities are saved as a list of components, and references to other entities are saved as indices in the list.
+
+use legion::world::{Entry, EntryRef};
+use legion::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use galangua_common::app::game::appearance_manager::AppearanceManager;
+use galangua_common::app::game::attack_manager::AttackManager;
+use galangua_common::app::game::formation::Formation;
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::app::game::stage_indicator::StageIndicator;
+use galangua_common::app::game::star_manager::StarManager;
+use galangua_common::app::util::snapshot::{from_json, to_json};
+use galangua_common::framework::types::Vec2I;
+
+use super::components::*;
+use super::resources::*;
+
+// Entity to index while saving.
+struct SaveContext {
+    indices: HashMap<Entity, usize>,
+}
+
+impl SaveContext {
+    // `None` for a removed entity.
+    fn index(&self, entity: Entity) -> EntityIndex {
+        self.indices.get(&entity).copied()
+    }
+
+    fn index_option(&self, entity: Option<Entity>) -> Option<EntityIndex> {
+        entity.map(|entity| self.index(entity))
+    }
+}
+
+// Index to entity while loading.
+struct LoadContext {
+    entities: Vec<Entity>,
+    // Removed entity, for references which are already dangling when saved.
+    dead: Entity,
+}
+
+impl LoadContext {
+    fn entity(&self, index: EntityIndex) -> Result<Entity, String> {
+        match index {
+            Some(index) => self.entities.get(index).copied()
+                .ok_or_else(|| format!("Illegal entity index: {}", index)),
+            None => Ok(self.dead),
+        }
+    }
+
+    fn entity_option(&self, index: Option<EntityIndex>) -> Result<Option<Entity>, String> {
+        index.map(|index| self.entity(index)).transpose()
+    }
+}
+
+type EntityIndex = Option<usize>;
+
+// Component which refers to other entities, saved in the form which has indices instead.
+trait RefComponent: Sized + Send + Sync + 'static {
+    type Saved: Serialize + DeserializeOwned;
+    fn save(&self, context: &SaveContext) -> Self::Saved;
+    fn load(saved: Self::Saved, context: &LoadContext) -> Result<Self, String>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedPlayer {
+    state: PlayerState,
+    count: u32,
+    shot_enable: bool,
+    dual: Option<EntityIndex>,
+}
+
+impl RefComponent for Player {
+    type Saved = SavedPlayer;
+
+    fn save(&self, context: &SaveContext) -> Self::Saved {
+        SavedPlayer { state: self.state, count: self.count, shot_enable: self.shot_enable, dual: context.index_option(self.dual) }
+    }
+
+    fn load(saved: Self::Saved, context: &LoadContext) -> Result<Self, String> {
+        Ok(Player { state: saved.state, count: saved.count, shot_enable: saved.shot_enable, dual: context.entity_option(saved.dual)? })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedRecapturedFighter {
+    state: RecapturedFighterState,
+    count: u32,
+    player_entity: EntityIndex,
+}
+
+impl RefComponent for RecapturedFighter {
+    type Saved = SavedRecapturedFighter;
+
+    fn save(&self, context: &SaveContext) -> Self::Saved {
+        SavedRecapturedFighter { state: self.state, count: self.count, player_entity: context.index(self.player_entity) }
+    }
+
+    fn load(saved: Self::Saved, context: &LoadContext) -> Result<Self, String> {
+        Ok(RecapturedFighter { state: saved.state, count: saved.count, player_entity: context.entity(saved.player_entity)? })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedMyShot {
+    player_entity: EntityIndex,
+    dual: Option<EntityIndex>,
+}
+
+impl RefComponent for MyShot {
+    type Saved = SavedMyShot;
+
+    fn save(&self, context: &SaveContext) -> Self::Saved {
+        SavedMyShot { player_entity: context.index(self.player_entity), dual: context.index_option(self.dual) }
+    }
+
+    fn load(saved: Self::Saved, context: &LoadContext) -> Result<Self, String> {
+        Ok(MyShot { player_entity: context.entity(saved.player_entity)?, dual: context.entity_option(saved.dual)? })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTractorBeam {
+    pos: Vec2I,
+    state: TractorBeamState,
+    count: u32,
+    color_count: u32,
+    size_count: i32,
+    beam_sprites: [Option<EntityIndex>; TRACTOR_BEAM_SPRITE_COUNT],
+    capturing_player: Option<EntityIndex>,
+}
+
+impl RefComponent for TractorBeam {
+    type Saved = SavedTractorBeam;
+
+    fn save(&self, context: &SaveContext) -> Self::Saved {
+        SavedTractorBeam {
+            pos: self.pos.clone(),
+            state: self.state,
+            count: self.count,
+            color_count: self.color_count,
+            size_count: self.size_count,
+            beam_sprites: self.beam_sprites.map(|entity| context.index_option(entity)),
+            capturing_player: context.index_option(self.capturing_player),
+        }
+    }
+
+    fn load(saved: Self::Saved, context: &LoadContext) -> Result<Self, String> {
+        let mut beam_sprites = [None; TRACTOR_BEAM_SPRITE_COUNT];
+        for (sprite, index) in beam_sprites.iter_mut().zip(saved.beam_sprites) {
+            *sprite = context.entity_option(index)?;
+        }
+        Ok(TractorBeam {
+            pos: saved.pos,
+            state: saved.state,
+            count: saved.count,
+            color_count: saved.color_count,
+            size_count: saved.size_count,
+            beam_sprites,
+            capturing_player: context.entity_option(saved.capturing_player)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTroops {
+    members: [Option<SavedTroop>; MAX_TROOPS],
+    copy_angle_to_troops: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTroop {
+    entity: EntityIndex,
+    offset: Vec2I,
+    is_guard: bool,
+}
+
+impl RefComponent for Troops {
+    type Saved = SavedTroops;
+
+    fn save(&self, context: &SaveContext) -> Self::Saved {
+        let members = self.members.each_ref().map(|troop| troop.as_ref().map(|troop| {
+            SavedTroop { entity: context.index(troop.entity), offset: troop.offset.clone(), is_guard: troop.is_guard }
+        }));
+        SavedTroops { members, copy_angle_to_troops: self.copy_angle_to_troops }
+    }
+
+    fn load(saved: Self::Saved, context: &LoadContext) -> Result<Self, String> {
+        let mut members: [Option<Troop>; MAX_TROOPS] = Default::default();
+        for (member, troop) in members.iter_mut().zip(saved.members) {
+            if let Some(troop) = troop {
+                *member = Some(Troop { entity: context.entity(troop.entity)?, offset: troop.offset, is_guard: troop.is_guard });
+            }
+        }
+        Ok(Troops { members, copy_angle_to_troops: saved.copy_angle_to_troops })
+    }
+}
+
+pub fn save_world(world: &World) -> Result<Value, String> {
+    let entities: Vec<Entity> = <Entity>::query().iter(world).copied().collect();
+    let context = SaveContext {
+        indices: entities.iter().enumerate()
+            .map(|(i, entity)| (*entity, i))
+            .collect(),
+    };
+
+    let values = entities.iter()
+        .map(|entity| save_entity(&world.entry_ref(*entity).unwrap(), &context))
+        .collect::<Result<Vec<Value>, String>>()?;
+    Ok(Value::Array(values))
+}
+
+pub fn load_world(value: &Value) -> Result<World, String> {
+    let values = value.as_array().ok_or_else(|| String::from("entities: Not an array"))?;
+    let mut world = World::default();
+    let dead = world.push(());
+    world.remove(dead);
+    let entities: Vec<Entity> = values.iter().map(|_| world.push(())).collect();
+    let context = LoadContext { entities: entities.clone(), dead };
+
+    entities.iter().zip(values.iter())
+        .enumerate()
+        .try_for_each(|(i, (entity, value))| {
+            load_entity(value, &mut world.entry(*entity).unwrap(), &context)
+                .map_err(|e| format!("entities[{}].{}", i, e))
+        })?;
+    Ok(world)
+}
+
+fn save_entity(entry: &EntryRef, context: &SaveContext) -> Result<Value, String> {
+    let mut map = Map::new();
+    save_ref_component::<Player>(entry, "player", &mut map, context)?;
+    save_ref_component::<RecapturedFighter>(entry, "recaptured_fighter", &mut map, context)?;
+    save_ref_component::<MyShot>(entry, "myshot", &mut map, context)?;
+    save_component::<Enemy>(entry, "enemy", &mut map)?;
+    save_component::<Zako>(entry, "zako", &mut map)?;
+    save_component::<Owl>(entry, "owl", &mut map)?;
+    save_ref_component::<Troops>(entry, "troops", &mut map, context)?;
+    save_ref_component::<TractorBeam>(entry, "tractor_beam", &mut map, context)?;
+    save_component::<EneShot>(entry, "eneshot", &mut map)?;
+    save_component::<SequentialSpriteAnime>(entry, "sequential_sprite_anime", &mut map)?;
+    save_component::<SpriteColor>(entry, "sprite_color", &mut map)?;
+    save_component::<SpriteDrawable>(entry, "sprite_drawable", &mut map)?;
+    save_component::<Speed>(entry, "speed", &mut map)?;
+    save_component::<CollRect>(entry, "coll_rect", &mut map)?;
+    save_component::<Posture>(entry, "posture", &mut map)?;
+    Ok(Value::Object(map))
+}
+
+// Every entity except a stray one has `Posture`, so adding it at the last
+// creates archetypes in the same order as the saved world, and keeps the order of query results.
+fn load_entity(value: &Value, entry: &mut Entry, context: &LoadContext) -> Result<(), String> {
+    load_ref_component::<Player>(value, "player", entry, context)?;
+    load_ref_component::<RecapturedFighter>(value, "recaptured_fighter", entry, context)?;
+    load_ref_component::<MyShot>(value, "myshot", entry, context)?;
+    load_component::<Enemy>(value, "enemy", entry)?;
+    load_component::<Zako>(value, "zako", entry)?;
+    load_component::<Owl>(value, "owl", entry)?;
+    load_ref_component::<Troops>(value, "troops", entry, context)?;
+    load_ref_component::<TractorBeam>(value, "tractor_beam", entry, context)?;
+    load_component::<EneShot>(value, "eneshot", entry)?;
+    load_component::<SequentialSpriteAnime>(value, "sequential_sprite_anime", entry)?;
+    load_component::<SpriteColor>(value, "sprite_color", entry)?;
+    load_component::<SpriteDrawable>(value, "sprite_drawable", entry)?;
+    load_component::<Speed>(value, "speed", entry)?;
+    load_component::<CollRect>(value, "coll_rect", entry)?;
+    load_component::<Posture>(value, "posture", entry)?;
+    Ok(())
+}
+
+fn save_component<T: 'static + Send + Sync + Serialize>(entry: &EntryRef, key: &str, map: &mut Map<String, Value>) -> Result<(), String> {
+    if let Ok(component) = entry.get_component::<T>() {
+        map.insert(String::from(key), to_json(component).map_err(|e| format!("{}: {}", key, e))?);
+    }
+    Ok(())
+}
+
+fn load_component<T: 'static + Send + Sync + DeserializeOwned>(value: &Value, key: &str, entry: &mut Entry) -> Result<(), String> {
+    if value.get(key).is_some() {
+        entry.add_component(from_json::<T>(value, key)?);
+    }
+    Ok(())
+}
+
+fn save_ref_component<T: RefComponent>(entry: &EntryRef, key: &str, map: &mut Map<String, Value>, context: &SaveContext) -> Result<(), String> {
+    if let Ok(component) = entry.get_component::<T>() {
+        map.insert(String::from(key), to_json(&component.save(context)).map_err(|e| format!("{}: {}", key, e))?);
+    }
+    Ok(())
+}
+
+fn load_ref_component<T: RefComponent>(value: &Value, key: &str, entry: &mut Entry, context: &LoadContext) -> Result<(), String> {
+    if value.get(key).is_some() {
+        entry.add_component(T::load(from_json::<T::Saved>(value, key)?, context).map_err(|e| format!("{}: {}", key, e))?);
+    }
+    Ok(())
+}
+
+pub fn save_resources(resources: &Resources) -> Result<Value, String> {
+    let mut map = Map::new();
+    save_resource::<GameInfo>(resources, "game_info", &mut map)?;
+    save_resource::<StageIndicator>(resources, "stage_indicator", &mut map)?;
+    save_resource::<Formation>(resources, "formation", &mut map)?;
+    save_resource::<AppearanceManager>(resources, "appearance_manager", &mut map)?;
+    save_resource::<AttackManager>(resources, "attack_manager", &mut map)?;
+    save_resource::<EneShotSpawner>(resources, "eneshot_spawner", &mut map)?;
+    save_resource::<StarManager>(resources, "star_manager", &mut map)?;
+    Ok(Value::Object(map))
+}
+
+// `SoundQueue` and `Pad` are not saved.
+pub fn load_resources(value: &Value, stage_definition: &Arc<StageDefinition>) -> Result<Resources, String> {
+    let mut appearance_manager: AppearanceManager = from_json(value, "appearance_manager")?;
+    appearance_manager.set_stage_definition(stage_definition.clone());
+
+    let mut resources = Resources::default();
+    resources.insert(from_json::<StarManager>(value, "star_manager")?);
+    resources.insert(from_json::<StageIndicator>(value, "stage_indicator")?);
+    resources.insert(from_json::<Formation>(value, "formation")?);
+    resources.insert(appearance_manager);
+    resources.insert(from_json::<AttackManager>(value, "attack_manager")?);
+    resources.insert(from_json::<EneShotSpawner>(value, "eneshot_spawner")?);
+    resources.insert(from_json::<GameInfo>(value, "game_info")?);
+    resources.insert(SoundQueue::new());
+    Ok(resources)
+}
+
+fn save_resource<T: 'static + Serialize>(resources: &Resources, key: &str, map: &mut Map<String, Value>) -> Result<(), String> {
+    let resource = resources.get::<T>().ok_or_else(|| format!("{}: Not found", key))?;
+    map.insert(String::from(key), to_json(&*resource).map_err(|e| format!("{}: {}", key, e))?);
+    Ok(())
+}
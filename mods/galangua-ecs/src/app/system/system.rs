@@ -7,7 +7,9 @@ use galangua_common::app::game::appearance_manager::Accessor as AppearanceManage
 use galangua_common::app::game::appearance_manager::AppearanceManager;
 use galangua_common::app::game::attack_manager::Accessor as AttackManagerAccessor;
 use galangua_common::app::game::attack_manager::AttackManager;
+use galangua_common::app::game::challenging_stage::is_challenging_stage;
 use galangua_common::app::game::formation::Formation;
+use galangua_common::app::game::observation::{EnemyObservation, Observation};
 use galangua_common::app::game::stage_indicator::StageIndicator;
 use galangua_common::app::game::star_manager::StarManager;
 use galangua_common::app::game::{CaptureState, EnemyType, FormationIndex};
@@ -28,7 +30,12 @@ use super::system_player::*;
 #[system]
 #[write_component(Player)]
 #[write_component(Posture)]
+#[read_component(Enemy)]
 #[read_component(EneShot)]
+#[read_component(MyShot)]
+#[read_component(TractorBeam)]
+#[read_component(RecapturedFighter)]
+#[read_component(SequentialSpriteAnime)]
 pub fn update_game_controller(
     world: &mut SubWorld,
     #[resource] game_info: &mut GameInfo,
@@ -52,18 +59,18 @@ pub fn move_player(
     player: &mut Player, entity: &Entity,
     #[resource] pad: &Pad,
     #[resource] game_info: &mut GameInfo,
-    #[resource] attack_manager: &mut AttackManager,
     world: &mut SubWorld, commands: &mut CommandBuffer,
 ) {
-    do_move_player(player, pad, *entity, game_info, attack_manager, world, commands);
+    do_move_player(player, pad, *entity, game_info, world, commands);
 }
 
 #[system(for_each)]
 #[read_component(MyShot)]
-pub fn fire_myshot(player: &Player, posture: &Posture, entity: &Entity, world: &mut SubWorld, #[resource] pad: &Pad, #[resource] sound_queue: &mut SoundQueue, commands: &mut CommandBuffer) {
+pub fn fire_myshot(player: &Player, posture: &Posture, entity: &Entity, world: &mut SubWorld, #[resource] pad: &Pad, #[resource] sound_queue: &mut SoundQueue, #[resource] game_info: &mut GameInfo, commands: &mut CommandBuffer) {
     let shot_count = <&MyShot>::query().iter(world).count();
     if pad.is_trigger(PadBit::A) && shot_count < 2 {
         if do_fire_myshot(player, posture, *entity, commands) {
+            game_info.score_holder.record.shot_count += 1;
             sound_queue.push_play_se(CH_SHOT, SE_MYSHOT);
         }
     }
@@ -117,7 +124,8 @@ pub fn run_appearance_manager(world: &mut SubWorld, #[resource] appearance_manag
 
     if appearance_manager.done {
         formation.done_appearance();
-        attack_manager.set_enable(true);
+        // Enemies in challenging stage just fly through.
+        attack_manager.set_enable(!appearance_manager.is_challenging());
     }
 }
 
@@ -207,11 +215,18 @@ impl<'a, 'b> AttackManagerAccessor for SysAttackManagerAccessor<'a, 'b> {
     }
 }
 
-#[system(for_each)]
-#[read_component(Player)]
+// Moves enemies in formation order, same as the enemy slots in OO version,
+// because the order affects random numbers, shots and troops.
+#[system]
+#[write_component(Player)]
+#[write_component(TractorBeam)]
+#[write_component(Enemy)]
+#[write_component(Zako)]
+#[write_component(Owl)]
+#[write_component(Speed)]
 #[write_component(Posture)]
-pub fn move_zako(
-    enemy: &mut Enemy, zako: &mut Zako, speed: &mut Speed, entity: &Entity,
+#[write_component(Troops)]
+pub fn move_enemy(
     world: &mut SubWorld,
     #[resource] formation: &Formation,
     #[resource] eneshot_spawner: &mut EneShotSpawner,
@@ -219,7 +234,23 @@ pub fn move_zako(
     #[resource] game_info: &mut GameInfo,
     commands: &mut CommandBuffer,
 ) {
-    do_move_zako(zako, *entity, enemy, speed, formation, eneshot_spawner, sound_queue, game_info, world, commands);
+    let mut entities: Vec<(FormationIndex, Entity)> = <(&Enemy, Entity)>::query().iter(world)
+        .map(|(enemy, entity)| (enemy.formation_index, *entity))
+        .collect();
+    entities.sort_by_key(|(fi, _)| fi.order());
+
+    for (_, entity) in entities {
+        let (mut subworld1, mut subworld2) = world.split::<(&mut Owl, &mut Speed)>();
+        if let Ok((owl, speed)) = <(&mut Owl, &mut Speed)>::query().get_mut(&mut subworld1, entity) {
+            do_move_owl(owl, entity, speed, formation, eneshot_spawner, sound_queue, game_info, &mut subworld2, commands);
+            continue;
+        }
+
+        let (mut subworld1, mut subworld2) = world.split::<(&mut Enemy, &mut Zako, &mut Speed)>();
+        if let Ok((enemy, zako, speed)) = <(&mut Enemy, &mut Zako, &mut Speed)>::query().get_mut(&mut subworld1, entity) {
+            do_move_zako(zako, entity, enemy, speed, formation, eneshot_spawner, sound_queue, game_info, &mut subworld2, commands);
+        }
+    }
 }
 
 #[system(for_each)]
@@ -230,24 +261,6 @@ pub fn animate_zako(
     do_animate_zako(enemy.enemy_type, sprite, game_info.frame_count);
 }
 
-#[system(for_each)]
-#[read_component(Player)]
-#[write_component(TractorBeam)]
-#[write_component(Enemy)]
-#[write_component(Zako)]
-#[write_component(Posture)]
-#[write_component(Troops)]
-pub fn move_owl(
-    owl: &mut Owl, speed: &mut Speed, entity: &Entity,
-    #[resource] formation: &Formation,
-    #[resource] eneshot_spawner: &mut EneShotSpawner,
-    #[resource] sound_queue: &mut SoundQueue,
-    #[resource] game_info: &mut GameInfo,
-    world: &mut SubWorld, commands: &mut CommandBuffer,
-) {
-    do_move_owl(owl, *entity, speed, formation, eneshot_spawner, sound_queue, game_info, world, commands);
-}
-
 #[system(for_each)]
 pub fn animate_owl(
     owl: &Owl, sprite: &mut SpriteDrawable,
@@ -267,13 +280,13 @@ pub fn move_troops(troops: &mut Troops, owl: &mut Owl, entity: &Entity, world: &
 #[write_component(Posture)]
 #[write_component(SpriteColor)]
 pub fn move_tractor_beam(
-    tractor_beam: &mut TractorBeam, owl: &mut Owl, enemy: &Enemy, entity: &Entity,
+    tractor_beam: &mut TractorBeam,
     #[resource] game_info: &mut GameInfo,
     #[resource] star_manager: &mut StarManager,
     #[resource] attack_manager: &mut AttackManager,
     world: &mut SubWorld, commands: &mut CommandBuffer,
 ) {
-    do_move_tractor_beam(tractor_beam, *entity, owl, enemy, game_info, star_manager, attack_manager, world, commands);
+    do_move_tractor_beam(tractor_beam, game_info, star_manager, attack_manager, world, commands);
 }
 
 #[system]
@@ -309,6 +322,11 @@ pub fn coll_check_myshot_enemy(
     #[resource] game_info: &mut GameInfo,
     commands: &mut CommandBuffer,
 ) {
+    // Shot fired at the moment of the capture must not break the capture sequence.
+    if <&Player>::query().iter(world).any(|player| player.state == PlayerState::Captured) {
+        return;
+    }
+
     let mut colls: Vec<(Entity, Entity)> = Vec::new();
     for (shot, shot_pos, shot_coll_rect, shot_entity) in <(&MyShot, &Posture, &CollRect, Entity)>::query().iter(world) {
         let shot_collboxes = [
@@ -317,25 +335,35 @@ pub fn coll_check_myshot_enemy(
         ];
         let mut hit = false;
         for shot_collbox in shot_collboxes.iter().flat_map(|x| x) {
-            for (_enemy, enemy_pos, enemy_coll_rect, enemy_entity) in <(&Enemy, &Posture, &CollRect, Entity)>::query().iter(world) {
-                let enemy_collbox = CollBox { top_left: &round_vec(&enemy_pos.0) + &enemy_coll_rect.offset, size: enemy_coll_rect.size };
-                if shot_collbox.check_collision(&enemy_collbox) {
-                    colls.push((*enemy_entity, shot.player_entity));
-                    hit = true;
-                    break;
-                }
+            let target = <(&Enemy, &Posture, &CollRect, Entity)>::query().iter(world)
+                .filter(|(_enemy, enemy_pos, enemy_coll_rect, _)| shot_collbox.check_collision(&pos_to_coll_box(&enemy_pos.0, enemy_coll_rect)))
+                .min_by_key(|(enemy, _, _, _)| enemy.formation_index.order());
+            if let Some((_, _, _, enemy_entity)) = target {
+                colls.push((*enemy_entity, shot.player_entity));
+                hit = true;
             }
             if hit {
                 delete_myshot(shot, *shot_entity, commands);
             }
         }
+        if hit {
+            game_info.score_holder.record.hit_count += 1;
+        }
     }
 
+    // Removal is deferred, so skip the enemy destroyed by another shot in the same frame.
+    let mut destroyed: Vec<Entity> = Vec::new();
     for (enemy_entity, player_entity) in colls {
+        if destroyed.contains(&enemy_entity) {
+            continue;
+        }
         let enemy_type = <&Enemy>::query().get(world, enemy_entity).unwrap().enemy_type;
         set_enemy_damage(
             enemy_type, enemy_entity, 1, player_entity, star_manager, attack_manager, eneshot_spawner,
             sound_queue, game_info, world, commands);
+        if <&Owl>::query().get(world, enemy_entity).map_or(true, |owl| owl.life == 0) {
+            destroyed.push(enemy_entity);
+        }
     }
 }
 
@@ -503,11 +531,17 @@ pub fn draw_system<R: RendererTrait>(world: &World, resources: &Resources, rende
 
     match game_info.game_state {
         GameState::StartStage => {
+            draw_player_no(&game_info, renderer);
             renderer.set_texture_color_mod("font", 0, 255, 255);
-            renderer.draw_str("font", 10 * 8, 18 * 8, &format!("STAGE {}", game_info.stage + 1));
+            if is_challenging_stage(game_info.stage) {
+                renderer.draw_str("font", (28 - 17) / 2 * 8, 18 * 8, "CHALLENGING STAGE");
+            } else {
+                renderer.draw_str("font", 10 * 8, 18 * 8, &format!("STAGE {}", game_info.stage + 1));
+            }
         }
         GameState::WaitReady | GameState::WaitReady2 => {
-            if game_info.left_ship > 1 || game_info.game_state == GameState::WaitReady2 {
+            if (game_info.left_ship > 1 && !game_info.has_other_player()) || game_info.game_state == GameState::WaitReady2 {
+                draw_player_no(&game_info, renderer);
                 renderer.set_texture_color_mod("font", 0, 255, 255);
                 renderer.draw_str("font", (28 - 6) / 2 * 8, 18 * 8, "READY");
             }
@@ -518,7 +552,16 @@ pub fn draw_system<R: RendererTrait>(world: &World, resources: &Resources, rende
                 renderer.draw_str("font", (28 - 16) / 2 * 8, 19 * 8, "FIGHTER CAPTURED");
             }
         }
+        GameState::ChallengingResult => {
+            game_info.challenging_result.draw(renderer);
+        }
+        GameState::SwitchPlayer => {
+            draw_player_no(&game_info, renderer);
+            renderer.set_texture_color_mod("font", 0, 255, 255);
+            renderer.draw_str("font", (28 - 6) / 2 * 8, 18 * 8, "READY");
+        }
         GameState::GameOver => {
+            draw_player_no(&game_info, renderer);
             renderer.set_texture_color_mod("font", 0, 255, 255);
             renderer.draw_str("font", (28 - 8) / 2 * 8, 18 * 8, "GAME OVER");
         }
@@ -526,6 +569,57 @@ pub fn draw_system<R: RendererTrait>(world: &World, resources: &Resources, rende
     }
 }
 
+fn draw_player_no<R: RendererTrait>(game_info: &GameInfo, renderer: &mut R) {
+    if game_info.score_holder.is_two_players() {
+        renderer.set_texture_color_mod("font", 0, 255, 255);
+        renderer.draw_str("font", (28 - 8) / 2 * 8, 15 * 8, &format!("PLAYER {}", game_info.score_holder.current_player() + 1));
+    }
+}
+
+pub fn observe(world: &World, resources: &Resources) -> Observation {
+    let game_info = resources.get::<GameInfo>().unwrap();
+    let mut observation = Observation {
+        score: game_info.score_holder.score,
+        left_ship: game_info.left_ship,
+        stage: game_info.stage,
+        capture_state: game_info.capture_state,
+        ..Default::default()
+    };
+    for (player, posture) in <(&Player, &Posture)>::query().iter(world).filter(|(player, _)| player.state != PlayerState::Dead) {
+        observation.players.push(posture.0);
+        if let Some(dual) = player.dual {
+            if let Ok(dual_posture) = <&Posture>::query().get(world, dual) {
+                observation.players.push(dual_posture.0);
+            }
+        }
+    }
+    for (shot, posture) in <(&MyShot, &Posture)>::query().iter(world) {
+        observation.my_shots.push(posture.0);
+        if let Some(dual) = shot.dual {
+            if let Ok(dual_posture) = <&Posture>::query().get(world, dual) {
+                observation.my_shots.push(dual_posture.0);
+            }
+        }
+    }
+    for (enemy, posture) in <(&Enemy, &Posture)>::query().iter(world) {
+        observation.enemies.push(EnemyObservation {
+            enemy_type: enemy.enemy_type,
+            formation_index: enemy.formation_index,
+            pos: posture.0,
+            angle: posture.1,
+            is_formation: enemy.is_formation,
+            formation_pos: None,
+        });
+    }
+    observation.set_formation_pos(&resources.get::<Formation>().unwrap());
+    for (_, posture) in <(&EneShot, &Posture)>::query().iter(world) {
+        observation.ene_shots.push(posture.0);
+    }
+    observation.tractor_beam = <&TractorBeam>::query().iter(world).next().map(|tractor_beam| tractor_beam.pos);
+    observation.normalize();
+    observation
+}
+
 //
 
 fn pos_to_coll_box(pos: &Vec2I, coll_rect: &CollRect) -> CollBox {
@@ -36,21 +36,10 @@ pub fn update_seqanime(anime: &mut SequentialSpriteAnime, drawable: Option<&mut
     }
 }
 
-const FLASH_ENEMY_ANIME_TABLE: [&[&str]; 4] = [
-    &FLASH_BEE_ANIME_TABLE,
-    &FLASH_BUTTERFLY_ANIME_TABLE,
-    &FLASH_OWL_ANIME_TABLE,
-    &FLASH_CAPTURED_FIGHTER_ANIME_TABLE,
-];
-const FLASH_BEE_ANIME_TABLE: [&str; 1] = ["gopher_flash"];
-const FLASH_BUTTERFLY_ANIME_TABLE: [&str; 1] = ["dman_flash"];
-const FLASH_OWL_ANIME_TABLE: [&str; 1] = ["cpp_flash"];
-const FLASH_CAPTURED_FIGHTER_ANIME_TABLE: [&str; 1] = ["rustacean_flash"];
-
 pub fn create_flash_enemy_effect(
     pos: &Vec2I, angle: i32, enemy_type: EnemyType, commands: &mut CommandBuffer,
 ) {
-    let anime_table = FLASH_ENEMY_ANIME_TABLE[enemy_type as usize];
+    let anime_table = &FLASH_ENEMY_SPRITE_TABLE[enemy_type as usize];
     let sprite_name = anime_table[0];
     let offset = Vec2I::new(-8, -8);
     commands.push((
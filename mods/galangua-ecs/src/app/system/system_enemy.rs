@@ -1,11 +1,12 @@
 use legion::systems::CommandBuffer;
 use legion::world::SubWorld;
 use legion::*;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_xoshiro::Xoshiro128Plus;
 
 use galangua_common::app::consts::*;
 use galangua_common::app::game::attack_manager::AttackManager;
+use galangua_common::app::game::challenging_stage::is_challenging_stage;
 use galangua_common::app::game::effect_table::{to_earned_point_type, FLASH_ENEMY_FRAME};
 use galangua_common::app::game::formation::Formation;
 use galangua_common::app::game::formation_table::{X_COUNT, Y_COUNT};
@@ -124,7 +125,10 @@ pub fn set_enemy_damage(
         create_enemy_explosion_effect(&posture.0, FLASH_ENEMY_FRAME, commands);
 
         game_info.add_score(point, sound_queue);
-        game_info.decrement_alive_enemy();
+        game_info.enemy_destroyed();
+        if enemy_type != EnemyType::CapturedFighter {
+            game_info.score_holder.record.destroyed_count += 1;
+        }
     }
 }
 
@@ -143,13 +147,19 @@ pub fn do_move_zako(
             let posture = <&mut Posture>::query().get_mut(world, entity).unwrap();
             if !zako.base.update_trajectory(posture, speed, &mut accessor) {
                 zako.base.traj = None;
-                if enemy.formation_index.1 >= Y_COUNT as u8 {  // Assault
-                    zako.base.set_assault(speed, world);
+                if is_challenging_stage(game_info.stage) {
+                    commands.remove(entity);
+                    game_info.decrement_alive_enemy();
+                } else if enemy.formation_index.1 >= Y_COUNT as u8 {  // Assault
+                    zako.base.set_assault(speed, &mut game_info.rng, world);
                     zako.state = ZakoState::Assault(0);
                 } else {
                     zako.state = ZakoState::MoveToFormation;
                 }
             }
+            if let Ok(posture) = <&mut Posture>::query().get_mut(world, entity) {
+                forward(posture, speed);
+            }
         }
         ZakoState::Formation => {
             let posture = <&mut Posture>::query().get_mut(world, entity).unwrap();
@@ -170,6 +180,7 @@ pub fn do_move_zako(
                     update_attack_traj(zako, enemy, posture, speed, formation, eneshot_spawner, sound_queue, game_info, entity, commands);
                 }
             }
+            forward(posture, speed);
         }
         ZakoState::MoveToFormation => {
             let posture = <&mut Posture>::query().get_mut(world, entity).unwrap();
@@ -399,10 +410,9 @@ impl EnemyBase {
         self.traj = Some(traj);
     }
 
-    pub fn set_assault(&mut self, speed: &mut Speed, world: &SubWorld) {
+    pub fn set_assault(&mut self, speed: &mut Speed, rng: &mut Xoshiro128Plus, world: &SubWorld) {
         let target_pos = enum_player_target_pos(world);
 
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
         let count = target_pos.iter().count();
         let target: &Vec2I = target_pos.iter()
             .nth(rng.gen_range(0, count)).unwrap();
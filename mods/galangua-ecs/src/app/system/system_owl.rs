@@ -21,7 +21,7 @@ use crate::app::resources::{EneShotSpawner, GameInfo, SoundQueue};
 
 use super::system_enemy::{forward, move_to_formation, set_zako_to_troop, EneBaseAccessorImpl};
 use super::system_player::{
-    escape_player_from_tractor_beam, move_capturing_player, set_player_captured,
+    complete_player_capture, escape_player_from_tractor_beam, move_capturing_player, set_player_captured,
     start_player_capturing, start_recapture_effect,
 };
 
@@ -59,12 +59,14 @@ pub fn do_move_owl(
                 owl.base.traj = None;
                 let enemy = <&Enemy>::query().get(world, entity).unwrap();
                 if enemy.formation_index.1 >= Y_COUNT as u8 {  // Assault
-                    owl.base.set_assault(speed, world);
+                    owl.base.set_assault(speed, &mut game_info.rng, world);
                     owl.state = OwlState::Assault(0);
                 } else {
                     owl.state = OwlState::MoveToFormation;
                 }
             }
+            let posture = <&mut Posture>::query().get_mut(world, entity).unwrap();
+            forward(posture, speed);
         }
         OwlState::Formation => {
             let (enemy, posture) = <(&Enemy, &mut Posture)>::query().get_mut(world, entity).unwrap();
@@ -77,6 +79,9 @@ pub fn do_move_owl(
             let (subworld1, mut subworld2) = world.split::<(&Enemy, &Troops)>();
             let (enemy, troops_opt) = <(&Enemy, Option<&Troops>)>::query().get(&subworld1, entity).unwrap();
             update_attack_traj(owl, entity, enemy, speed, troops_opt, formation, eneshot_spawner, sound_queue, game_info, &mut subworld2);
+
+            let posture = <&mut Posture>::query().get_mut(world, entity).unwrap();
+            forward(posture, speed);
         }
         OwlState::CaptureAttack(phase) => {
             let (mut subworld1, mut subworld2) = world.split::<&mut TractorBeam>();
@@ -105,6 +110,11 @@ pub fn do_move_owl(
             forward(posture, speed);
         }
     }
+
+    // The ghost disappears when all troops are destroyed.
+    if owl.life == 0 && !<&Troops>::query().get(world, entity).is_ok_and(|troops| live_troops_exist(troops, world)) {
+        commands.remove(entity);
+    }
 }
 
 pub fn owl_start_attack(
@@ -245,7 +255,7 @@ fn run_capture_attack(
                     entity,
                     create_tractor_beam(&(&*pos + &Vec2I::new(0, 8 * ONE))));
 
-                sound_queue.push_play_se(CH_JINGLE, SE_TRACTOR_BEAM1);
+                sound_queue.push_play_loop(CH_BEAM, SE_TRACTOR_BEAM1);
 
                 owl.state = OwlState::CaptureAttack(OwlCaptureAttackPhase::CaptureBeam);
                 //self.base.count = 0;
@@ -256,12 +266,14 @@ fn run_capture_attack(
             if tractor_beam_closed(tractor_beam) {
                 //owl.tractor_beam = None;
                 remove_tractor_beam(entity, commands);
+                sound_queue.push_stop_se(CH_BEAM, BEAM_FADE_OUT_MS);
                 let spd = &mut speed.0;
                 *spd = 5 * ONE / 2;
 
                 owl.state = OwlState::CaptureAttack(OwlCaptureAttackPhase::NoCaptureGoOut);
             } else if is_tractor_beam_capturing(tractor_beam) {
                 game_info.capture_player();
+                sound_queue.push_stop_se(CH_BEAM, 0);
                 sound_queue.push_play_se(CH_JINGLE, SE_TRACTOR_BEAM2);
 
                 owl.capturing_state = OwlCapturingState::BeamTracting;
@@ -289,13 +301,18 @@ fn run_capture_attack(
             }
         }
         OwlCaptureAttackPhase::Capturing => {
-            // Handled in TractorBeam.
-        }
-        OwlCaptureAttackPhase::CaptureDoneWait => {
-            if owl.base.count == 0 {
+            // Moving the player is handled in TractorBeam.
+            let closed = tractor_beam_opt.filter(|tractor_beam| tractor_beam_closed(tractor_beam));
+            if let Some(player_entity) = closed.and_then(|tractor_beam| tractor_beam.capturing_player) {
+                let player = <&mut Player>::query().get_mut(world, player_entity).unwrap();
+                complete_player_capture(player);
+                let (enemy, posture) = <(&Enemy, &Posture)>::query().get(world, entity).unwrap();
+                spawn_captured_fighter(&enemy.formation_index, &posture.0, entity, game_info, commands);
                 remove_tractor_beam(entity, commands);
+                on_capturing_player_completed(owl, game_info);
             }
-
+        }
+        OwlCaptureAttackPhase::CaptureDoneWait => {
             owl.base.count += 1;
             if owl.base.count >= 120 {
                 let spd = &mut speed.0;
@@ -340,6 +357,13 @@ fn run_capture_attack(
             };
 
             if done {
+                // Put the captured fighter on the top before releasing.
+                {
+                    let (mut subworld1, mut subworld2) = world.split::<&mut Troops>();
+                    let troops = <&mut Troops>::query().get_mut(&mut subworld1, entity).unwrap();
+                    update_troops(troops, entity, owl, &mut subworld2);
+                }
+
                 //accessor.push_event(EventType::CaptureSequenceEnded);
                 set_to_formation(owl, entity, world, commands);
 
@@ -349,11 +373,9 @@ fn run_capture_attack(
     }
 }
 
-fn set_owl_capturing_player_completed(owl: &mut Owl, captured: bool) {
-    if captured {
-        owl.state = OwlState::CaptureAttack(OwlCaptureAttackPhase::CaptureDoneWait);
-        owl.base.count = 0;
-    }
+fn set_owl_capturing_player_completed(owl: &mut Owl) {
+    owl.state = OwlState::CaptureAttack(OwlCaptureAttackPhase::CaptureDoneWait);
+    owl.base.count = 0;
 }
 
 fn set_to_formation(
@@ -420,6 +442,7 @@ pub fn set_owl_damage(
             if let Ok(tractor_beam) = <&mut TractorBeam>::query().get_mut(&mut subworld1, entity) {
                 break_tractor_beam(tractor_beam, &mut subworld2, commands);
                 remove_tractor_beam(entity, commands);
+                sound_queue.push_stop_se(CH_BEAM, 0);
             }
         }
 
@@ -451,7 +474,7 @@ pub fn set_owl_damage(
 
                     *slot = None;
                 }
-                troops.members.iter().any(|x| x.is_some())
+                live_troops_exist(troops, &subworld2)
             } else {
                 false
             }
@@ -525,8 +548,7 @@ fn remove_tractor_beam(entity: Entity, commands: &mut CommandBuffer) {
 }
 
 pub fn do_move_tractor_beam(
-    tractor_beam: &mut TractorBeam, entity: Entity,
-    owl: &mut Owl, enemy: &Enemy,
+    tractor_beam: &mut TractorBeam,
     game_info: &mut GameInfo,
     star_manager: &mut StarManager,
     attack_manager: &mut AttackManager,
@@ -576,38 +598,16 @@ pub fn do_move_tractor_beam(
             }
         }
         Closing => {
-            let pn = (tractor_beam.size_count + ONE - 1) / ONE;
-            if tractor_beam.size_count > ONE / 3 {
-                tractor_beam.size_count -= ONE / 3;
-            } else {
-                tractor_beam.size_count = 0;
-            }
-            let an = (tractor_beam.size_count + ONE - 1) / ONE;
-            if an != pn {
-                let i = an as usize;
-                if let Some(entity) = tractor_beam.beam_sprites[i].take() {
-                    commands.remove(entity);
-                }
-
-                if an == 0 {
-                    tractor_beam.state = Closed;
-                    let captured = tractor_beam.capturing_player.is_some();
-                    if captured {
-                        star_manager.set_capturing(false);
-                    }
-                    on_capturing_player_completed(owl, captured, game_info);
-                    return;
-                }
-            }
+            close_tractor_beam(tractor_beam, star_manager, commands);
         }
         Closed => {}
         Capturing => {
             let player_entity = tractor_beam.capturing_player.unwrap();
             let (player, posture) = <(&mut Player, &mut Posture)>::query().iter_mut(world).find(|_| true).unwrap();
             if move_capturing_player(player, posture, &(&tractor_beam.pos + &Vec2I::new(0, 8 * ONE))) {
-                on_player_captured(
-                    enemy, &tractor_beam.pos, entity, player_entity, game_info, commands);
+                set_player_captured(player_entity, commands);
                 tractor_beam.state = TractorBeamState::Closing;
+                close_tractor_beam(tractor_beam, star_manager, commands);
             }
         }
     }
@@ -615,6 +615,29 @@ pub fn do_move_tractor_beam(
     update_beam_colors(&tractor_beam.beam_sprites, tractor_beam.color_count, world);
 }
 
+fn close_tractor_beam(tractor_beam: &mut TractorBeam, star_manager: &mut StarManager, commands: &mut CommandBuffer) {
+    let pn = (tractor_beam.size_count + ONE - 1) / ONE;
+    if tractor_beam.size_count > ONE / 3 {
+        tractor_beam.size_count -= ONE / 3;
+    } else {
+        tractor_beam.size_count = 0;
+    }
+    let an = (tractor_beam.size_count + ONE - 1) / ONE;
+    if an != pn {
+        let i = an as usize;
+        if let Some(entity) = tractor_beam.beam_sprites[i].take() {
+            commands.remove(entity);
+        }
+
+        if an == 0 {
+            tractor_beam.state = TractorBeamState::Closed;
+            if tractor_beam.capturing_player.is_some() {
+                star_manager.set_capturing(false);
+            }
+        }
+    }
+}
+
 fn update_beam_colors<'a>(beam_sprites: &[Option<Entity>], color_count: u32, world: &mut SubWorld) {
     for i in 0..beam_sprites.len() {
         if let Some(entity) = &beam_sprites[i] {
@@ -643,22 +666,20 @@ fn can_tractor_beam_capture_player(
     None
 }
 
-fn on_player_captured(
-    enemy: &Enemy,
-    pos: &Vec2I,
+// Spawns when the beam is closed, same as OO version.
+fn spawn_captured_fighter(
+    owner_fi: &FormationIndex,
+    owner_pos: &Vec2I,
     owner: Entity,
-    player: Entity,
     game_info: &mut GameInfo,
     commands: &mut CommandBuffer,
 ) {
-    set_player_captured(player, commands);
-
-    let fi = FormationIndex(enemy.formation_index.0, enemy.formation_index.1 - 1);
+    let fi = FormationIndex(owner_fi.0, owner_fi.1 - 1);
     let base = EnemyBase::new(None);
     let captured = commands.push((
         Enemy { enemy_type: EnemyType::CapturedFighter, formation_index: fi, is_formation: false },
         Zako { base, state: ZakoState::Troop },
-        Posture(pos + &Vec2I::new(0, 8 * ONE), 0),
+        Posture(owner_pos + &Vec2I::new(0, 16 * ONE), 0),
         Speed(0, 0),
         CollRect { offset: Vec2I::new(-6, -6), size: Vec2I::new(12, 12) },
         SpriteDrawable { sprite_name: "rustacean_captured", offset: Vec2I::new(-8, -8) },
@@ -671,11 +692,9 @@ fn on_player_captured(
     game_info.alive_enemy_count += 1;
 }
 
-fn on_capturing_player_completed(owl: &mut Owl, captured: bool, game_info: &mut GameInfo) {
-    set_owl_capturing_player_completed(owl, captured);
-    if captured {
-        game_info.player_captured();
-    }
+fn on_capturing_player_completed(owl: &mut Owl, game_info: &mut GameInfo) {
+    set_owl_capturing_player_completed(owl);
+    game_info.player_captured();
 }
 
 fn tractor_beam_closed(tractor_beam: &TractorBeam) -> bool {
@@ -762,6 +781,11 @@ pub fn update_troops(
     }
 }
 
+fn live_troops_exist(troops: &Troops, world: &SubWorld) -> bool {
+    troops.members.iter().flatten()
+        .any(|troop| <&Enemy>::query().get(world, troop.entity).is_ok())
+}
+
 fn add_captured_player_to_troops(troops: &mut Troops, captured: Entity, offset: &Vec2I) {
     // On capture attack, must be no troops exists.
     assert!(troops.members[0].is_none());
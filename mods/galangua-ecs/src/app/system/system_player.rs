@@ -26,7 +26,6 @@ pub fn new_player() -> Player {
 pub fn do_move_player(
     player: &mut Player, pad: &Pad, entity: Entity,
     game_info: &mut GameInfo,
-    attack_manager: &mut AttackManager,
     world: &mut SubWorld, commands: &mut CommandBuffer,
 ) {
     use PlayerState::*;
@@ -78,9 +77,8 @@ pub fn do_move_player(
             if pos.y >= PLAYER_Y {
                 pos.y = PLAYER_Y;
                 player.state = Normal;
-                commands.push((entity, player_coll_rect()));
+                commands.add_component(entity, player_coll_rect());
 
-                attack_manager.pause(false);
                 game_info.escape_ended();
             }
         }
@@ -174,6 +172,10 @@ pub fn set_player_captured(entity: Entity, commands: &mut CommandBuffer) {
     commands.remove_component::<SpriteDrawable>(entity);
 }
 
+pub fn complete_player_capture(player: &mut Player) {
+    player.state = PlayerState::Dead;
+}
+
 pub fn escape_player_from_tractor_beam(player: &mut Player) {
     player.state = PlayerState::EscapeCapturing;
 }
@@ -340,17 +342,29 @@ pub fn do_recapture_fighter(
                 pos.y == PLAYER_Y
             };
             if done {
-                let (mut subworld1, mut subworld2) = world.split::<&mut Player>();
-                let player = <&mut Player>::query().get_mut(&mut subworld1, me.player_entity).unwrap();
-                let dual = set_player_recapture_done(player, me.player_entity, &mut subworld2, commands);
-                //accessor.push_event(EventType::RecaptureEnded(true));
-                game_info.end_recapturing(dual);
-                attack_manager.pause(false);
-                commands.remove(entity);
-
                 me.state = Done;
+                // Living player docks at the next frame, as it checks the fighter before moving it.
+                let player_living = <&Player>::query().get(world, me.player_entity).unwrap().state != PlayerState::Dead;
+                if !player_living {
+                    end_recapture(me, entity, game_info, world, commands);
+                }
             }
         }
-        Done => {}
+        Done => {
+            end_recapture(me, entity, game_info, world, commands);
+        }
     }
 }
+
+fn end_recapture(
+    me: &RecapturedFighter, entity: Entity,
+    game_info: &mut GameInfo,
+    world: &mut SubWorld, commands: &mut CommandBuffer,
+) {
+    let (mut subworld1, mut subworld2) = world.split::<&mut Player>();
+    let player = <&mut Player>::query().get_mut(&mut subworld1, me.player_entity).unwrap();
+    let dual = set_player_recapture_done(player, me.player_entity, &mut subworld2, commands);
+    //accessor.push_event(EventType::RecaptureEnded(true));
+    game_info.end_recapturing(dual);
+    commands.remove(entity);
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use super::game::manager::game_manager::GameManager;
+use super::game::manager::game_manager::Params as GameManagerParams;
+
+use galangua_common::app::bot::BotEnv;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::app::game::star_manager::StarManager;
+use galangua_common::app::game_config::GameConfig;
+use galangua_common::app::score_holder::ScoreHolder;
+use galangua_common::framework::SystemTrait;
+use galangua_common::util::pad::{Pad, PadBit};
+use galangua_common::util::rng::{create_rng, derive_rng};
+
+// Single game without the title and the rendering, to be played by a bot.
+pub struct BotGame<S: SystemTrait> {
+    game_manager: GameManager,
+    star_manager: StarManager,
+    score_holder: ScoreHolder,
+    pad: Pad,
+    system: S,
+}
+
+impl<S: SystemTrait> BotGame<S> {
+    pub fn new(seed: u64, config: GameConfig, two_players: bool, system: S) -> Self {
+        let mut rng = create_rng(seed);
+        let mut game_manager = GameManager::new(seed, config);
+        game_manager.set_stage_definition(Arc::new(StageDefinition::default()));
+        let mut score_holder = ScoreHolder::new(0);
+        if two_players {
+            game_manager.set_two_players();
+            score_holder.set_two_players(true);
+        }
+
+        Self {
+            game_manager,
+            star_manager: StarManager::new(derive_rng(&mut rng)),
+            score_holder,
+            pad: Pad::default(),
+            system,
+        }
+    }
+
+    pub fn score_holder(&self) -> ScoreHolder {
+        self.score_holder.clone()
+    }
+
+    // Rank of the current player.
+    pub fn rank(&self) -> DifficultyRank {
+        self.game_manager.rank()
+    }
+
+    pub fn system(&self) -> &S {
+        &self.system
+    }
+}
+
+impl<S: SystemTrait> BotEnv for BotGame<S> {
+    fn observe(&self) -> Observation {
+        self.game_manager.observe(self.score_holder.score)
+    }
+
+    fn step(&mut self, bits: PadBit) -> bool {
+        self.pad.set_bits(bits);
+        self.pad.update();
+
+        let mut params = GameManagerParams {
+            star_manager: &mut self.star_manager,
+            pad: &self.pad,
+            score_holder: &mut self.score_holder,
+        };
+        self.game_manager.update(&mut params, &mut self.system);
+        !self.game_manager.is_finished()
+    }
+}
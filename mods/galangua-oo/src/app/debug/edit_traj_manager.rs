@@ -2,15 +2,12 @@ use crate::app::game::manager::event_queue::EventQueue;
 use crate::app::game::manager::game_manager::GameManager;
 use crate::app::util::unsafe_util::peep;
 
-use galangua_common::app::game::traj_command::TrajCommand;
+use galangua_common::app::game::traj_script::parse_traj_script;
 use galangua_common::app::game::FormationIndex;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::{RendererTrait, VKey};
 use galangua_common::util::math::ONE;
 
-use std::fs::File;
-use std::io::{prelude::*, BufReader};
-
 pub struct EditTrajManager {
     fi: FormationIndex,
     no: u32,
@@ -95,128 +92,27 @@ impl EditTrajManager {
 
     fn set_traj_attack(&mut self, game_manager: &mut GameManager, no: u32, flip_x: bool) {
         let filename = format!("debug/debug_traj{}.txt", no);
-        if let Some(traj_command_vec) = load_traj_command_file(&filename) {
-            let stage_manager = game_manager.stage_manager_mut();
-            if let Some(enemy) = stage_manager.get_enemy_at_mut(&self.fi) {
-                if self.from_top {
-                    let pos = *enemy.pos();
-                    enemy.set_pos(&Vec2I::new(pos.x, -16 * ONE));
+        let traj_command_vec = match std::fs::read_to_string(&filename) {
+            Ok(text) => match parse_traj_script(&text) {
+                Ok(vec) => vec,
+                Err(err) => {
+                    eprintln!("{}: {}", &filename, err);
+                    return;
                 }
-                enemy.set_table_attack(traj_command_vec, flip_x);
+            },
+            Err(err) => {
+                eprintln!("{} load failed: {}", &filename, err);
+                return;
             }
-        } else {
-            eprintln!("{} load failed", &filename);
-        }
-    }
-}
-
-fn load_traj_command_file(filename: &str) -> Option<Vec<TrajCommand>> {
-    match File::open(filename) {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-            let mut vec = Vec::new();
-            let mut err = false;
-            let one = ONE as f32;
-            for (lineno, line) in (1..).zip(reader.lines()) {
-                if let Ok(line) = line {
-                    if !line.is_empty() && line.starts_with('#') {
-                        continue;
-                    }
+        };
 
-                    let words: Vec<&str> = line.split_whitespace().collect();
-                    if !words.is_empty() {
-                        match words[0] {
-                            "Pos" if words.len() >= 3 => {
-                                if let (Ok(x), Ok(y)) = (&words[1].parse::<f32>(), &words[2].parse::<f32>()) {
-                                    let ix = (x * one).round() as i32;
-                                    let iy = (y * one).round() as i32;
-                                    vec.push(TrajCommand::Pos(ix, iy));
-                                    continue;
-                                }
-                                println!("Line {}: number expected", lineno);
-                                err = true;
-                            }
-                            "Speed" if words.len() >= 2 => {
-                                if let Ok(speed) = &words[1].parse::<f32>() {
-                                    let ispeed = (speed * one).round() as i32;
-                                    vec.push(TrajCommand::Speed(ispeed));
-                                } else {
-                                    println!("Line {}: number expected", lineno);
-                                    err = true;
-                                }
-                            }
-                            "Angle" if words.len() >= 2 => {
-                                if let Ok(angle) = &words[1].parse::<f32>() {
-                                    let iangle = (angle * one).round() as i32;
-                                    vec.push(TrajCommand::Angle(iangle));
-                                } else {
-                                    println!("Line {}: number expected", lineno);
-                                    err = true;
-                                }
-                            }
-                            "VAngle" if words.len() >= 2 => {
-                                if let Ok(vangle) = words[1].parse::<f32>() {
-                                    let ivangle = (vangle * one).round() as i32;
-                                    vec.push(TrajCommand::VAngle(ivangle));
-                                } else {
-                                    println!("Line {}: number expected", lineno);
-                                    err = true;
-                                }
-                            }
-                            "Delay" if words.len() >= 2 => {
-                                if let Ok(delay) = words[1].parse::<u32>() {
-                                    vec.push(TrajCommand::Delay(delay));
-                                } else {
-                                    println!("Line {}: number expected", lineno);
-                                    err = true;
-                                }
-                            }
-                            "DestAngle" if words.len() >= 3 => {
-                                if let (Ok(angle), Ok(radius)) = (&words[1].parse::<f32>(), &words[2].parse::<f32>()) {
-                                    let iangle = (angle * one).round() as i32;
-                                    let iradius = (radius * one).round() as i32;
-                                    vec.push(TrajCommand::DestAngle(iangle, iradius));
-                                    continue;
-                                }
-                                println!("Line {}: number expected", lineno);
-                                err = true;
-                            }
-                            "WaitYG" if words.len() >= 2 => {
-                                if let Ok(value) = &words[1].parse::<f32>() {
-                                    let ivalue = (value * one).round() as i32;
-                                    vec.push(TrajCommand::WaitYG(ivalue));
-                                } else {
-                                    println!("Line {}: number expected", lineno);
-                                    err = true;
-                                }
-                            }
-                            "AddPos" if words.len() >= 3 => {
-                                if let (Ok(x), Ok(y)) = (&words[1].parse::<f32>(), &words[2].parse::<f32>()) {
-                                    let ix = (x * one).round() as i32;
-                                    let iy = (y * one).round() as i32;
-                                    vec.push(TrajCommand::AddPos(ix, iy));
-                                    continue;
-                                }
-                                println!("Line {}: number expected", lineno);
-                                err = true;
-                            }
-                            "CopyFormationX" => {
-                                vec.push(TrajCommand::CopyFormationX);
-                            }
-                            _ => {
-                                println!("Line {}: Unhandled, {:?}", lineno, words);
-                                err = true;
-                            }
-                        }
-                    }
-                }
-            }
-            if err {
-                None
-            } else {
-                Some(vec)
+        let stage_manager = game_manager.stage_manager_mut();
+        if let Some(enemy) = stage_manager.get_enemy_at_mut(&self.fi) {
+            if self.from_top {
+                let pos = *enemy.pos();
+                enemy.set_pos(&Vec2I::new(pos.x, -16 * ONE));
             }
+            enemy.set_table_attack(traj_command_vec, flip_x);
         }
-        Err(_err) => None,
     }
 }
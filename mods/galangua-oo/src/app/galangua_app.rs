@@ -1,12 +1,32 @@
+use rand::Rng;
+use rand_xoshiro::Xoshiro128Plus;
+use serde_json::json;
+use std::sync::Arc;
+
 use super::game::manager::game_manager::GameManager;
 use super::game::manager::game_manager::Params as GameManagerParams;
 
+use galangua_common::app::attract_mode::{attract_phase, ranking_start_frame, AttractPhase, DemoPlay, DEMO_SEED};
+use galangua_common::app::audio_volume::AudioVolume;
 use galangua_common::app::consts::*;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
 use galangua_common::app::game::star_manager::StarManager;
+use galangua_common::app::game_config::GameConfig;
+use galangua_common::app::high_score_table::HighScoreTable;
+use galangua_common::app::lifetime_stats::LifetimeStats;
+use galangua_common::app::name_entry::NameEntry;
+use galangua_common::app::options_screen::OptionsScreen;
+use galangua_common::app::pause_menu::{PauseCommand, PauseMenu};
+use galangua_common::app::results_screen::ResultsScreen;
+use galangua_common::app::scene::Scene;
 use galangua_common::app::score_holder::ScoreHolder;
+use galangua_common::app::util::snapshot::{from_json, to_json};
 use galangua_common::framework::{AppTrait, RendererTrait, SystemTrait, VKey};
 use galangua_common::util::fps_calc::{FpsCalc, TimerTrait};
 use galangua_common::util::pad::{Pad, PadBit};
+use galangua_common::util::pad_binding::PadBinding;
+use galangua_common::util::rng::{create_rng, derive_rng};
 
 #[cfg(debug_assertions)]
 use super::debug::EditTrajManager;
@@ -14,7 +34,11 @@ use super::debug::EditTrajManager;
 #[derive(PartialEq)]
 enum AppState {
     Title,
+    Demo,
     Game,
+    Results,
+    NameEntry,
+    Options,
 
     #[cfg(debug_assertions)]
     EditTraj,
@@ -27,10 +51,25 @@ pub struct GalanguaApp<T: TimerTrait, S: SystemTrait> {
     pressed_key: Option<VKey>,
     fps_calc: FpsCalc<T>,
     game_manager: Option<GameManager>,
+    demo_play: Option<DemoPlay>,
+    demo_score_holder: ScoreHolder,
     star_manager: StarManager,
     frame_count: u32,
     score_holder: ScoreHolder,
     prev_high_score: u32,
+    high_score_table: HighScoreTable,
+    lifetime_stats: LifetimeStats,
+    results_screen: Option<ResultsScreen>,
+    name_entry: Option<NameEntry>,
+    options_screen: Option<OptionsScreen>,
+    pause_menu: Option<PauseMenu>,
+    game_config: GameConfig,
+    audio_volume: AudioVolume,
+    ranking_highlight: Option<usize>,
+    stage_definition: Arc<StageDefinition>,
+    quick_save: Option<String>,
+    snapshot_error: Option<String>,  // Last failure of quick save or load.
+    rng: Xoshiro128Plus,
 
     #[cfg(debug_assertions)]
     paused: bool,
@@ -39,22 +78,45 @@ pub struct GalanguaApp<T: TimerTrait, S: SystemTrait> {
 }
 
 impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
-    pub fn new(timer: T, system: S) -> Self {
-        let high_score = system.get_u32(&KEY_HIGH_SCORE)
-                .or(Some(DEFAULT_HIGH_SCORE))
-                .unwrap();
+    pub fn new(timer: T, system: S, seed: u64) -> Self {
+        let high_score_table = HighScoreTable::load(&system);
+        let high_score = std::cmp::max(
+            system.get_u32(&KEY_HIGH_SCORE).unwrap_or(DEFAULT_HIGH_SCORE),
+            high_score_table.top_score().unwrap_or(0));
+        let mut rng = create_rng(seed);
+        let star_manager = StarManager::new(derive_rng(&mut rng));
+        let mut pad = Pad::default();
+        pad.set_binding(PadBinding::load(&system));
+        let game_config = GameConfig::load(&system);
+        let audio_volume = AudioVolume::load(&system);
+        let lifetime_stats = LifetimeStats::load(&system);
 
         Self {
             system,
             state: AppState::Title,
-            pad: Pad::default(),
+            pad,
             pressed_key: None,
             fps_calc: FpsCalc::new(timer),
             game_manager: None,
-            star_manager: StarManager::default(),
+            demo_play: None,
+            demo_score_holder: ScoreHolder::new(high_score),
+            star_manager,
             frame_count: 0,
             score_holder: ScoreHolder::new(high_score),
             prev_high_score: 0,
+            high_score_table,
+            lifetime_stats,
+            results_screen: None,
+            name_entry: None,
+            options_screen: None,
+            pause_menu: None,
+            game_config,
+            audio_volume,
+            ranking_highlight: None,
+            stage_definition: Arc::new(StageDefinition::default()),
+            quick_save: None,
+            snapshot_error: None,
+            rng,
 
             #[cfg(debug_assertions)]
             paused: false,
@@ -65,13 +127,27 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
 
     fn update_main(&mut self) -> bool {
         if self.pressed_key == Some(VKey::Escape) {
-            if self.state != AppState::Title {
-                self.back_to_title();
-            } else {
-                return false;
+            match self.state {
+                AppState::Title => return false,
+                AppState::Game => self.toggle_pause(),
+                AppState::Results => {
+                    // Skipping the results must not skip entering initials.
+                    self.results_screen = None;
+                    self.enter_name_entry();
+                }
+                _ => self.back_to_title(),
             }
         }
 
+        let snapshot_result = match self.pressed_key {
+            Some(VKey::F5) => self.quick_save(),
+            Some(VKey::F9) => self.quick_load(),
+            _ => Ok(()),
+        };
+        if let Err(err) = snapshot_result {
+            self.snapshot_error = Some(err);
+        }
+
         #[cfg(debug_assertions)]
         {
             if self.pressed_key == Some(VKey::Return) {
@@ -82,25 +158,63 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
             }
         }
 
+        // The game and the stars are frozen while paused.
+        if let Some(pause_menu) = &mut self.pause_menu {
+            match pause_menu.update(&self.pad) {
+                Some(PauseCommand::Resume) => self.resume(),
+                Some(PauseCommand::Restart) => self.restart_game(),
+                Some(PauseCommand::Quit) => self.back_to_title(),
+                None => {}
+            }
+            return true;
+        }
+
         self.star_manager.update();
 
         match self.state {
             AppState::Title => {
                 self.frame_count = self.frame_count.wrapping_add(1);
                 if self.pad.is_trigger(PadBit::A) {
-                    self.start_game();
+                    self.start_game(false);
+                } else if self.pressed_key == Some(VKey::Num2) {
+                    self.start_game(true);
+                } else if self.pressed_key == Some(VKey::O) {
+                    self.options_screen = Some(OptionsScreen::new(&self.game_config, &self.audio_volume, self.pad.binding()));
+                    self.state = AppState::Options;
+                } else if attract_phase(self.frame_count) == AttractPhase::Demo {
+                    self.start_demo();
                 }
 
                 #[cfg(debug_assertions)]
                 if self.pressed_key == Some(VKey::E) {
                     self.state = AppState::EditTraj;
 
-                    let mut game_manager = GameManager::new();
+                    let mut game_manager = GameManager::new(self.rng.gen(), self.game_config);
                     game_manager.start_edit_mode();
                     self.game_manager = Some(game_manager);
                     self.edit_traj_manager = Some(EditTrajManager::new());
                 }
             }
+            AppState::Demo => {
+                // Any key exits the demo.
+                if self.pressed_key.is_some() || self.pad.raw_trigger().is_some() {
+                    self.back_to_title();
+                    return true;
+                }
+
+                let game_manager = self.game_manager.as_mut().unwrap();
+                let demo_play = self.demo_play.as_mut().unwrap();
+                let observation = game_manager.observe(self.demo_score_holder.score);
+                let mut params = GameManagerParams {
+                    star_manager: &mut self.star_manager,
+                    pad: demo_play.update(&observation),
+                    score_holder: &mut self.demo_score_holder,
+                };
+                game_manager.update(&mut params, &mut self.system);
+                if game_manager.is_finished() || demo_play.is_over() {
+                    self.back_to_title();
+                }
+            }
             AppState::Game => {
                 self.frame_count = self.frame_count.wrapping_add(1);
                 let mut params = GameManagerParams {
@@ -111,6 +225,32 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
                 let game_manager = self.game_manager.as_mut().unwrap();
                 game_manager.update(&mut params, &mut self.system);
                 if game_manager.is_finished() {
+                    self.end_game();
+                }
+            }
+            AppState::Results => {
+                if !self.results_screen.as_mut().unwrap().update(&self.pad) {
+                    self.results_screen = None;
+                    self.enter_name_entry();
+                }
+            }
+            AppState::NameEntry => {
+                if !self.name_entry.as_mut().unwrap().update(&self.pad, &mut self.high_score_table) {
+                    self.back_to_title();
+                }
+            }
+            AppState::Options => {
+                if !self.options_screen.as_mut().unwrap().update(&self.pad) {
+                    let options_screen = self.options_screen.take().unwrap();
+                    self.game_config = *options_screen.config();
+                    self.audio_volume = *options_screen.volume();
+                    self.pad.set_binding(options_screen.binding().clone());
+                    self.audio_volume.apply(&mut self.system);
+                    self.system.begin_transaction();
+                    self.game_config.save(&mut self.system).expect("game config");
+                    self.audio_volume.save(&mut self.system).expect("audio volume");
+                    self.pad.binding().save(&mut self.system);
+                    self.system.commit_transaction();
                     self.back_to_title();
                 }
             }
@@ -139,6 +279,10 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
     fn draw_main<R: RendererTrait>(&mut self, renderer: &mut R) {
         self.star_manager.draw(renderer);
         match self.state {
+            AppState::Title if attract_phase(self.frame_count) == AttractPhase::Ranking => {
+                self.high_score_table.draw(renderer, self.ranking_highlight);
+                self.score_holder.draw(renderer, true);
+            }
             AppState::Title => {
                 renderer.set_texture_color_mod("font", 255, 255, 255);
                 renderer.draw_str("font", 10 * 8, 8 * 8, "GALANGUA");
@@ -150,15 +294,40 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
                         "PRESS SPACE KEY TO START"
                     };
                     renderer.draw_str("font", (28 - msg.len() as i32) / 2 * 8, 25 * 8, msg);
+                    if !self.system.is_touch_device() {
+                        let msg = "PRESS 2 KEY FOR 2 PLAYERS";
+                        renderer.draw_str("font", (28 - msg.len() as i32) / 2 * 8, 27 * 8, msg);
+                        let msg = "PRESS O KEY FOR OPTIONS";
+                        renderer.draw_str("font", (28 - msg.len() as i32) / 2 * 8, 29 * 8, msg);
+                    }
                 }
                 self.score_holder.draw(renderer, true);
 
                 renderer.set_texture_color_mod("font", 128, 128, 128);
                 renderer.draw_str("font", WIDTH - (VERSION.len() as i32) * 8, HEIGHT - 1 * 8, VERSION);
             }
+            AppState::Demo => {
+                self.game_manager.as_mut().unwrap().draw(renderer);
+                self.demo_play.as_ref().unwrap().draw(renderer);
+                self.demo_score_holder.draw(renderer, true);
+            }
             AppState::Game => {
                 self.game_manager.as_mut().unwrap().draw(renderer);
                 self.score_holder.draw(renderer, (self.frame_count & 31) < 16);
+                if let Some(pause_menu) = &self.pause_menu {
+                    pause_menu.draw(renderer);
+                }
+            }
+            AppState::Results => {
+                self.results_screen.as_ref().unwrap().draw(renderer);
+                self.score_holder.draw(renderer, true);
+            }
+            AppState::NameEntry => {
+                self.name_entry.as_ref().unwrap().draw(renderer);
+                self.score_holder.draw(renderer, true);
+            }
+            AppState::Options => {
+                self.options_screen.as_ref().unwrap().draw(renderer);
             }
             #[cfg(debug_assertions)]
             AppState::EditTraj => {
@@ -169,8 +338,11 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
             }
         }
 
-        renderer.set_texture_color_mod("font", 128, 128, 128);
-        renderer.draw_str("font", 21 * 8, 1 * 8, "OO VER.");
+        // 2UP score is drawn at the same place.
+        if !self.score_holder.is_two_players() {
+            renderer.set_texture_color_mod("font", 128, 128, 128);
+            renderer.draw_str("font", 21 * 8, 1 * 8, "OO VER.");
+        }
 
         #[cfg(debug_assertions)]
         {
@@ -179,29 +351,212 @@ impl<T: TimerTrait, S: SystemTrait> GalanguaApp<T, S> {
         }
     }
 
-    fn start_game(&mut self) {
-        self.game_manager = Some(GameManager::new());
+    pub fn set_stage_definition(&mut self, definition: StageDefinition) {
+        self.stage_definition = Arc::new(definition);
+    }
+
+    pub fn observe(&self) -> Option<Observation> {
+        match self.state {
+            AppState::Game => self.game_manager.as_ref().map(|game_manager| game_manager.observe(self.score_holder.score)),
+            AppState::Demo => self.game_manager.as_ref().map(|game_manager| game_manager.observe(self.demo_score_holder.score)),
+            _ => None,
+        }
+    }
+
+    // Score of the running game, `None` in the demo.
+    pub fn score_holder(&self) -> Option<ScoreHolder> {
+        match self.state {
+            AppState::Game => Some(self.score_holder.clone()),
+            _ => None,
+        }
+    }
+
+    // Save state of the running game, in JSON.
+    pub fn save_snapshot(&self) -> Result<String, String> {
+        let game_manager = match (&self.state, &self.game_manager) {
+            (AppState::Game, Some(game_manager)) => game_manager,
+            _ => return Err(String::from("Not in game")),
+        };
+        let snapshot = json!({
+            "app": "oo",
+            "score_holder": to_json(&self.score_holder)?,
+            "star_manager": to_json(&self.star_manager)?,
+            "game": to_json(game_manager)?,
+        });
+        Ok(snapshot.to_string())
+    }
+
+    pub fn load_snapshot(&mut self, text: &str) -> Result<(), String> {
+        let snapshot: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if snapshot["app"] != "oo" {
+            return Err(format!("Not a snapshot for OO version: {}", snapshot["app"]));
+        }
+        let mut score_holder: ScoreHolder = from_json(&snapshot, "score_holder")?;
+        let star_manager: StarManager = from_json(&snapshot, "star_manager")?;
+        let mut game_manager: GameManager = from_json(&snapshot, "game")?;
+        game_manager.set_stage_definition(self.stage_definition.clone());
+
+        let high_score = self.score_holder.high_score;
+        score_holder.high_score = std::cmp::max(score_holder.high_score, high_score);
+        self.prev_high_score = high_score;
+        self.score_holder = score_holder;
+        self.star_manager = star_manager;
+        self.game_manager = Some(game_manager);
+        self.pause_menu = None;
+        self.state = AppState::Game;
+        Ok(())
+    }
+
+    pub fn scene(&self) -> Scene {
+        match self.state {
+            AppState::Title => Scene::Title,
+            AppState::Demo => Scene::Demo,
+            AppState::Game => Scene::Game,
+            AppState::Results => Scene::Results,
+            AppState::NameEntry => Scene::NameEntry,
+            AppState::Options => Scene::Options,
+
+            #[cfg(debug_assertions)]
+            AppState::EditTraj => Scene::EditTraj,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_menu.is_some()
+    }
+
+    pub fn quick_save_slot(&self) -> Option<&str> {
+        self.quick_save.as_deref()
+    }
+
+    // Replaces the slot loaded by the key, e.g. with a snapshot kept in a file.
+    pub fn set_quick_save_slot(&mut self, snapshot: Option<String>) {
+        self.quick_save = snapshot;
+    }
+
+    // Error of the last quick save or load by the keys, which doesn't stop the app.
+    pub fn snapshot_error(&self) -> Option<&str> {
+        self.snapshot_error.as_deref()
+    }
+
+    pub fn quick_save(&mut self) -> Result<(), String> {
+        let snapshot = self.save_snapshot().map_err(|err| format!("Quick save failed: {}", err))?;
+        self.quick_save = Some(snapshot);
+        Ok(())
+    }
+
+    // Does nothing without a quick save, and keeps it even if loading fails.
+    pub fn quick_load(&mut self) -> Result<(), String> {
+        if let Some(snapshot) = self.quick_save.take() {
+            let result = self.load_snapshot(&snapshot);
+            self.quick_save = Some(snapshot);
+            result.map_err(|err| format!("Quick load failed: {}", err))?;
+            self.system.stop_all_se();  // Looping sounds must not go on.
+        }
+        Ok(())
+    }
+
+    fn start_game(&mut self, two_players: bool) {
+        let mut game_manager = GameManager::new(self.rng.gen(), self.game_config);
+        game_manager.set_stage_definition(self.stage_definition.clone());
+        if two_players {
+            game_manager.set_two_players();
+        }
+        self.game_manager = Some(game_manager);
         self.prev_high_score = self.score_holder.high_score;
+        self.score_holder.set_two_players(two_players);
         self.score_holder.reset_score();
+        self.ranking_highlight = None;
+        self.pause_menu = None;
 
         self.state = AppState::Game;
         self.frame_count = 0;
     }
 
+    fn restart_game(&mut self) {
+        self.save_high_score();
+        self.system.stop_all_se();
+        self.start_game(self.score_holder.is_two_players());
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.pause_menu.is_some() {
+            self.resume();
+        } else {
+            self.pause_menu = Some(PauseMenu::default());
+            self.system.stop_all_se();
+        }
+    }
+
+    fn resume(&mut self) {
+        self.pause_menu = None;
+        if let Some(game_manager) = &self.game_manager {
+            game_manager.resume_se(&mut self.system);
+        }
+    }
+
+    // Plays the same game every time, not to affect the score.
+    fn start_demo(&mut self) {
+        let mut game_manager = GameManager::new(DEMO_SEED, GameConfig::default());
+        game_manager.set_stage_definition(self.stage_definition.clone());
+        self.game_manager = Some(game_manager);
+        self.demo_play = Some(DemoPlay::default());
+        self.demo_score_holder = ScoreHolder::new(self.score_holder.high_score);
+
+        self.state = AppState::Demo;
+    }
+
+    // Shows the results of the game at first.
+    fn end_game(&mut self) {
+        self.save_high_score();
+        self.system.stop_all_se();
+        self.game_manager = None;
+        self.lifetime_stats.add_game(&self.score_holder);
+        self.lifetime_stats.save(&mut self.system).expect("lifetime stats");
+        self.results_screen = Some(ResultsScreen::new(&self.score_holder, &self.lifetime_stats));
+        self.state = AppState::Results;
+    }
+
+    // Enters initials if the score ranks in, otherwise goes back to the title.
+    fn enter_name_entry(&mut self) {
+        match NameEntry::new(&self.score_holder, &self.high_score_table) {
+            Some(name_entry) => {
+                self.name_entry = Some(name_entry);
+                self.state = AppState::NameEntry;
+            }
+            None => self.back_to_title(),
+        }
+    }
+
     fn back_to_title(&mut self) {
+        self.system.stop_all_se();  // Looping sounds must not go on.
         self.game_manager = None;
+        self.demo_play = None;
+        self.options_screen = None;
+        self.results_screen = None;
+        self.pause_menu = None;
         self.star_manager.set_stop(false);
 
-        if self.score_holder.high_score > self.prev_high_score {
-            self.on_high_score_updated();
+        self.save_high_score();
+
+        // Shows the ranking at first after initials are entered.
+        self.frame_count = 0;
+        if let Some(name_entry) = self.name_entry.take() {
+            self.high_score_table.save(&mut self.system).expect("high score table");
+            self.ranking_highlight = name_entry.last_rank();
+            if self.ranking_highlight.is_some() {
+                self.frame_count = ranking_start_frame();
+            }
         }
 
         self.state = AppState::Title;
-        self.frame_count = 0;
     }
 
-    fn on_high_score_updated(&mut self) {
-        self.system.set_u32(KEY_HIGH_SCORE, self.score_holder.high_score);
+    fn save_high_score(&mut self) {
+        if self.score_holder.high_score > self.prev_high_score {
+            self.system.set_u32(KEY_HIGH_SCORE, self.score_holder.high_score);
+            self.prev_high_score = self.score_holder.high_score;
+        }
     }
 }
 
@@ -221,9 +576,17 @@ impl<R: RendererTrait, T: TimerTrait, S: SystemTrait> AppTrait<R> for GalanguaAp
         self.pad.on_joystick_button(button_index, down);
     }
 
+    fn on_focus(&mut self, focused: bool) {
+        if !focused && self.state == AppState::Game && self.pause_menu.is_none() {
+            self.toggle_pause();
+        }
+    }
+
     fn init(&mut self, renderer: &mut R) {
         renderer.load_textures("assets", &["chr.png", "font.png"]);
         renderer.load_sprite_sheet("assets/chr.json");
+        self.system.load_sounds(&SOUND_ASSETS);
+        self.audio_volume.apply(&mut self.system);
     }
 
     fn update(&mut self) -> bool {
@@ -1,10 +1,14 @@
+use serde::{Deserialize, Serialize};
+
 use galangua_common::app::consts::*;
 use galangua_common::app::game::effect_table::*;
 use galangua_common::app::game::{EarnedPointType, EnemyType};
+use galangua_common::app::util::snapshot;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::RendererTrait;
 use galangua_common::util::math::{quantize_angle, round_vec};
 
+#[derive(Serialize, Deserialize)]
 pub enum Effect {
     SequentialSpriteAnime(SequentialSpriteAnime),
     RotSprite(RotSprite),
@@ -20,7 +24,7 @@ impl Effect {
     }
 
     pub fn create_flash_enemy(pos: &Vec2I, angle: i32, enemy_type: EnemyType) -> Self {
-        let sprite_name = FLASH_ENEMY_SPRITE_NAMES[enemy_type as usize];
+        let sprite_name = FLASH_ENEMY_SPRITE_TABLE[enemy_type as usize][0];
         Effect::RotSprite(
             RotSprite::new(
                 &round_vec(&pos) + &Vec2I::new(-8, -8),
@@ -62,8 +66,10 @@ impl Effect {
 
 //
 
+#[derive(Serialize, Deserialize)]
 pub struct SequentialSpriteAnime {
     pos: Vec2I,
+    #[serde(with = "snapshot::sprite_anime")]
     sprites: &'static [&'static str],
     delay: u32,
     frame_wait: u32,
@@ -110,10 +116,12 @@ impl SequentialSpriteAnime {
 
 //
 
+#[derive(Serialize, Deserialize)]
 pub struct RotSprite {
     pos: Vec2I,
     angle: u8,
-    sprite_name: &'static str,
+    #[serde(with = "snapshot::sprite_name")]
+    sprite_name: snapshot::StaticStr,
     duration: u32,
     count: u32,
 }
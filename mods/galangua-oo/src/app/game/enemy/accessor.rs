@@ -1,5 +1,7 @@
 // Accessor of game information for Enemy.
 
+use rand_xoshiro::Xoshiro128Plus;
+
 use super::Enemy;
 
 use crate::app::game::manager::EventType;
@@ -21,6 +23,7 @@ pub trait Accessor {
     fn pause_enemy_shot(&mut self, wait: u32);
     fn is_rush(&self) -> bool;
     fn get_stage_no(&self) -> u16;
+    fn rng(&mut self) -> &mut Xoshiro128Plus;
 
     fn push_event(&mut self, event: EventType);
 }
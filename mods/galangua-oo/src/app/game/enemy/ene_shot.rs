@@ -1,9 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 use galangua_common::app::consts::*;
 use galangua_common::app::util::collision::{CollBox, Collidable};
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::RendererTrait;
 use galangua_common::util::math::{round_vec, ONE};
 
+#[derive(Serialize, Deserialize)]
 pub struct EneShot {
     pos: Vec2I,
     vel: Vec2I,
@@ -26,6 +29,10 @@ impl EneShot {
         let pos = round_vec(&self.pos);
         renderer.draw_sprite("ene_shot", &(&pos + &Vec2I::new(-2, -4)));
     }
+
+    pub fn pos(&self) -> &Vec2I {
+        &self.pos
+    }
 }
 
 impl Collidable for EneShot {
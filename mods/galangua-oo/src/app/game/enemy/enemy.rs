@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use super::owl::{Owl, OwlState};
 use super::zako::{Zako, ZakoState};
 use super::{Accessor, DamageResult};
@@ -15,6 +17,7 @@ pub trait Enemy: Collidable {
     fn update(&mut self, accessor: &mut dyn Accessor) -> bool;
     fn draw(&self, renderer: &mut dyn RendererTrait, pat: usize);
 
+    fn enemy_type(&self) -> EnemyType;
     fn pos(&self) -> &Vec2I;
     fn set_pos(&mut self, pos: &Vec2I);
     fn angle(&self) -> i32;
@@ -31,10 +34,37 @@ pub trait Enemy: Collidable {
     fn set_to_troop(&mut self);
     fn set_to_formation(&mut self);
 
+    fn tractor_beam_pos(&self) -> Option<Vec2I>;
+
+    fn snapshot(&self) -> EnemySnapshot<'_>;
+
     #[cfg(debug_assertions)]
     fn set_table_attack(&mut self, traj_command_vec: Vec<TrajCommand>, flip_x: bool);
 }
 
+// Concrete enemy, to save `dyn Enemy` in a save state.
+#[derive(Serialize)]
+pub enum EnemySnapshot<'a> {
+    Zako(&'a Zako),
+    Owl(&'a Owl),
+}
+
+// Loaded from `EnemySnapshot`.
+#[derive(Deserialize)]
+pub enum SavedEnemy {
+    Zako(Zako),
+    Owl(Owl),
+}
+
+impl SavedEnemy {
+    pub fn into_enemy(self) -> Box<dyn Enemy> {
+        match self {
+            SavedEnemy::Zako(zako) => Box::new(zako),
+            SavedEnemy::Owl(owl) => Box::new(owl),
+        }
+    }
+}
+
 //================================================
 
 pub fn create_enemy(
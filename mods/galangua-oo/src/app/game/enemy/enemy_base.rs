@@ -1,5 +1,5 @@
-use rand::{Rng, SeedableRng};
-use rand_xoshiro::Xoshiro128Plus;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use super::Accessor;
 
@@ -26,6 +26,7 @@ impl<'a> TrajAccessor for TrajAccessorImpl<'a> {
     fn get_stage_no(&self) -> u16 { self.accessor.get_stage_no() }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct EnemyInfo {
     pub(super) pos: Vec2I,
     pub(super) angle: i32,
@@ -66,6 +67,7 @@ impl EnemyInfo {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct EnemyBase {
     pub(super) traj: Option<Traj>,
     pub(super) shot_wait: Option<u32>,
@@ -134,15 +136,14 @@ impl EnemyBase {
         self.traj = Some(traj);
     }
 
-    pub(super) fn set_assault(&mut self, info: &mut EnemyInfo, accessor: &dyn Accessor) {
-        let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
+    pub(super) fn set_assault(&mut self, info: &mut EnemyInfo, accessor: &mut dyn Accessor) {
         let target_pos = [
             Some(*accessor.get_player_pos()),
             accessor.get_dual_player_pos(),
         ];
         let count = target_pos.iter().flatten().count();
         let target: &Vec2I = target_pos.iter()
-            .flatten().nth(rng.gen_range(0, count)).unwrap();
+            .flatten().nth(accessor.rng().gen_range(0, count)).unwrap();
 
         self.target_pos = *target;
         info.vangle = 0;
@@ -1,4 +1,6 @@
-use super::enemy::Enemy;
+use serde::{Deserialize, Serialize};
+
+use super::enemy::{Enemy, EnemySnapshot};
 use super::enemy_base::{EnemyBase, EnemyInfo};
 use super::tractor_beam::TractorBeam;
 use super::{Accessor, DamageResult};
@@ -25,7 +27,7 @@ const LIFE: u32 = 2;
 
 const OWL_SPRITE_NAMES: [&str; 4] = ["cpp11", "cpp12", "cpp21", "cpp22"];
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub(super) enum OwlAttackPhase {
     Traj,
     Capture,
@@ -38,7 +40,7 @@ pub(super) enum OwlAttackPhase {
     CaptureDonePushUp,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub(super) enum OwlState {
     None,
     Appearance,
@@ -48,7 +50,7 @@ pub(super) enum OwlState {
     Attack(OwlAttackPhase),
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum CapturingState {
     None,
     Attacking,
@@ -57,6 +59,7 @@ enum CapturingState {
     Failed,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Owl {
     pub(super) info: EnemyInfo,
     pub(super) base: EnemyBase,
@@ -239,7 +242,7 @@ impl Owl {
             self.info.vangle = 0;
 
             self.tractor_beam = Some(TractorBeam::new(&(&self.info.pos + &Vec2I::new(0, 8 * ONE))));
-            accessor.push_event(EventType::PlaySe(CH_JINGLE, SE_TRACTOR_BEAM1));
+            accessor.push_event(EventType::PlayLoopSe(CH_BEAM, SE_TRACTOR_BEAM1));
 
             self.set_state(OwlState::Attack(OwlAttackPhase::CaptureBeam));
             self.base.count = 0;
@@ -249,12 +252,14 @@ impl Owl {
         let tractor_beam = self.tractor_beam.as_mut().unwrap();
         if tractor_beam.closed() {
             self.tractor_beam = None;
+            accessor.push_event(EventType::StopSe(CH_BEAM, BEAM_FADE_OUT_MS));
             self.info.speed = 5 * ONE / 2;
             self.set_state(OwlState::Attack(OwlAttackPhase::NoCaptureGoOut));
         } else if accessor.can_player_capture() &&
                     tractor_beam.can_capture(accessor.get_player_pos())
         {
             accessor.push_event(EventType::CapturePlayer(&self.info.pos + &Vec2I::new(0, 16 * ONE)));
+            accessor.push_event(EventType::StopSe(CH_BEAM, 0));
             accessor.push_event(EventType::PlaySe(CH_JINGLE, SE_TRACTOR_BEAM2));
             tractor_beam.start_capture();
             self.capturing_state = CapturingState::BeamTracting;
@@ -376,15 +381,20 @@ impl Owl {
             self.life = 0;
             let point = self.calc_point();
 
+            if self.tractor_beam.is_some() {
+                accessor.push_event(EventType::StopSe(CH_BEAM, 0));
+            }
+
             // Release capturing.
             match self.capturing_state {
                 CapturingState::None => {
                     let cap_fi = FormationIndex(self.info.formation_index.0, self.info.formation_index.1 - 1);
-                    if let Some(slot) = self.troops.iter_mut().filter(|x| x.is_some())
-                        .find(|slot| slot.unwrap() == cap_fi)
-                    {
-                        let cap_fighter = accessor.get_enemy_at(&cap_fi).unwrap();
-                        let angle = cap_fighter.angle();
+                    // The captured fighter might be destroyed already.
+                    let cap_fighter_angle = accessor.get_enemy_at(&cap_fi).map(|cap_fighter| cap_fighter.angle());
+                    if let (Some(slot), Some(angle)) = (
+                        self.troops.iter_mut().filter(|x| x.is_some()).find(|slot| slot.unwrap() == cap_fi),
+                        cap_fighter_angle,
+                    ) {
                         accessor.push_event(EventType::RecapturePlayer(cap_fi, angle));
                         *slot = None;
                     }
@@ -463,6 +473,7 @@ impl Enemy for Owl {
         }
     }
 
+    fn enemy_type(&self) -> EnemyType { EnemyType::Owl }
     fn pos(&self) -> &Vec2I { &self.info.pos }
     fn set_pos(&mut self, pos: &Vec2I) { self.info.pos = *pos; }
     fn angle(&self) -> i32 { self.info.angle }
@@ -532,6 +543,12 @@ impl Enemy for Owl {
         }
     }
 
+    fn tractor_beam_pos(&self) -> Option<Vec2I> {
+        self.tractor_beam.as_ref().map(|tractor_beam| *tractor_beam.pos())
+    }
+
+    fn snapshot(&self) -> EnemySnapshot<'_> { EnemySnapshot::Owl(self) }
+
     #[cfg(debug_assertions)]
     fn set_table_attack(&mut self, traj_command_vec: Vec<TrajCommand>, flip_x: bool) {
         self.base.set_table_attack(&mut self.info, traj_command_vec, flip_x);
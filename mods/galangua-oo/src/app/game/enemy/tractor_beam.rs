@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 use galangua_common::app::game::tractor_beam_table::*;
 use galangua_common::app::util::hsv;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::RendererTrait;
 use galangua_common::util::math::{round_vec, ONE};
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 enum State {
     Opening,
     Full,
@@ -13,6 +15,7 @@ enum State {
     Capturing,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct TractorBeam {
     pos: Vec2I,
     state: State,
@@ -80,6 +83,10 @@ impl TractorBeam {
         }
     }
 
+    pub fn pos(&self) -> &Vec2I {
+        &self.pos
+    }
+
     pub fn closed(&self) -> bool {
         self.state == State::Closed
     }
@@ -1,10 +1,13 @@
-use super::enemy::Enemy;
+use serde::{Deserialize, Serialize};
+
+use super::enemy::{Enemy, EnemySnapshot};
 use super::enemy_base::{EnemyBase, EnemyInfo};
 use super::{Accessor, DamageResult};
 
 use crate::app::game::manager::EventType;
 
 use galangua_common::app::consts::*;
+use galangua_common::app::game::challenging_stage::is_challenging_stage;
 use galangua_common::app::game::formation_table::Y_COUNT;
 use galangua_common::app::game::traj::Traj;
 use galangua_common::app::game::traj_command::TrajCommand;
@@ -41,13 +44,13 @@ const VTABLE: [Vtable; 4] = [
     },
 ];
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub(super) enum ZakoAttackType {
     BeeAttack,
     Traj,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub(super) enum ZakoState {
     None,
     Appearance,
@@ -58,7 +61,8 @@ pub(super) enum ZakoState {
     Troop,
 }
 
-pub(super) struct Zako {
+#[derive(Serialize, Deserialize)]
+pub struct Zako {
     pub(super) enemy_type: EnemyType,
     pub(super) info: EnemyInfo,
     pub(super) base: EnemyBase,
@@ -89,7 +93,9 @@ impl Zako {
             ZakoState::None | ZakoState::Troop => {}
             ZakoState::Appearance => {
                 if !self.base.update_trajectory(&mut self.info, accessor) {
-                    if self.info.formation_index.1 >= Y_COUNT as u8 {  // Assault
+                    if is_challenging_stage(accessor.get_stage_no()) {
+                        self.base.disappeared = true;
+                    } else if self.info.formation_index.1 >= Y_COUNT as u8 {  // Assault
                         self.base.set_assault(&mut self.info, accessor);
                         self.set_state(ZakoState::Assault(0));
                     } else {
@@ -237,6 +243,7 @@ impl Enemy for Zako {
         renderer.draw_sprite_rot(sprite, &(&pos + &Vec2I::new(-8, -8)), angle, None);
     }
 
+    fn enemy_type(&self) -> EnemyType { self.enemy_type }
     fn pos(&self) -> &Vec2I { &self.info.pos }
     fn set_pos(&mut self, pos: &Vec2I) { self.info.pos = *pos; }
     fn angle(&self) -> i32 { self.info.angle }
@@ -286,6 +293,10 @@ impl Enemy for Zako {
         self.set_state(ZakoState::Formation);
     }
 
+    fn tractor_beam_pos(&self) -> Option<Vec2I> { None }
+
+    fn snapshot(&self) -> EnemySnapshot<'_> { EnemySnapshot::Zako(self) }
+
     #[cfg(debug_assertions)]
     fn set_table_attack(&mut self, traj_command_vec: Vec<TrajCommand>, flip_x: bool) {
         self.base.set_table_attack(&mut self.info, traj_command_vec, flip_x);
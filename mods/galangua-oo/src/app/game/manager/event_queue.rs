@@ -56,4 +56,6 @@ pub enum EventType {
     EscapeEnded,
     CapturedFighterDestroyed,
     PlaySe(u32, &'static str),
+    PlayLoopSe(u32, &'static str),
+    StopSe(u32, u32),  // (channel, fade ms)
 }
@@ -1,3 +1,7 @@
+use rand_xoshiro::Xoshiro128Plus;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
 use super::event_queue::EventQueue;
 use super::stage::stage_manager::StageManager;
 use super::EventType;
@@ -10,30 +14,40 @@ use crate::app::game::player::{MyShot, Player};
 use crate::app::util::unsafe_util::peep;
 
 use galangua_common::app::consts::*;
+use galangua_common::app::game::challenging_stage::{is_challenging_stage, ChallengingResult};
 use galangua_common::app::game::effect_table::FLASH_ENEMY_FRAME;
 use galangua_common::app::game::stage_indicator::StageIndicator;
 use galangua_common::app::game::star_manager::StarManager;
-use galangua_common::app::game::{CaptureState, FormationIndex};
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
+use galangua_common::app::game::player_state::PlayerState;
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::app::game::{CaptureState, EnemyType, FormationIndex};
+use galangua_common::app::game_config::GameConfig;
 use galangua_common::app::score_holder::ScoreHolder;
 use galangua_common::app::util::collision::Collidable;
+use galangua_common::app::util::looping_se::LoopingSe;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::{RendererTrait, SystemTrait};
 use galangua_common::util::math::ONE;
 use galangua_common::util::pad::Pad;
+use galangua_common::util::rng::create_rng;
 
 const MYSHOT_COUNT: usize = 2;
 const MAX_EFFECT_COUNT: usize = 16;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 enum GameState {
     StartStage,
     Playing,
     PlayerDead,
     WaitReady,
     WaitReady2,
+    SwitchPlayer,
     Capturing,
     Captured,
     Recapturing,
+    ChallengingResult,
     StageClear,
     GameOver,
     Finished,
@@ -48,6 +62,7 @@ pub struct Params<'a> {
     pub score_holder: &'a mut ScoreHolder,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct GameManager {
     state: GameState,
     count: u32,
@@ -56,18 +71,31 @@ pub struct GameManager {
     myshots: [Option<MyShot>; MYSHOT_COUNT],
     stage_manager: StageManager,
     effects: [Option<Effect>; MAX_EFFECT_COUNT],
+    #[serde(skip, default = "EventQueue::new")]
     event_queue: EventQueue,
     stage: u16,
     left_ship: u32,
     capture_state: CaptureState,
     capture_enemy_fi: FormationIndex,
+    challenging_result: ChallengingResult,
+    two_players: bool,
+    player_no: usize,
+    other_player: Option<PlayerState>,
+    resume_state: Option<PlayerState>,
+    rng: Xoshiro128Plus,
+    #[serde(default)]
+    config: GameConfig,
+    #[serde(skip)]
+    looping_se: LoopingSe,
 }
 
 impl GameManager {
-    pub fn new() -> Self {
+    pub fn new(seed: u64, config: GameConfig) -> Self {
         let stage = 0;
         let mut stage_indicator = StageIndicator::default();
         stage_indicator.set_stage(stage + 1);
+        let mut rng = create_rng(seed);
+        let stage_manager = StageManager::new(&mut rng, config.difficulty);
 
         Self {
             state: GameState::StartStage,
@@ -75,17 +103,38 @@ impl GameManager {
             stage_indicator,
             player: Player::new(),
             myshots: Default::default(),
-            stage_manager: StageManager::new(),
+            stage_manager,
             event_queue: EventQueue::new(),
             effects: Default::default(),
 
             stage,
-            left_ship: DEFAULT_LEFT_SHIP,
+            left_ship: config.start_ships,
             capture_state: CaptureState::NoCapture,
             capture_enemy_fi: FormationIndex(0, 0),
+            challenging_result: ChallengingResult::default(),
+            two_players: false,
+            player_no: 0,
+            other_player: None,
+            resume_state: None,
+            rng,
+            config,
+            looping_se: LoopingSe::default(),
         }
     }
 
+    pub fn set_two_players(&mut self) {
+        self.two_players = true;
+        self.other_player = Some(PlayerState {
+            left_ship: self.config.start_ships,
+            rank: DifficultyRank::new(self.config.difficulty),
+            ..PlayerState::default()
+        });
+    }
+
+    pub fn set_stage_definition(&mut self, definition: Arc<StageDefinition>) {
+        self.stage_manager.set_stage_definition(definition);
+    }
+
     #[cfg(debug_assertions)]
     pub fn stage_manager_mut(&mut self) -> &mut StageManager {
         &mut self.stage_manager
@@ -110,6 +159,16 @@ impl GameManager {
         self.state == GameState::Finished
     }
 
+    // Rank of the current player.
+    pub fn rank(&self) -> DifficultyRank {
+        self.stage_manager.rank()
+    }
+
+    // Restarts looping sounds stopped by the pause.
+    pub fn resume_se<S: SystemTrait>(&self, system: &mut S) {
+        self.looping_se.resume(system);
+    }
+
     pub fn update<S: SystemTrait>(&mut self, params: &mut Params, system: &mut S) {
         self.update_common(params, system);
 
@@ -126,12 +185,22 @@ impl GameManager {
                         None
                     };
                     self.stage_manager.start_next_stage(self.stage, captured_fighter);
+                    params.score_holder.record.stage = self.stage;
+                    if let Some(state) = self.resume_state.take() {
+                        self.stage_manager.resume(state.destroyed, state.hit_count);
+                    }
                     self.state = GameState::Playing;
                 }
             }
             GameState::Playing => {
                 if self.stage_manager.all_destroyed() {
-                    self.state = GameState::StageClear;
+                    if self.stage_manager.is_challenging() {
+                        self.challenging_result = ChallengingResult::new(self.stage_manager.hit_count());
+                        self.event_queue.push(EventType::AddScore(self.challenging_result.bonus()));
+                        self.state = GameState::ChallengingResult;
+                    } else {
+                        self.state = GameState::StageClear;
+                    }
                     self.count = 0;
                 }
             }
@@ -150,7 +219,7 @@ impl GameManager {
                 if self.stage_manager.is_no_attacker() {
                     self.count += 1;
                     if self.count >= 60 {
-                        self.next_player();
+                        self.next_player(params);
                     }
                 }
             }
@@ -164,10 +233,23 @@ impl GameManager {
                     self.count = 0;
                 }
             }
+            GameState::SwitchPlayer => {
+                self.count += 1;
+                if self.count >= 120 {
+                    self.state = GameState::StartStage;
+                    self.count = 0;
+                }
+            }
+            GameState::ChallengingResult => {
+                if !self.challenging_result.update() {
+                    self.state = GameState::StageClear;
+                }
+            }
             GameState::StageClear => {
                 self.count += 1;
                 if self.count >= 60 {
                     self.stage = self.stage.saturating_add(1);
+                    self.stage_manager.on_stage_cleared();
                     self.stage_indicator.set_stage(std::cmp::min(self.stage, 255) + 1);
 
                     self.state = GameState::StartStage;
@@ -177,7 +259,11 @@ impl GameManager {
             GameState::GameOver => {
                 self.count += 1;
                 if self.count >= 35 * 60 / 10 {
-                    self.state = GameState::Finished;
+                    if self.other_player.is_some() {
+                        self.switch_player(params, false);
+                    } else {
+                        self.state = GameState::Finished;
+                    }
                 }
             }
             GameState::Finished => {}
@@ -187,9 +273,12 @@ impl GameManager {
         }
     }
 
-    fn next_player(&mut self) {
+    fn next_player(&mut self, params: &mut Params) {
         self.left_ship -= 1;
-        if self.left_ship == 0 {
+        self.stage_manager.on_player_dead();
+        if self.left_ship > 0 && self.other_player.is_some() {
+            self.switch_player(params, true);
+        } else if self.left_ship == 0 {
             self.stage_manager.pause_attack(true);
             self.state = GameState::GameOver;
             self.count = 0;
@@ -201,6 +290,37 @@ impl GameManager {
         }
     }
 
+    // Swaps the state with the waiting player, and restarts the stage of the player.
+    fn switch_player(&mut self, params: &mut Params, keep_current: bool) {
+        let next = self.other_player.take().unwrap();
+        if keep_current {
+            let destroyed = self.stage_manager.calc_destroyed();
+            self.other_player = Some(PlayerState::new(
+                self.stage, self.left_ship, self.capture_state, self.capture_enemy_fi,
+                destroyed, self.stage_manager.hit_count(), self.stage_manager.rank()));
+        }
+        params.score_holder.switch_player();
+        self.player_no = params.score_holder.current_player();
+
+        self.stage = next.stage;
+        self.left_ship = next.left_ship;
+        self.capture_state = next.capture_state;
+        self.capture_enemy_fi = next.capture_enemy_fi;
+        self.stage_manager.set_rank(next.rank);
+        self.resume_state = Some(next);
+
+        self.stage_manager.clear();
+        self.player.restart();
+        self.player.set_shot_enable(true);
+        self.myshots = Default::default();
+        self.effects = Default::default();
+        params.star_manager.set_stop(false);
+
+        self.stage_indicator.set_stage(std::cmp::min(self.stage, 255) + 1);
+        self.state = GameState::SwitchPlayer;
+        self.count = 0;
+    }
+
     fn update_common<S: SystemTrait>(&mut self, params: &mut Params, system: &mut S) {
         self.update_player(params);
         self.update_myshots();
@@ -212,7 +332,7 @@ impl GameManager {
 
         //
 
-        self.check_collision();
+        self.check_collision(params.score_holder);
 
         self.handle_event_queue(params, system);
     }
@@ -266,11 +386,17 @@ impl GameManager {
 
         match self.state {
             GameState::StartStage => {
+                self.draw_player_no(renderer);
                 renderer.set_texture_color_mod("font", 0, 255, 255);
-                renderer.draw_str("font", 10 * 8, 18 * 8, &format!("STAGE {}", self.stage + 1));
+                if is_challenging_stage(self.stage) {
+                    renderer.draw_str("font", (28 - 17) / 2 * 8, 18 * 8, "CHALLENGING STAGE");
+                } else {
+                    renderer.draw_str("font", 10 * 8, 18 * 8, &format!("STAGE {}", self.stage + 1));
+                }
             }
             GameState::WaitReady | GameState::WaitReady2 => {
-                if self.left_ship > 1 || self.state == GameState::WaitReady2 {
+                if (self.left_ship > 1 && self.other_player.is_none()) || self.state == GameState::WaitReady2 {
+                    self.draw_player_no(renderer);
                     renderer.set_texture_color_mod("font", 0, 255, 255);
                     renderer.draw_str("font", (28 - 6) / 2 * 8, 18 * 8, "READY");
                 }
@@ -281,7 +407,16 @@ impl GameManager {
                     renderer.draw_str("font", (28 - 16) / 2 * 8, 19 * 8, "FIGHTER CAPTURED");
                 }
             }
+            GameState::ChallengingResult => {
+                self.challenging_result.draw(renderer);
+            }
+            GameState::SwitchPlayer => {
+                self.draw_player_no(renderer);
+                renderer.set_texture_color_mod("font", 0, 255, 255);
+                renderer.draw_str("font", (28 - 6) / 2 * 8, 18 * 8, "READY");
+            }
             GameState::GameOver => {
+                self.draw_player_no(renderer);
                 renderer.set_texture_color_mod("font", 0, 255, 255);
                 renderer.draw_str("font", (28 - 8) / 2 * 8, 18 * 8, "GAME OVER");
             }
@@ -289,6 +424,33 @@ impl GameManager {
         }
     }
 
+    fn draw_player_no<R: RendererTrait>(&self, renderer: &mut R) {
+        if self.two_players {
+            renderer.set_texture_color_mod("font", 0, 255, 255);
+            renderer.draw_str("font", (28 - 8) / 2 * 8, 15 * 8, &format!("PLAYER {}", self.player_no + 1));
+        }
+    }
+
+    pub fn observe(&self, score: u32) -> Observation {
+        let mut observation = Observation {
+            score,
+            left_ship: self.left_ship,
+            stage: self.stage,
+            capture_state: self.capture_state,
+            ..Default::default()
+        };
+        if !self.player.is_dead() {
+            observation.players.push(*self.player.pos());
+            observation.players.extend(self.player.dual_pos());
+        }
+        for myshot in self.myshots.iter().flatten() {
+            observation.my_shots.extend(myshot.positions().iter().flatten());
+        }
+        self.stage_manager.observe(&mut observation);
+        observation.normalize();
+        observation
+    }
+
     fn do_push_event(&mut self, event: EventType) {
         self.event_queue.push(event);
     }
@@ -299,6 +461,7 @@ impl GameManager {
             match self.event_queue[i] {
                 EventType::MyShot(pos, dual, angle) => {
                     if self.spawn_myshot(&pos, dual, angle) {
+                        params.score_holder.record.shot_count += 1;
                         system.play_se(CH_SHOT, SE_MYSHOT);
                     }
                 }
@@ -313,10 +476,14 @@ impl GameManager {
                     self.spawn_effect(Effect::create_earned_point(point_type, &pos));
                 }
                 EventType::EnemyExplosion(pos, angle, enemy_type) => {
+                    if enemy_type != EnemyType::CapturedFighter {
+                        params.score_holder.record.destroyed_count += 1;
+                    }
                     self.spawn_effect(Effect::create_flash_enemy(&pos, angle, enemy_type));
                     self.spawn_effect(Effect::create_enemy_explosion(&pos, FLASH_ENEMY_FRAME));
                 }
                 EventType::PlayerExplosion(pos) => {
+                    params.score_holder.record.lost_count += 1;
                     self.spawn_effect(Effect::create_player_explosion(&pos));
                     system.play_se(CH_BOMB, SE_BOMB_PLAYER);
                 }
@@ -344,6 +511,7 @@ impl GameManager {
                     self.capture_state = CaptureState::Capturing;
                 }
                 EventType::CapturePlayerCompleted => {
+                    params.score_holder.record.captured_count += 1;
                     params.star_manager.set_capturing(false);
                     self.player.complete_capture();
                     self.capture_state = CaptureState::Captured;
@@ -351,7 +519,7 @@ impl GameManager {
                     self.count = 0;
                 }
                 EventType::CaptureSequenceEnded => {
-                    self.next_player();
+                    self.next_player(params);
                 }
                 EventType::SpawnCapturedFighter(pos, formation_index) => {
                     self.stage_manager.spawn_captured_fighter(&pos, &formation_index);
@@ -371,6 +539,9 @@ impl GameManager {
                     self.player.start_move_home_pos();
                 }
                 EventType::RecaptureEnded(dual) => {
+                    if dual {
+                        params.score_holder.record.rescued_count += 1;
+                    }
                     self.stage_manager.pause_attack(false);
                     self.capture_state = if dual { CaptureState::Dual } else { CaptureState::NoCapture };
                     self.capture_enemy_fi = FormationIndex(0, 0);
@@ -394,6 +565,12 @@ impl GameManager {
                 EventType::PlaySe(channel, asset_path) => {
                     system.play_se(channel, &asset_path);
                 }
+                EventType::PlayLoopSe(channel, asset_path) => {
+                    self.looping_se.play(channel, asset_path, system);
+                }
+                EventType::StopSe(channel, fade_ms) => {
+                    self.looping_se.stop(channel, fade_ms, system);
+                }
             }
             i += 1;
         }
@@ -401,12 +578,7 @@ impl GameManager {
     }
 
     fn add_score<S: SystemTrait>(&mut self, before: u32, add: u32, system: &mut S) {
-        let ext = if before < EXTEND_FIRST_SCORE {
-            EXTEND_FIRST_SCORE
-        } else {
-            (before + EXTEND_AFTER_SCORE - 1) / EXTEND_AFTER_SCORE * EXTEND_AFTER_SCORE
-        };
-        if before + add >= ext {
+        if self.config.extend.is_extended(before, before.saturating_add(add)) {
             self.extend_ship(system);
         }
     }
@@ -434,17 +606,22 @@ impl GameManager {
         self.stage_manager.spawn_shot(pos, &player_pos, speed);
     }
 
-    fn check_collision(&mut self) {
+    fn check_collision(&mut self, score_holder: &mut ScoreHolder) {
         #[cfg(debug_assertions)]
         if self.state == GameState::EditTraj {
             return;
         }
 
-        self.check_collision_myshot_enemy();
+        self.check_collision_myshot_enemy(score_holder);
         self.check_collision_player_enemy();
     }
 
-    fn check_collision_myshot_enemy(&mut self) {
+    fn check_collision_myshot_enemy(&mut self, score_holder: &mut ScoreHolder) {
+        // Shot fired at the moment of the capture must not break the capture sequence.
+        if self.player.is_captured() {
+            return;
+        }
+
         let power = 1;
         let accessor = unsafe { peep(self) };
         for myshot_opt in self.myshots.iter_mut().filter(|x| x.is_some()) {
@@ -461,6 +638,7 @@ impl GameManager {
             }
             if hit {
                 *myshot_opt = None;
+                score_holder.record.hit_count += 1;
             }
         }
     }
@@ -568,6 +746,10 @@ impl AccessorForEnemy for GameManager {
         self.stage
     }
 
+    fn rng(&mut self) -> &mut Xoshiro128Plus {
+        &mut self.rng
+    }
+
     fn push_event(&mut self, event: EventType) { self.do_push_event(event); }
 }
 
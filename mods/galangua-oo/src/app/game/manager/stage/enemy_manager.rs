@@ -1,6 +1,7 @@
 use array_macro::*;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_xoshiro::Xoshiro128Plus;
+use serde::{Deserialize, Serialize};
 
 use crate::app::game::enemy::ene_shot::EneShot;
 use crate::app::game::enemy::enemy::{create_enemy, Enemy};
@@ -10,6 +11,7 @@ use crate::app::game::manager::EventType;
 use galangua_common::app::consts::*;
 use galangua_common::app::game::effect_table::to_earned_point_type;
 use galangua_common::app::game::formation_table::X_COUNT;
+use galangua_common::app::game::observation::{EnemyObservation, Observation};
 use galangua_common::app::game::{EnemyType, FormationIndex};
 use galangua_common::app::util::collision::{CollBox, Collidable};
 use galangua_common::framework::types::Vec2I;
@@ -18,28 +20,35 @@ use galangua_common::util::math::{atan2_lut, calc_velocity, clamp, ANGLE, ONE};
 
 const MAX_ENEMY_COUNT: usize = 70;
 
+#[derive(Serialize, Deserialize)]
 pub struct EnemyManager {
+    #[serde(with = "enemies_snapshot")]
     enemies: [Option<Box<dyn Enemy>>; MAX_ENEMY_COUNT],
     pub(super) alive_enemy_count: u32,
+    pub(super) hit_count: u32,
     shots: [Option<EneShot>; MAX_ENE_SHOT_COUNT],
     shot_paused_count: u32,
     frame_count: u32,
+    rng: Xoshiro128Plus,
 }
 
 impl EnemyManager {
-    pub fn new() -> Self {
+    pub fn new(rng: Xoshiro128Plus) -> Self {
         Self {
             enemies: array![None; MAX_ENEMY_COUNT],
             alive_enemy_count: 0,
+            hit_count: 0,
             shots: Default::default(),
             shot_paused_count: 0,
             frame_count: 0,
+            rng,
         }
     }
 
     pub fn start_next_stage(&mut self) {
         self.enemies = array![None; MAX_ENEMY_COUNT];
         self.alive_enemy_count = 0;
+        self.hit_count = 0;
         self.shots = Default::default();
         self.shot_paused_count = 0;
         self.frame_count = 0;
@@ -69,6 +78,20 @@ impl EnemyManager {
         }
     }
 
+    pub fn observe(&self, observation: &mut Observation) {
+        observation.enemies.extend(self.enemies.iter().flatten().map(|enemy| EnemyObservation {
+            enemy_type: enemy.enemy_type(),
+            formation_index: *enemy.formation_index(),
+            pos: *enemy.pos(),
+            angle: enemy.angle(),
+            is_formation: enemy.is_formation(),
+            formation_pos: None,
+        }));
+        observation.ene_shots.extend(self.shots.iter().flatten().map(|shot| *shot.pos()));
+        observation.tractor_beam = self.enemies.iter().flatten()
+            .find_map(|enemy| enemy.tractor_beam_pos());
+    }
+
     pub fn check_collision<A: Accessor>(
         &mut self, target: &CollBox, power: u32, accessor: &mut A,
     ) -> bool {
@@ -80,6 +103,7 @@ impl EnemyManager {
                     let result = enemy.set_damage(power, accessor);
                     if result.point > 0 {
                         accessor.push_event(EventType::AddScore(result.point));
+                        self.hit_count += 1;
 
                         if let Some(point_type) = to_earned_point_type(result.point) {
                             accessor.push_event(EventType::EarnPointEffect(point_type, pos));
@@ -139,16 +163,15 @@ impl EnemyManager {
         true
     }
 
-    pub fn spawn_shot(&mut self, pos: &Vec2I, target_pos: &[Option<Vec2I>], speed: i32) {
+    pub fn spawn_shot(&mut self, pos: &Vec2I, target_pos: &[Option<Vec2I>], speed: i32, max_count: usize) {
         if self.shot_paused_count > 0 {
             return;
         }
 
-        if let Some(index) = self.shots.iter().position(|x| x.is_none()) {
-            let mut rng = Xoshiro128Plus::from_seed(rand::thread_rng().gen());
+        if let Some(index) = self.shots[..max_count].iter().position(|x| x.is_none()) {
             let count = target_pos.iter().flatten().count();
             let target: &Vec2I = target_pos.iter()
-                .flatten().nth(rng.gen_range(0, count)).unwrap();
+                .flatten().nth(self.rng.gen_range(0, count)).unwrap();
             let d = target - pos;
 
             let limit = ANGLE * ONE * 30 / 360;
@@ -212,3 +235,26 @@ impl EnemyManager {
 fn calc_array_index(fi: &FormationIndex) -> usize {
     (fi.0 as usize) + (fi.1 as usize) * X_COUNT
 }
+
+// `Box<dyn Enemy>` is saved through `EnemySnapshot`.
+mod enemies_snapshot {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::convert::TryInto;
+
+    use super::MAX_ENEMY_COUNT;
+    use crate::app::game::enemy::enemy::{Enemy, SavedEnemy};
+
+    pub fn serialize<S: Serializer>(enemies: &[Option<Box<dyn Enemy>>; MAX_ENEMY_COUNT], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(enemies.iter().map(|enemy| enemy.as_ref().map(|enemy| enemy.snapshot())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Option<Box<dyn Enemy>>; MAX_ENEMY_COUNT], D::Error> {
+        let enemies = Vec::<Option<SavedEnemy>>::deserialize(deserializer)?;
+        let len = enemies.len();
+        let enemies: Vec<Option<Box<dyn Enemy>>> = enemies.into_iter()
+            .map(|enemy| enemy.map(|enemy| enemy.into_enemy()))
+            .collect();
+        enemies.try_into().map_err(|_| serde::de::Error::invalid_length(len, &"enemy slots"))
+    }
+}
+
@@ -1,3 +1,7 @@
+use rand_xoshiro::Xoshiro128Plus;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
 use super::enemy_manager::EnemyManager;
 
 use crate::app::game::enemy::enemy::{create_appearance_enemy, Enemy};
@@ -5,16 +9,20 @@ use crate::app::game::enemy::Accessor;
 use crate::app::game::manager::EventType;
 use crate::app::util::unsafe_util::peep;
 
-use galangua_common::app::consts::*;
 use galangua_common::app::game::appearance_manager::Accessor as AccessorForAppearance;
 use galangua_common::app::game::appearance_manager::AppearanceManager;
 use galangua_common::app::game::attack_manager::Accessor as AttackManagerAccessor;
 use galangua_common::app::game::attack_manager::AttackManager;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
 use galangua_common::app::game::formation::Formation;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
 use galangua_common::app::game::{CaptureState, FormationIndex};
+use galangua_common::app::game_config::Difficulty;
 use galangua_common::app::util::collision::CollBox;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::RendererTrait;
+use galangua_common::util::rng::derive_rng;
 
 #[cfg(debug_assertions)]
 use galangua_common::app::game::appearance_table::{ENEMY_TYPE_TABLE, ORDER};
@@ -22,7 +30,7 @@ use galangua_common::app::game::appearance_table::{ENEMY_TYPE_TABLE, ORDER};
 #[cfg(debug_assertions)]
 use crate::app::game::enemy::enemy::create_enemy;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum StageState {
     APPEARANCE,
     NORMAL,
@@ -30,23 +38,37 @@ enum StageState {
     CLEARED,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct StageManager {
     enemy_manager: EnemyManager,
     formation: Formation,
     appearance_manager: AppearanceManager,
     attack_manager: AttackManager,
     stage_state: StageState,
+    #[serde(default)]
+    rank: DifficultyRank,
 }
 
 impl StageManager {
-    pub fn new() -> Self {
-        Self {
-            enemy_manager: EnemyManager::new(),
+    pub fn new(rng: &mut Xoshiro128Plus, difficulty: Difficulty) -> Self {
+        let appearance_manager = AppearanceManager::new(derive_rng(rng));
+        let attack_manager = AttackManager::new(derive_rng(rng));
+        let enemy_manager = EnemyManager::new(derive_rng(rng));
+
+        let mut stage_manager = Self {
+            enemy_manager,
             formation: Formation::default(),
-            appearance_manager: AppearanceManager::default(),
-            attack_manager: AttackManager::default(),
+            appearance_manager,
+            attack_manager,
             stage_state: StageState::APPEARANCE,
-        }
+            rank: DifficultyRank::new(difficulty),
+        };
+        stage_manager.set_rank(stage_manager.rank);
+        stage_manager
+    }
+
+    pub fn set_stage_definition(&mut self, definition: Arc<StageDefinition>) {
+        self.appearance_manager.set_stage_definition(definition);
     }
 
     pub fn start_next_stage(&mut self, stage: u16, captured_fighter: Option<FormationIndex>) {
@@ -57,6 +79,47 @@ impl StageManager {
         self.stage_state = StageState::APPEARANCE;
     }
 
+    pub fn rank(&self) -> DifficultyRank {
+        self.rank
+    }
+
+    // Switches to the rank of the other player.
+    pub fn set_rank(&mut self, rank: DifficultyRank) {
+        self.rank = rank;
+        self.appearance_manager.set_rank(rank);
+        self.attack_manager.set_rank(rank);
+    }
+
+    pub fn on_stage_cleared(&mut self) {
+        let mut rank = self.rank;
+        rank.on_stage_cleared();
+        self.set_rank(rank);
+    }
+
+    pub fn on_player_dead(&mut self) {
+        let mut rank = self.rank;
+        rank.on_player_dead();
+        self.set_rank(rank);
+    }
+
+    // Removes all enemies until the next stage starts, to switch the player.
+    pub fn clear(&mut self) {
+        self.enemy_manager.start_next_stage();
+        self.appearance_manager.done = true;
+        self.attack_manager.restart(0);
+        self.stage_state = StageState::APPEARANCE;
+    }
+
+    // Called after `start_next_stage`, to continue the stage of the player.
+    pub fn resume(&mut self, destroyed: Vec<FormationIndex>, hit_count: u32) {
+        self.appearance_manager.set_skip(destroyed);
+        self.enemy_manager.hit_count = hit_count;
+    }
+
+    pub fn calc_destroyed(&self) -> Vec<FormationIndex> {
+        self.appearance_manager.calc_destroyed(|fi| self.enemy_manager.get_enemy_at(fi).is_some())
+    }
+
     pub fn all_destroyed(&self) -> bool {
         self.stage_state == StageState::CLEARED &&
             self.enemy_manager.all_destroyed()
@@ -74,6 +137,11 @@ impl StageManager {
         self.enemy_manager.draw(renderer);
     }
 
+    pub fn observe(&self, observation: &mut Observation) {
+        self.enemy_manager.observe(observation);
+        observation.set_formation_pos(&self.formation);
+    }
+
     pub fn check_collision<A: Accessor>(
         &mut self, target: &CollBox, power: u32, accessor: &mut A,
     ) -> bool {
@@ -98,7 +166,8 @@ impl StageManager {
         if !prev_done && self.appearance_manager.done {
             self.stage_state = StageState::NORMAL;
             self.formation.done_appearance();
-            self.attack_manager.set_enable(true);
+            // Enemies in challenging stage just fly through.
+            self.attack_manager.set_enable(!self.appearance_manager.is_challenging());
         }
     }
 
@@ -135,9 +204,10 @@ impl StageManager {
             return;
         }
 
+        let rush_threshold = self.rank.rush_threshold();
         let new_state = match self.enemy_manager.alive_enemy_count {
             n if n == 0               => StageState::CLEARED,
-            n if n <= RUSH_THRESHOLD  => StageState::RUSH,
+            n if n <= rush_threshold  => StageState::RUSH,
             _                         => self.stage_state,
         };
         if new_state != self.stage_state {
@@ -150,7 +220,8 @@ impl StageManager {
     }
 
     pub fn spawn_shot(&mut self, pos: &Vec2I, target_pos: &[Option<Vec2I>], speed: i32) {
-        self.enemy_manager.spawn_shot(pos, target_pos, speed);
+        let speed = self.rank.ene_shot_speed(speed);
+        self.enemy_manager.spawn_shot(pos, target_pos, speed, self.rank.max_ene_shot_count());
     }
 
     pub fn pause_attack(&mut self, value: bool) {
@@ -174,6 +245,14 @@ impl StageManager {
         self.formation.pos(formation_index)
     }
 
+    pub fn is_challenging(&self) -> bool {
+        self.appearance_manager.is_challenging()
+    }
+
+    pub fn hit_count(&self) -> u32 {
+        self.enemy_manager.hit_count
+    }
+
     pub fn is_rush(&self) -> bool {
         self.stage_state == StageState::RUSH
     }
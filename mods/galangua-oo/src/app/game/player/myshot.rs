@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use galangua_common::app::consts::*;
 use galangua_common::app::util::collision::{CollBox, Collidable};
 use galangua_common::framework::types::Vec2I;
@@ -6,6 +8,7 @@ use galangua_common::util::math::{calc_velocity, quantize_angle, round_vec, ONE}
 
 const SPRITE_NAME: &str = "myshot";
 
+#[derive(Serialize, Deserialize)]
 pub struct MyShot {
     pos: Vec2I,
     dual: bool,
@@ -45,6 +48,11 @@ impl MyShot {
         }
     }
 
+    pub fn positions(&self) -> [Option<Vec2I>; 2] {
+        let dual_pos = if self.dual { Some(&self.pos + &Vec2I::new(16 * ONE, 0)) } else { None };
+        [Some(self.pos), dual_pos]
+    }
+
     pub fn dual_collbox(&self) -> Option<CollBox> {
         if self.dual {
             Some(CollBox {
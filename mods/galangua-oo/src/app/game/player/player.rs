@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use galangua_common::app::consts::*;
 use galangua_common::app::util::collision::{CollBox, Collidable};
 use galangua_common::framework::types::{Vec2I, ZERO_VEC};
@@ -13,7 +15,7 @@ use super::Accessor;
 const SPRITE_NAME: &str = "rustacean";
 const SPRITE_NAME_CAPTURED: &str = "rustacean_captured";
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 enum State {
     Normal,
     Dead,
@@ -23,6 +25,7 @@ enum State {
     MoveHomePos,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Player {
     pos: Vec2I,
     state: State,
@@ -112,14 +115,15 @@ impl Player {
     }
 
     pub fn update_capture<A: Accessor>(&mut self, pad: &Pad, accessor: &mut A) {
+        // Fire before moving, as the tractor beam moves the player after the shot in ECS version.
+        self.fire_bullet(pad, accessor);
+
         const D: i32 = 1 * ONE;
         let d = &self.capture_pos - &self.pos;
         self.pos.x += clamp(d.x, -D, D);
         self.pos.y += clamp(d.y, -D, D);
         self.angle += ANGLE * ONE / ANGLE_DIV;
 
-        self.fire_bullet(pad, accessor);
-
         if d.x == 0 && d.y == 0 {
             self.state = State::Captured;
             self.angle = 0;
@@ -203,6 +207,10 @@ impl Player {
         self.angle = 0;
     }
 
+    pub fn is_dead(&self) -> bool {
+        self.state == State::Dead
+    }
+
     pub fn is_captured(&self) -> bool {
         self.state == State::Captured
     }
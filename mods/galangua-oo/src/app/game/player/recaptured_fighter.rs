@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use galangua_common::app::consts::*;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::RendererTrait;
@@ -9,7 +11,7 @@ use super::Accessor;
 
 const SPRITE_NAME: &str = "rustacean";
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum State {
     Rotate,
     SlideHorz,
@@ -17,6 +19,7 @@ enum State {
     Done,
 }
 
+#[derive(Serialize, Deserialize)]
 pub(super) struct RecapturedFighter {
     pos: Vec2I,
     state: State,
@@ -1,7 +1,9 @@
+mod bot_game;
 mod galangua_app;
 mod game;
 pub mod util;
 
+pub use self::bot_game::BotGame;
 pub use self::galangua_app::GalanguaApp;
 
 #[cfg(debug_assertions)]
@@ -0,0 +1,149 @@
+use rand::Rng;
+
+use galangua_common::app::bot::BotEnv;
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game_config::GameConfig;
+use galangua_common::app::scene::Scene;
+use galangua_common::app::score_holder::{PlayRecord, ScoreHolder};
+use galangua_common::framework::headless::{NullSystem, NullTimer};
+use galangua_common::framework::input_log::{InputEvent, InputLog};
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::{AppTrait, VKey};
+use galangua_common::util::rng::create_rng;
+use galangua_ecs::app::GalanguaEcsApp;
+use galangua_oo::app::GalanguaApp;
+
+pub type OoApp = GalanguaApp<NullTimer, NullSystem>;
+pub type EcsApp = GalanguaEcsApp<NullTimer, NullSystem>;
+pub type OoAudioApp = GalanguaApp<NullTimer, NullSystem<RecordingAudio>>;
+pub type EcsAudioApp = GalanguaEcsApp<NullTimer, NullSystem<RecordingAudio>>;
+pub type OoBotGame = galangua_oo::app::BotGame<NullSystem<RecordingAudio>>;
+pub type EcsBotGame = galangua_ecs::app::BotGame<NullSystem<RecordingAudio>>;
+
+// Random, but reproducible play: start the game, then wander and shoot.
+pub fn create_input_log(seed: u64, frames: u32) -> InputLog {
+    let mut rng = create_rng(seed);
+    let mut log = InputLog::new(seed);
+    log.events.push((1, InputEvent::Key(VKey::Space, true)));
+    log.events.push((2, InputEvent::Key(VKey::Space, false)));
+    let mut dir: Option<VKey> = None;
+    for frame in (10..frames).step_by(4) {
+        if rng.gen_range(0, 8) == 0 {
+            if let Some(vkey) = dir.take() {
+                log.events.push((frame, InputEvent::Key(vkey, false)));
+            }
+            dir = match rng.gen_range(0, 3) {
+                0 => Some(VKey::Left),
+                1 => Some(VKey::Right),
+                _ => None,
+            };
+            if let Some(vkey) = dir {
+                log.events.push((frame, InputEvent::Key(vkey, true)));
+            }
+        }
+        let shot = rng.gen_range(0, 2) == 0;
+        log.events.push((frame, InputEvent::Key(VKey::Space, shot)));
+    }
+    log
+}
+
+// Common interface of both versions for the tests.
+pub trait SaveState: AppTrait<NullRenderer> + Sized {
+    fn with_system(seed: u64, system: NullSystem) -> Self;
+    fn save(&self) -> String;
+    fn load(&mut self, text: &str);
+    fn observation(&self) -> Option<Observation>;
+    fn play_record(&self) -> Option<PlayRecord>;
+
+    fn create(seed: u64) -> Self {
+        Self::with_system(seed, NullSystem::default())
+    }
+}
+
+impl SaveState for OoApp {
+    fn with_system(seed: u64, system: NullSystem) -> Self { GalanguaApp::new(NullTimer, system, seed) }
+    fn save(&self) -> String { self.save_snapshot().unwrap() }
+    fn load(&mut self, text: &str) { self.load_snapshot(text).unwrap() }
+    fn observation(&self) -> Option<Observation> { self.observe() }
+    fn play_record(&self) -> Option<PlayRecord> { self.score_holder().map(|score_holder| score_holder.record) }
+}
+
+impl SaveState for EcsApp {
+    fn with_system(seed: u64, system: NullSystem) -> Self { GalanguaEcsApp::new(NullTimer, system, seed) }
+    fn save(&self) -> String { self.save_snapshot().unwrap() }
+    fn load(&mut self, text: &str) { self.load_snapshot(text).unwrap() }
+    fn observation(&self) -> Option<Observation> { self.observe() }
+    fn play_record(&self) -> Option<PlayRecord> { self.score_holder().map(|score_holder| score_holder.record) }
+}
+
+// Common interface of both versions for the tests going through the screens, with sounds recorded.
+pub trait SceneApp: AppTrait<NullRenderer> + Sized {
+    fn with_audio(seed: u64, audio: RecordingAudio) -> Self;
+    fn scene(&self) -> Scene;
+    fn is_paused(&self) -> bool;
+    fn observation(&self) -> Option<Observation>;
+    fn save(&self) -> Result<String, String>;
+    fn load(&mut self, text: &str) -> Result<(), String>;
+    fn quick_save(&mut self) -> Result<(), String>;
+    fn quick_load(&mut self) -> Result<(), String>;
+    fn quick_save_slot(&self) -> Option<&str>;
+    fn set_quick_save_slot(&mut self, snapshot: Option<String>);
+    fn snapshot_error(&self) -> Option<&str>;
+}
+
+impl SceneApp for OoAudioApp {
+    fn with_audio(seed: u64, audio: RecordingAudio) -> Self { GalanguaApp::new(NullTimer, NullSystem::with_audio(audio), seed) }
+    fn scene(&self) -> Scene { self.scene() }
+    fn is_paused(&self) -> bool { self.is_paused() }
+    fn observation(&self) -> Option<Observation> { self.observe() }
+    fn save(&self) -> Result<String, String> { self.save_snapshot() }
+    fn load(&mut self, text: &str) -> Result<(), String> { self.load_snapshot(text) }
+    fn quick_save(&mut self) -> Result<(), String> { self.quick_save() }
+    fn quick_load(&mut self) -> Result<(), String> { self.quick_load() }
+    fn quick_save_slot(&self) -> Option<&str> { self.quick_save_slot() }
+    fn set_quick_save_slot(&mut self, snapshot: Option<String>) { self.set_quick_save_slot(snapshot) }
+    fn snapshot_error(&self) -> Option<&str> { self.snapshot_error() }
+}
+
+impl SceneApp for EcsAudioApp {
+    fn with_audio(seed: u64, audio: RecordingAudio) -> Self { GalanguaEcsApp::new(NullTimer, NullSystem::with_audio(audio), seed) }
+    fn scene(&self) -> Scene { self.scene() }
+    fn is_paused(&self) -> bool { self.is_paused() }
+    fn observation(&self) -> Option<Observation> { self.observe() }
+    fn save(&self) -> Result<String, String> { self.save_snapshot() }
+    fn load(&mut self, text: &str) -> Result<(), String> { self.load_snapshot(text) }
+    fn quick_save(&mut self) -> Result<(), String> { self.quick_save() }
+    fn quick_load(&mut self) -> Result<(), String> { self.quick_load() }
+    fn quick_save_slot(&self) -> Option<&str> { self.quick_save_slot() }
+    fn set_quick_save_slot(&mut self, snapshot: Option<String>) { self.set_quick_save_slot(snapshot) }
+    fn snapshot_error(&self) -> Option<&str> { self.snapshot_error() }
+}
+
+// Common interface of both versions of the game played by a bot.
+pub trait BotGame: BotEnv + Sized {
+    fn with_config(seed: u64, config: GameConfig, two_players: bool, audio: RecordingAudio) -> Self;
+    fn score_holder(&self) -> ScoreHolder;
+    fn rank(&self) -> DifficultyRank;
+
+    fn create(seed: u64, audio: RecordingAudio) -> Self {
+        Self::with_config(seed, GameConfig::default(), false, audio)
+    }
+}
+
+impl BotGame for OoBotGame {
+    fn with_config(seed: u64, config: GameConfig, two_players: bool, audio: RecordingAudio) -> Self {
+        Self::new(seed, config, two_players, NullSystem::with_audio(audio))
+    }
+    fn score_holder(&self) -> ScoreHolder { self.score_holder() }
+    fn rank(&self) -> DifficultyRank { self.rank() }
+}
+
+impl BotGame for EcsBotGame {
+    fn with_config(seed: u64, config: GameConfig, two_players: bool, audio: RecordingAudio) -> Self {
+        Self::new(seed, config, two_players, NullSystem::with_audio(audio))
+    }
+    fn score_holder(&self) -> ScoreHolder { self.score_holder() }
+    fn rank(&self) -> DifficultyRank { self.rank() }
+}
@@ -0,0 +1,62 @@
+use galangua_common::app::bot::{run_bot, Bot, HeuristicBot};
+use galangua_common::app::consts::*;
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_tests::{BotGame, EcsBotGame, OoBotGame};
+
+fn check_heuristic_bot<G: BotGame>() {
+    let mut game = G::create(1, RecordingAudio::default());
+    let mut bot = HeuristicBot::default();
+    let run = run_bot(&mut game, &mut bot, 60 * 60);
+    assert!(!run.stalled);
+    assert!(run.observation.score > 0);
+    assert_eq!(run.observation.score, game.score_holder().score);
+    assert!(game.score_holder().record.destroyed_count > 0);
+}
+
+#[test]
+fn test_heuristic_bot_oo() {
+    check_heuristic_bot::<OoBotGame>();
+}
+
+#[test]
+fn test_heuristic_bot_ecs() {
+    check_heuristic_bot::<EcsBotGame>();
+}
+
+// Extend sound is played just once when the score gets over the first extend score.
+fn check_extend_sound<G: BotGame>() {
+    let audio = RecordingAudio::default();
+    // The bot gets over the first extend score with this seed.
+    let mut game = G::create(7, audio.clone());
+    let mut bot = HeuristicBot::default();
+    let mut observation = game.observe();
+    let mut extend_frame = None;
+    for frame in 0..30 * 60 * 60 {
+        let before = observation.score;
+        let running = game.step(bot.decide(&observation));
+        observation = game.observe();
+        if before < EXTEND_FIRST_SCORE && observation.score >= EXTEND_FIRST_SCORE {
+            extend_frame = Some(frame);
+        }
+        audio.next_frame();
+        if !running || extend_frame.is_some_and(|extend_frame| frame >= extend_frame + 60) {
+            break;
+        }
+    }
+
+    let extends: Vec<_> = audio.played().into_iter()
+        .filter(|(_, _, filename)| filename == SE_EXTEND_SHIP)
+        .collect();
+    assert!(extend_frame.is_some());
+    assert_eq!(vec![(extend_frame.unwrap(), CH_JINGLE, String::from(SE_EXTEND_SHIP))], extends);
+}
+
+#[test]
+fn test_extend_sound_oo() {
+    check_extend_sound::<OoBotGame>();
+}
+
+#[test]
+fn test_extend_sound_ecs() {
+    check_extend_sound::<EcsBotGame>();
+}
@@ -0,0 +1,97 @@
+use galangua_common::app::attract_mode::{DEMO_FRAMES, DEMO_SEED};
+use galangua_common::app::bot::{Bot, BotEnv, HeuristicBot};
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::CaptureState;
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_common::util::pad::PadBit;
+use galangua_tests::{BotGame, EcsBotGame, OoBotGame};
+
+// Walks into the tractor beam and fires while being pulled up, to be captured with shots in flight.
+struct CaptureBot {
+    bot: HeuristicBot,
+    frame: u32,
+}
+
+impl Default for CaptureBot {
+    fn default() -> Self {
+        Self { bot: HeuristicBot::for_demo(), frame: 0 }
+    }
+}
+
+impl Bot for CaptureBot {
+    fn decide(&mut self, observation: &Observation) -> PadBit {
+        self.frame += 1;
+        let bits = self.bot.decide(observation);
+        if observation.capture_state == CaptureState::Capturing && self.frame % 2 == 0 { bits | PadBit::A } else { bits }
+    }
+}
+
+// Returns capture states in the order they appear, until the next player comes out after the capture.
+fn run_capture<E: BotEnv>(env: &mut E) -> Vec<CaptureState> {
+    let mut bot = CaptureBot::default();
+    let mut capture_states = Vec::new();
+    let mut observation = env.observe();
+    for _ in 0..10 * 60 * 60 {
+        if !env.step(bot.decide(&observation)) {
+            break;
+        }
+        observation = env.observe();
+        if capture_states.last() != Some(&observation.capture_state) {
+            capture_states.push(observation.capture_state);
+        }
+        if observation.capture_state == CaptureState::Captured && !observation.players.is_empty() {
+            break;
+        }
+    }
+    capture_states
+}
+
+fn check_capture<G: BotGame>() {
+    // Shot fired at the moment of the capture hits the owl with seed 5.
+    for seed in 4..8 {
+        let capture_states = run_capture(&mut G::create(seed, RecordingAudio::default()));
+        let capturing = capture_states.iter().position(|state| *state == CaptureState::Capturing);
+        let captured = capture_states.iter().position(|state| *state == CaptureState::Captured);
+        assert!(capturing.is_some() && captured > capturing, "seed={}: {:?}", seed, capture_states);
+    }
+}
+
+#[test]
+fn test_shot_at_capture_oo() {
+    check_capture::<OoBotGame>();
+}
+
+#[test]
+fn test_shot_at_capture_ecs() {
+    check_capture::<EcsBotGame>();
+}
+
+// Demo play is supposed to show the capture and the rescue, to the dual fighter.
+fn check_demo_capture<G: BotGame>() {
+    let mut env = G::create(DEMO_SEED, RecordingAudio::default());
+    let mut bot = HeuristicBot::for_demo();
+    let mut capture_states = Vec::new();
+    let mut observation = env.observe();
+    for _ in 0..DEMO_FRAMES {
+        if !env.step(bot.decide(&observation)) {
+            break;
+        }
+        observation = env.observe();
+        if capture_states.last() != Some(&observation.capture_state) {
+            capture_states.push(observation.capture_state);
+        }
+    }
+    let captured = capture_states.iter().position(|state| *state == CaptureState::Captured);
+    let dual = capture_states.iter().position(|state| *state == CaptureState::Dual);
+    assert!(captured.is_some() && dual > captured, "{:?}", capture_states);
+}
+
+#[test]
+fn test_demo_capture_oo() {
+    check_demo_capture::<OoBotGame>();
+}
+
+#[test]
+fn test_demo_capture_ecs() {
+    check_demo_capture::<EcsBotGame>();
+}
@@ -0,0 +1,51 @@
+use galangua_common::app::bot::{Bot, HeuristicBot};
+use galangua_common::app::game::challenging_stage::is_challenging_stage;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_tests::{BotGame, EcsBotGame, OoBotGame};
+
+// Returns observations of the first challenging stage, the bot reaches it with seed 0.
+fn run_challenging_stage<G: BotGame>() -> Vec<Observation> {
+    let mut game = G::create(0, RecordingAudio::default());
+    let mut bot = HeuristicBot::default();
+    let mut observation = game.observe();
+    let mut observations = Vec::new();
+    for _ in 0..10 * 60 * 60 {
+        assert!(game.step(bot.decide(&observation)), "game over at stage {}", observation.stage);
+        observation = game.observe();
+        if is_challenging_stage(observation.stage) {
+            observations.push(observation.clone());
+        } else if !observations.is_empty() {
+            break;
+        }
+    }
+    observations
+}
+
+fn check_challenging_stage<G: BotGame>() {
+    let observations = run_challenging_stage::<G>();
+    assert!(!observations.is_empty());
+
+    // Enemies fly through without joining the formation nor shooting, and the next stage comes after them.
+    let definition = StageDefinition::default();
+    let appeared = observations.iter().position(|observation| !observation.enemies.is_empty()).unwrap();
+    for observation in observations[appeared..].iter() {
+        assert!(observation.ene_shots.is_empty(), "{:?}", observation.ene_shots);
+        for enemy in observation.enemies.iter() {
+            assert!(!enemy.is_formation);
+            assert!(definition.challenging_enemy_types.iter().any(|pair| pair.contains(&enemy.enemy_type)), "{:?}", enemy);
+        }
+    }
+    assert!(observations.last().unwrap().enemies.is_empty());
+}
+
+#[test]
+fn test_challenging_stage_oo() {
+    check_challenging_stage::<OoBotGame>();
+}
+
+#[test]
+fn test_challenging_stage_ecs() {
+    check_challenging_stage::<EcsBotGame>();
+}
@@ -0,0 +1,75 @@
+use galangua_common::app::game::difficulty_rank::DifficultyRank;
+use galangua_common::app::game_config::{Difficulty, GameConfig};
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_common::util::pad::PadBit;
+use galangua_tests::{BotGame, EcsBotGame, OoBotGame};
+
+fn config(difficulty: Difficulty) -> GameConfig {
+    GameConfig { difficulty, ..GameConfig::default() }
+}
+
+// Counts enemy shots until the game is over, while the player stands still.
+fn count_ene_shots<G: BotGame>(difficulty: Difficulty) -> usize {
+    let mut game = G::with_config(0, config(difficulty), false, RecordingAudio::default());
+    let mut observation = game.observe();
+    let mut count = 0;
+    for _ in 0..10 * 60 * 60 {
+        let before = observation.ene_shots.len();
+        if !game.step(PadBit::empty()) {
+            break;
+        }
+        observation = game.observe();
+        count += observation.ene_shots.len().saturating_sub(before);
+    }
+    count
+}
+
+fn check_shot_rate<G: BotGame>() {
+    let counts: Vec<usize> = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard].iter()
+        .map(|difficulty| count_ene_shots::<G>(*difficulty))
+        .collect();
+    assert!(counts[0] < counts[1] && counts[1] < counts[2], "{:?}", counts);
+}
+
+#[test]
+fn test_shot_rate_oo() {
+    check_shot_rate::<OoBotGame>();
+}
+
+#[test]
+fn test_shot_rate_ecs() {
+    check_shot_rate::<EcsBotGame>();
+}
+
+// Both players stand still until they are shot down, and returns the rank at each switch.
+fn run_switches<G: BotGame>(switch_count: usize) -> Vec<(usize, u32)> {
+    let mut game = G::with_config(0, config(Difficulty::Adaptive), true, RecordingAudio::default());
+    let mut player = game.score_holder().current_player();
+    let mut ranks = Vec::new();
+    while ranks.len() < switch_count && game.step(PadBit::empty()) {
+        let current = game.score_holder().current_player();
+        if current != player {
+            player = current;
+            ranks.push((player, game.rank().rank()));
+        }
+    }
+    ranks
+}
+
+fn check_rank_per_player<G: BotGame>() {
+    // Lost ship lowers the rank of the player only.
+    let initial = DifficultyRank::new(Difficulty::Adaptive);
+    let mut lowered = initial;
+    lowered.on_player_dead();
+    assert_eq!(vec![(1, initial.rank()), (0, lowered.rank())], run_switches::<G>(2));
+}
+
+#[test]
+fn test_rank_per_player_oo() {
+    check_rank_per_player::<OoBotGame>();
+}
+
+#[test]
+fn test_rank_per_player_ecs() {
+    check_rank_per_player::<EcsBotGame>();
+}
@@ -0,0 +1,43 @@
+use galangua_common::app::consts::REPLAYED_KEYS;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game_config::{Difficulty, GameConfig};
+use galangua_common::framework::headless::NullSystem;
+use galangua_common::framework::input_log::{InputLog, InputReplayer};
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::AppTrait;
+use galangua_tests::{create_input_log, EcsApp, OoApp, SaveState};
+
+fn run_log<A: SaveState>(system: NullSystem, log: InputLog, frames: u32) -> Vec<Option<Observation>> {
+    let seed = log.seed;
+    let mut app = InputReplayer::new(A::with_system(seed, system), log);
+    (0..frames).map(|_| {
+        AppTrait::<NullRenderer>::update(&mut app);
+        app.app().observation()
+    }).collect()
+}
+
+// Settings saved at the recording are applied to the replay, not the ones on the replaying machine.
+fn check_replay_settings<A: SaveState>() {
+    let frames = 30 * 60;
+    let mut system = NullSystem::default();
+    GameConfig { start_ships: 5, difficulty: Difficulty::Hard, ..GameConfig::default() }.save(&mut system).unwrap();
+    let mut log = create_input_log(0, frames);
+    log.take_saved_values(&system, &REPLAYED_KEYS);
+    let original = run_log::<A>(system, log.clone(), frames);
+
+    let log = InputLog::from_json_str(&log.to_json_string()).unwrap();
+    let mut replay_system = NullSystem::default();
+    log.restore_saved_values(&mut replay_system);
+    assert_eq!(original, run_log::<A>(replay_system, log.clone(), frames));
+    assert_ne!(original, run_log::<A>(NullSystem::default(), log, frames));
+}
+
+#[test]
+fn test_replay_settings_oo() {
+    check_replay_settings::<OoApp>();
+}
+
+#[test]
+fn test_replay_settings_ecs() {
+    check_replay_settings::<EcsApp>();
+}
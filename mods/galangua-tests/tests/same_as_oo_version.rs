@@ -0,0 +1,70 @@
+use galangua_common::app::game::observation::Observation;
+use galangua_common::framework::input_log::{InputLog, InputReplayer};
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::AppTrait;
+use galangua_tests::{create_input_log, EcsApp, OoApp, SaveState};
+
+struct Divergence {
+    frame: u32,
+    field: &'static str,
+    oo: Option<Observation>,
+    ecs: Option<Observation>,
+}
+
+// Runs both versions in lockstep, returns the first frame which differs.
+fn find_divergence(log: &InputLog, frames: u32) -> Option<Divergence> {
+    let mut oo = InputReplayer::new(OoApp::create(log.seed), log.clone());
+    let mut ecs = InputReplayer::new(EcsApp::create(log.seed), log.clone());
+
+    for frame in 0..frames {
+        let oo_running = AppTrait::<NullRenderer>::update(&mut oo);
+        let ecs_running = AppTrait::<NullRenderer>::update(&mut ecs);
+        let oo_observation = oo.app().observation();
+        let ecs_observation = ecs.app().observation();
+        let field = match (&oo_observation, &ecs_observation) {
+            _ if oo_running != ecs_running => Some("running"),
+            // Counters for the results screen and the lifetime stats.
+            _ if oo.app().play_record() != ecs.app().play_record() => Some("play_record"),
+            (Some(a), Some(b)) => a.diff(b),
+            (None, None) => None,
+            _ => Some("app_state"),
+        };
+        if let Some(field) = field {
+            return Some(Divergence { frame, field, oo: oo_observation, ecs: ecs_observation });
+        }
+        if !oo_running {
+            break;
+        }
+    }
+    None
+}
+
+fn check_same_as_oo_version(frames: u32) {
+    for seed in 0..4 {
+        let log = create_input_log(seed, frames);
+        if let Some(d) = find_divergence(&log, frames) {
+            panic!("seed={}: diverged at frame {} in {}\nOO : {:?}\nECS: {:?}", seed, d.frame, d.field, d.oo, d.ecs);
+        }
+    }
+}
+
+#[test]
+fn test_same_as_oo_version_until_appearance() {
+    // Title and stage start, until the first enemies appear.
+    check_same_as_oo_version(90);
+}
+
+#[test]
+fn test_same_as_oo_version() {
+    // Several stages with captures, recaptures and lost ships.
+    check_same_as_oo_version(3 * 60 * 60);
+}
+
+#[test]
+fn test_same_as_oo_version_demo_play() {
+    // Without any input, the attract mode goes on to the demo play by the bot.
+    let log = InputLog::new(0);
+    if let Some(d) = find_divergence(&log, (16 + 60) * 60) {
+        panic!("demo play diverged at frame {} in {}\nOO : {:?}\nECS: {:?}", d.frame, d.field, d.oo, d.ecs);
+    }
+}
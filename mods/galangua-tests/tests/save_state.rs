@@ -0,0 +1,110 @@
+use galangua_common::app::game::CaptureState;
+use galangua_common::framework::input_log::{InputEvent, InputLog, InputReplayer};
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::{AppTrait, VKey};
+use galangua_tests::{create_input_log, EcsApp, OoApp, SaveState};
+
+// Plays until `save_frame` and releases all keys, then checks the loaded game goes on the same as the original.
+fn check_snapshot_round_trip<A: SaveState>(save_frame: u32, frames: u32) {
+    for seed in 0..2 {
+        let mut log = create_input_log(seed, save_frame - 10);
+        for vkey in [VKey::Left, VKey::Right, VKey::Space].iter() {
+            log.events.push((save_frame - 10, InputEvent::Key(*vkey, false)));
+        }
+        let mut original = InputReplayer::new(A::create(seed), log);
+        for _ in 0..save_frame {
+            AppTrait::<NullRenderer>::update(&mut original);
+        }
+        let snapshot = original.app().save();
+
+        let mut loaded = A::create(seed + 100);
+        loaded.load(&snapshot);
+        assert_eq!(original.app().observation(), loaded.observation(), "seed={}", seed);
+        for frame in 0..frames {
+            let running = AppTrait::<NullRenderer>::update(&mut original);
+            assert_eq!(running, loaded.update(), "seed={}, frame={}", seed, frame);
+            assert_eq!(original.app().observation(), loaded.observation(), "seed={}, frame={}", seed, frame);
+            if !running {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip_oo() {
+    check_snapshot_round_trip::<OoApp>(20 * 60, 10 * 60);
+}
+
+#[test]
+fn test_snapshot_round_trip_ecs() {
+    check_snapshot_round_trip::<EcsApp>(20 * 60, 10 * 60);
+}
+
+// Waits for the demo play without any input, and returns capture states in the order they appear.
+fn run_demo_play<A: SaveState>() -> Vec<CaptureState> {
+    let mut app = A::create(0);
+    let mut capture_states = Vec::new();
+    let mut started = false;
+    for _ in 0..(16 + 60) * 60 {
+        assert!(app.update());
+        match app.observation() {
+            Some(observation) => {
+                started = true;
+                if capture_states.last() != Some(&observation.capture_state) {
+                    capture_states.push(observation.capture_state);
+                }
+            }
+            None if started => break,
+            None => {}
+        }
+    }
+    assert!(started);
+    capture_states
+}
+
+fn check_demo_play<A: SaveState>() {
+    let capture_states = run_demo_play::<A>();
+    let captured = capture_states.iter().position(|state| *state == CaptureState::Captured);
+    let dual = capture_states.iter().position(|state| *state == CaptureState::Dual);
+    assert!(captured.is_some() && dual > captured, "{:?}", capture_states);
+}
+
+#[test]
+fn test_demo_play_oo() {
+    check_demo_play::<OoApp>();
+}
+
+#[test]
+fn test_demo_play_ecs() {
+    check_demo_play::<EcsApp>();
+}
+
+// Loses the focus while playing, then resumes from the pause menu.
+fn check_pause<A: SaveState>() {
+    let mut log = InputLog::new(0);
+    log.events.push((1, InputEvent::Key(VKey::Space, true)));
+    log.events.push((2, InputEvent::Key(VKey::Space, false)));
+    log.events.push((300, InputEvent::Focus(false)));
+    log.events.push((400, InputEvent::Key(VKey::Space, true)));
+    log.events.push((401, InputEvent::Key(VKey::Space, false)));
+    let mut app = InputReplayer::new(A::create(0), log);
+    let mut observations = Vec::new();
+    for _ in 0..460 {
+        assert!(AppTrait::<NullRenderer>::update(&mut app));
+        observations.push(app.app().observation());
+    }
+    assert!(observations[300].is_some());
+    assert!(observations[300..400].iter().all(|observation| *observation == observations[300]));
+    assert_ne!(observations[300], observations[459]);
+}
+
+#[test]
+fn test_pause_oo() {
+    check_pause::<OoApp>();
+}
+
+#[test]
+fn test_pause_ecs() {
+    check_pause::<EcsApp>();
+}
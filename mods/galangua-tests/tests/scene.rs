@@ -0,0 +1,159 @@
+use galangua_common::app::consts::*;
+use galangua_common::app::scene::Scene;
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::{AppTrait, VKey};
+use galangua_tests::{EcsAudioApp, OoAudioApp, SceneApp};
+
+fn step<A: SceneApp>(app: &mut A, audio: &RecordingAudio) {
+    assert!(AppTrait::<NullRenderer>::update(app));
+    audio.next_frame();
+}
+
+fn press<A: SceneApp>(app: &mut A, audio: &RecordingAudio, vkey: VKey) {
+    AppTrait::<NullRenderer>::on_key(app, vkey, true);
+    step(app, audio);
+    AppTrait::<NullRenderer>::on_key(app, vkey, false);
+    step(app, audio);
+}
+
+fn beam_played(audio: &RecordingAudio) -> usize {
+    audio.played().iter().filter(|(_, channel, filename)| *channel == CH_BEAM && filename == SE_TRACTOR_BEAM1).count()
+}
+
+fn all_stopped(audio: &RecordingAudio) -> usize {
+    audio.stopped().iter().filter(|(_, channel)| channel.is_none()).count()
+}
+
+fn check_pause<A: SceneApp>() {
+    let audio = RecordingAudio::default();
+    let mut app = A::with_audio(1, audio.clone());
+    press(&mut app, &audio, VKey::Space);
+    assert_eq!(Scene::Game, app.scene());
+
+    // Pauses while the tractor beam is open.
+    while beam_played(&audio) == 0 {
+        step(&mut app, &audio);
+    }
+    let stopped = all_stopped(&audio);
+    AppTrait::<NullRenderer>::on_focus(&mut app, false);
+    assert!(app.is_paused());
+    assert_eq!(stopped + 1, all_stopped(&audio));
+    let observation = app.observation();
+    for _ in 0..60 {
+        step(&mut app, &audio);
+    }
+    assert_eq!(observation, app.observation());
+
+    // Resume restarts the beam sound.
+    press(&mut app, &audio, VKey::Space);
+    assert!(!app.is_paused());
+    assert_eq!(2, beam_played(&audio));
+}
+
+fn check_restart_and_quit<A: SceneApp>() {
+    let audio = RecordingAudio::default();
+    let mut app = A::with_audio(1, audio.clone());
+    press(&mut app, &audio, VKey::Space);
+    while beam_played(&audio) == 0 {
+        step(&mut app, &audio);
+    }
+
+    // Restart.
+    AppTrait::<NullRenderer>::on_focus(&mut app, false);
+    press(&mut app, &audio, VKey::Down);
+    let stopped = all_stopped(&audio);
+    press(&mut app, &audio, VKey::Space);
+    assert!(app.scene() == Scene::Game && !app.is_paused());
+    assert_eq!(stopped + 1, all_stopped(&audio));
+    assert_eq!(Some(0), app.observation().map(|observation| observation.score));
+
+    // Quit.
+    AppTrait::<NullRenderer>::on_focus(&mut app, false);
+    press(&mut app, &audio, VKey::Up);
+    let stopped = all_stopped(&audio);
+    press(&mut app, &audio, VKey::Space);
+    assert!(app.scene() == Scene::Title && !app.is_paused());
+    assert_eq!(stopped + 1, all_stopped(&audio));
+    assert_eq!(1, beam_played(&audio));
+}
+
+fn check_quick_load_error<A: SceneApp>() {
+    let audio = RecordingAudio::default();
+    let mut app = A::with_audio(1, audio.clone());
+    press(&mut app, &audio, VKey::Space);
+    app.set_quick_save_slot(Some(String::from("{}")));
+    press(&mut app, &audio, VKey::F9);
+    assert!(app.snapshot_error().is_some_and(|err| err.starts_with("Quick load failed")));
+    assert_eq!(Scene::Game, app.scene());
+    assert_eq!(Some("{}"), app.quick_save_slot());
+
+    assert_eq!(Ok(()), app.quick_save());
+    assert_eq!(Ok(()), app.quick_load());
+}
+
+// Plays until the game is over, shooting or not, then skips the results.
+fn check_skip_results<A: SceneApp>() {
+    for &shoot in &[false, true] {
+        let audio = RecordingAudio::default();
+        let mut app = A::with_audio(1, audio.clone());
+        press(&mut app, &audio, VKey::Space);
+        let mut score = 0;
+        for frame in 0..60 * 60 * 10 {
+            if let Some(observation) = app.observation() {
+                score = observation.score;
+            }
+            if app.scene() == Scene::Results {
+                break;
+            }
+            AppTrait::<NullRenderer>::on_key(&mut app, VKey::Space, shoot && frame % 8 == 0);
+            step(&mut app, &audio);
+        }
+        assert_eq!(Scene::Results, app.scene(), "shoot={}", shoot);
+        assert_eq!(shoot, score > 0, "shoot={}, score={}", shoot, score);
+
+        press(&mut app, &audio, VKey::Escape);
+        let expected = if shoot { Scene::NameEntry } else { Scene::Title };
+        assert_eq!(expected, app.scene(), "shoot={}", shoot);
+    }
+}
+
+#[test]
+fn test_pause_oo() {
+    check_pause::<OoAudioApp>();
+}
+
+#[test]
+fn test_pause_ecs() {
+    check_pause::<EcsAudioApp>();
+}
+
+#[test]
+fn test_restart_and_quit_oo() {
+    check_restart_and_quit::<OoAudioApp>();
+}
+
+#[test]
+fn test_restart_and_quit_ecs() {
+    check_restart_and_quit::<EcsAudioApp>();
+}
+
+#[test]
+fn test_quick_load_error_oo() {
+    check_quick_load_error::<OoAudioApp>();
+}
+
+#[test]
+fn test_quick_load_error_ecs() {
+    check_quick_load_error::<EcsAudioApp>();
+}
+
+#[test]
+fn test_skip_results_oo() {
+    check_skip_results::<OoAudioApp>();
+}
+
+#[test]
+fn test_skip_results_ecs() {
+    check_skip_results::<EcsAudioApp>();
+}
@@ -0,0 +1,142 @@
+use galangua_common::app::bot::{Bot, BotEnv, HeuristicBot};
+use galangua_common::app::consts::*;
+use galangua_common::app::game::observation::Observation;
+use galangua_common::app::game::{CaptureState, EnemyType};
+use galangua_common::framework::headless::{NullSystem, NullTimer};
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::{AppTrait, VKey};
+use galangua_ecs::app::GalanguaEcsApp;
+use galangua_oo::app::GalanguaApp;
+use galangua_tests::{BotGame, EcsBotGame, OoBotGame};
+
+#[derive(Debug, PartialEq)]
+enum BeamEnd {
+    Closed,
+    Captured,
+    OwlDestroyed,
+}
+
+// Plays with the bot, and returns how each tractor beam sound ends.
+fn run_beam_sounds<E: BotEnv>(env: &mut E, audio: &RecordingAudio, mut bot: HeuristicBot) -> Vec<BeamEnd> {
+    let mut observations: Vec<Observation> = vec![env.observe()];
+    for _ in 0..3 * 60 * 60 {
+        let running = env.step(bot.decide(observations.last().unwrap()));
+        audio.next_frame();
+        observations.push(env.observe());
+        if !running {
+            break;
+        }
+    }
+
+    let starts: Vec<u32> = audio.played().into_iter()
+        .filter(|(_, channel, filename)| *channel == CH_BEAM && filename == SE_TRACTOR_BEAM1)
+        .map(|(frame, _, _)| frame)
+        .collect();
+    let stops: Vec<u32> = audio.stopped().into_iter()
+        .filter(|(_, channel)| *channel == Some(CH_BEAM))
+        .map(|(frame, _)| frame)
+        .collect();
+    assert_eq!(starts.len(), stops.len(), "starts={:?}, stops={:?}", starts, stops);
+    starts.iter().zip(stops.iter()).map(|(&start, &stop)| {
+        assert!(start < stop && starts.iter().all(|&frame| frame <= start || frame > stop),
+                "starts={:?}, stops={:?}", starts, stops);
+        // Observation after the frame is at `frame + 1`.
+        let before = &observations[stop as usize];
+        let after = &observations[stop as usize + 1];
+        let beam_pos = before.tractor_beam.unwrap();
+        let owl = before.enemies.iter()
+            .filter(|enemy| enemy.enemy_type == EnemyType::Owl)
+            .min_by_key(|enemy| (enemy.pos.x - beam_pos.x).abs() + (enemy.pos.y - beam_pos.y).abs())
+            .unwrap();
+        if after.capture_state == CaptureState::Capturing {
+            BeamEnd::Captured
+        } else if after.enemies.iter().any(|enemy| enemy.formation_index == owl.formation_index) {
+            BeamEnd::Closed
+        } else {
+            BeamEnd::OwlDestroyed
+        }
+    }).collect()
+}
+
+fn check_beam_sounds<G: BotGame>() {
+    let mut ends = Vec::new();
+    for seed in 0..3 {
+        let audio = RecordingAudio::default();
+        let mut env = G::create(seed, audio.clone());
+        ends.extend(run_beam_sounds(&mut env, &audio, HeuristicBot::default()));
+        let audio = RecordingAudio::default();
+        let mut env = G::create(seed, audio.clone());
+        ends.extend(run_beam_sounds(&mut env, &audio, HeuristicBot::for_demo()));
+    }
+    for end in [BeamEnd::Closed, BeamEnd::Captured, BeamEnd::OwlDestroyed].iter() {
+        assert!(ends.contains(end), "{:?}: {:?}", end, ends);
+    }
+}
+
+#[test]
+fn test_beam_sound_oo() {
+    check_beam_sounds::<OoBotGame>();
+}
+
+#[test]
+fn test_beam_sound_ecs() {
+    check_beam_sounds::<EcsBotGame>();
+}
+
+fn beam_played(audio: &RecordingAudio) -> usize {
+    audio.played().iter().filter(|(_, channel, filename)| *channel == CH_BEAM && filename == SE_TRACTOR_BEAM1).count()
+}
+
+fn all_stopped(audio: &RecordingAudio) -> usize {
+    audio.stopped().iter().filter(|(_, channel)| channel.is_none()).count()
+}
+
+fn press<A: AppTrait<NullRenderer>>(app: &mut A, audio: &RecordingAudio, vkey: VKey) {
+    app.on_key(vkey, true);
+    assert!(app.update());
+    audio.next_frame();
+    app.on_key(vkey, false);
+    assert!(app.update());
+    audio.next_frame();
+}
+
+fn wait_beam<A: AppTrait<NullRenderer>>(app: &mut A, audio: &RecordingAudio) {
+    let count = beam_played(audio);
+    while beam_played(audio) == count {
+        assert!(app.update());
+        audio.next_frame();
+    }
+}
+
+// Quick load and quitting the game stop the tractor beam sound.
+fn check_leave_game<A: AppTrait<NullRenderer>>(mut app: A, audio: RecordingAudio) {
+    press(&mut app, &audio, VKey::Space);
+    press(&mut app, &audio, VKey::F5);
+
+    wait_beam(&mut app, &audio);
+    let stopped = all_stopped(&audio);
+    press(&mut app, &audio, VKey::F9);
+    assert_eq!(stopped + 1, all_stopped(&audio));
+
+    wait_beam(&mut app, &audio);
+    let stopped = all_stopped(&audio);
+    press(&mut app, &audio, VKey::Escape);
+    press(&mut app, &audio, VKey::Up);
+    press(&mut app, &audio, VKey::Space);
+    // One for the pause, and one for leaving the game.
+    assert_eq!(stopped + 2, all_stopped(&audio));
+    assert!(app.update());
+}
+
+#[test]
+fn test_leave_game_oo() {
+    let audio = RecordingAudio::default();
+    check_leave_game(GalanguaApp::new(NullTimer, NullSystem::with_audio(audio.clone()), 1), audio);
+}
+
+#[test]
+fn test_leave_game_ecs() {
+    let audio = RecordingAudio::default();
+    check_leave_game(GalanguaEcsApp::new(NullTimer, NullSystem::with_audio(audio.clone()), 1), audio);
+}
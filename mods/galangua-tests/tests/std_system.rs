@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use galangua_common::app::consts::*;
+use galangua_common::framework::headless::NullTimer;
+use galangua_common::framework::input_log::{InputEvent, InputReplayer};
+use galangua_common::framework::recording_audio::RecordingAudio;
+use galangua_common::framework::recording_renderer::NullRenderer;
+use galangua_common::framework::std_system::StdSystem;
+use galangua_common::framework::{AppTrait, SystemTrait, VKey};
+use galangua_ecs::app::GalanguaEcsApp;
+use galangua_oo::app::GalanguaApp;
+use galangua_tests::create_input_log;
+
+type System = StdSystem<RecordingAudio>;
+
+fn save_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("galangua-test-{}-{}", name, std::process::id()))
+        .join("savedata.json")
+}
+
+// Plays a while and quits the game from the pause menu, then checks the high score is saved to the file.
+fn check_save_high_score<A: AppTrait<NullRenderer>>(path: &Path, create: fn(System) -> A) {
+    {
+        let mut system = StdSystem::new(RecordingAudio::default(), path.to_path_buf(), None);
+        system.set_u32(KEY_HIGH_SCORE, 0);
+    }
+
+    let audio = RecordingAudio::default();
+    let frames = 60 * 60;
+    let mut log = create_input_log(0, frames);
+    for (i, vkey) in [VKey::Escape, VKey::Up, VKey::Space].iter().enumerate() {
+        let frame = frames + 2 * i as u32;
+        log.events.push((frame, InputEvent::Key(*vkey, true)));
+        log.events.push((frame + 1, InputEvent::Key(*vkey, false)));
+    }
+    let mut app = InputReplayer::new(create(StdSystem::new(audio.clone(), path.to_path_buf(), None)), log);
+    app.init(&mut NullRenderer);
+    for _ in 0..frames + 10 {
+        assert!(AppTrait::<NullRenderer>::update(&mut app));
+        audio.next_frame();
+    }
+
+    assert!(audio.played().iter().any(|(_, _, filename)| filename == SE_MYSHOT));
+    assert!(audio.stopped().iter().any(|(frame, channel)| *frame >= frames && channel.is_none()));
+    let system = StdSystem::new(RecordingAudio::default(), path.to_path_buf(), None);
+    assert!(system.get_u32(KEY_HIGH_SCORE).unwrap() > 0);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_save_high_score_oo() {
+    check_save_high_score(&save_path("oo"), |system| GalanguaApp::new(NullTimer, system, 0));
+}
+
+#[test]
+fn test_save_high_score_ecs() {
+    check_save_high_score(&save_path("ecs"), |system| GalanguaEcsApp::new(NullTimer, system, 0));
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use galangua_common::framework::SystemTrait;
+
+const KEY_DISPLAY_CONFIG: &str = "displayConfig";
+
+// How the screen is shown in the window, for the desktop version.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    pub integer_scale: bool,  // Pixel perfect, with black borders around.
+    pub scanlines: bool,
+    pub tate: bool,  // Rotated 90 degrees, for a vertical monitor.
+}
+
+impl DisplayConfig {
+    pub fn load<S: SystemTrait>(system: &S) -> Self {
+        system.get_json::<DisplayConfig>(KEY_DISPLAY_CONFIG)
+            .unwrap_or_default()
+    }
+
+    pub fn save<S: SystemTrait>(&self, system: &mut S) -> serde_json::Result<()> {
+        system.set_json(KEY_DISPLAY_CONFIG, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galangua_common::framework::headless::NullSystem;
+
+    #[test]
+    fn test_save_load() {
+        let mut system = NullSystem::default();
+        assert_eq!(DisplayConfig::default(), DisplayConfig::load(&system));
+
+        let config = DisplayConfig { integer_scale: true, scanlines: false, tate: true };
+        config.save(&mut system).unwrap();
+        assert_eq!(config, DisplayConfig::load(&system));
+
+        system.set_value(KEY_DISPLAY_CONFIG, serde_json::json!({"scanlines": true}));
+        assert_eq!(DisplayConfig { scanlines: true, ..DisplayConfig::default() }, DisplayConfig::load(&system));
+    }
+}
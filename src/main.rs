@@ -1,29 +1,126 @@
+mod display_config;
 mod sdl;
-mod std_system;
 mod std_timer;
 
 use counted_array::counted_array;
 use lazy_static::lazy_static;
 use sdl2::keyboard::Keycode;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use galangua_common::app::consts::*;
-use galangua_common::framework::{AppTrait, VKey};
+use galangua_common::app::game::stage_definition::StageDefinition;
+use galangua_common::framework::controller_input::DEFAULT_AXIS_DEADZONE;
+use galangua_common::framework::headless::NullSystem;
+use galangua_common::framework::input_log::{InputLog, InputRecorder, InputReplayer};
+use galangua_common::framework::recording_audio::NullAudio;
+use galangua_common::framework::std_system::StdSystem;
+use galangua_common::framework::{AppTrait, SystemTrait, VKey};
+use galangua_common::util::fps_calc::TimerTrait;
 use galangua_ecs::app::GalanguaEcsApp;
 use galangua_oo::app::GalanguaApp;
 
+use crate::display_config::DisplayConfig;
 use crate::sdl::SdlAppFramework;
 use crate::sdl::SdlAudio;
 use crate::sdl::SdlRenderer;
-use crate::std_system::StdSystem;
 use crate::std_timer::StdTimer;
 
 const APP_NAME: &str = "Galangua";
+const APP_DIR_NAME: &str = "galangua";
+const SAVE_FILE_NAME: &str = "savedata.json";
+const LEGACY_SAVE_FILE_NAME: &str = ".savedata.json";  // In the current directory.
 
-fn run_app<App: AppTrait<SdlRenderer>>(app: App, scale: u32, fullscreen: bool) -> Result<(), String> {
+enum InputMode {
+    Live,
+    Record(String, InputLog),  // Settings are in the log.
+    Replay(InputLog),
+    Snapshot(String),
+}
+
+struct FrameworkConfig {
+    scale: u32,
+    fullscreen: bool,
+    axis_deadzone: i16,
+    display: DisplayConfig,
+}
+
+// Save state, loaded at start and the last quick save is written at quit.
+trait SnapshotApp {
+    fn load_snapshot(&mut self, text: &str) -> Result<(), String>;
+    fn quick_save_slot(&self) -> Option<&str>;
+    fn snapshot_error(&self) -> Option<&str>;
+}
+
+impl<T: TimerTrait, S: SystemTrait> SnapshotApp for GalanguaApp<T, S> {
+    fn load_snapshot(&mut self, text: &str) -> Result<(), String> { self.load_snapshot(text) }
+    fn quick_save_slot(&self) -> Option<&str> { self.quick_save_slot() }
+    fn snapshot_error(&self) -> Option<&str> { self.snapshot_error() }
+}
+
+impl<T: TimerTrait, S: SystemTrait> SnapshotApp for GalanguaEcsApp<T, S> {
+    fn load_snapshot(&mut self, text: &str) -> Result<(), String> { self.load_snapshot(text) }
+    fn quick_save_slot(&self) -> Option<&str> { self.quick_save_slot() }
+    fn snapshot_error(&self) -> Option<&str> { self.snapshot_error() }
+}
+
+// Failure of quick save or load while running is reported at last.
+fn run_app<App: AppTrait<SdlRenderer> + SnapshotApp>(mut app: App, config: &FrameworkConfig, input_mode: InputMode) -> Result<(), String> {
+    let snapshot_error = match input_mode {
+        InputMode::Live => {
+            run_framework(app, config)?.snapshot_error().map(String::from)
+        }
+        InputMode::Record(filename, log) => {
+            let recorder = run_framework(InputRecorder::new(app, log), config)?;
+            std::fs::write(&filename, recorder.log().to_json_string())
+                .map_err(|e| format!("{}: {}", filename, e))?;
+            recorder.app().snapshot_error().map(String::from)
+        }
+        InputMode::Replay(log) => {
+            run_framework(InputReplayer::new(app, log), config)?.app().snapshot_error().map(String::from)
+        }
+        InputMode::Snapshot(filename) => {
+            // The file is created at the first time.
+            match std::fs::read_to_string(&filename) {
+                Ok(text) => app.load_snapshot(&text).map_err(|e| format!("{}: {}", filename, e))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("{}: {}", filename, e)),
+            }
+            let app = run_framework(app, config)?;
+            if let Some(snapshot) = app.quick_save_slot() {
+                std::fs::write(&filename, snapshot)
+                    .map_err(|e| format!("{}: {}", filename, e))?;
+            }
+            app.snapshot_error().map(String::from)
+        }
+    };
+    snapshot_error.map_or(Ok(()), Err)
+}
+
+fn start<S: SystemTrait>(system: S, oo: bool, stage_definition: Option<StageDefinition>, config: &FrameworkConfig, seed: u64, input_mode: InputMode) -> Result<(), String> {
+    let timer = StdTimer::new();
+    if oo {
+        let mut app = GalanguaApp::new(timer, system, seed);
+        if let Some(stage_definition) = stage_definition {
+            app.set_stage_definition(stage_definition);
+        }
+        run_app(app, config, input_mode)
+    } else {
+        let mut app = GalanguaEcsApp::new(timer, system, seed);
+        if let Some(stage_definition) = stage_definition {
+            app.set_stage_definition(stage_definition);
+        }
+        run_app(app, config, input_mode)
+    }
+}
+
+fn run_framework<App: AppTrait<SdlRenderer>>(app: App, config: &FrameworkConfig) -> Result<App, String> {
     let mut framework = SdlAppFramework::new(app, map_key)?;
+    framework.set_axis_deadzone(config.axis_deadzone);
     framework.run(APP_NAME,
-                  WIDTH as u32, HEIGHT as u32, scale, fullscreen)
+                  WIDTH as u32, HEIGHT as u32, config.scale, config.fullscreen, config.display)?;
+    Ok(framework.into_app())
 }
 
 pub fn main() -> Result<(), String> {
@@ -32,6 +129,8 @@ pub fn main() -> Result<(), String> {
         .about("2D shoot'em up game, writen in Rust.
   Move the fighter : Arrow keys (left or right)
   Shoot a bullet   : Space bar
+  Quick save/load  : F5 / F9
+  Options          : O key on the title screen
   Quit the app     : Escape key")
         .arg(clap::Arg::with_name("full")
              .help("Use fullscreen")
@@ -45,6 +144,54 @@ pub fn main() -> Result<(), String> {
          .arg(clap::Arg::with_name("oo")
              .help("Run OO version")
              .long("oo"))
+        .arg(clap::Arg::with_name("seed")
+             .help("Specify random seed (default: current time)")
+             .long("seed")
+             .takes_value(true))
+        .arg(clap::Arg::with_name("record")
+             .help("Record input to file, with the settings of the play")
+             .long("record")
+             .takes_value(true)
+             .value_name("FILE"))
+        .arg(clap::Arg::with_name("replay")
+             .help("Replay input from file, on the recorded settings without saving anything")
+             .long("replay")
+             .takes_value(true)
+             .value_name("FILE")
+             .conflicts_with_all(&["record", "seed", "oo", "stage"]))
+        .arg(clap::Arg::with_name("snapshot")
+             .help("Load save state from file, and write quick save (F5) to it at quit")
+             .long("snapshot")
+             .takes_value(true)
+             .value_name("FILE")
+             .conflicts_with_all(&["record", "replay"]))
+        .arg(clap::Arg::with_name("deadzone")
+             .help("Specify analog stick deadzone, 0-32767 (default: 10000)")
+             .long("deadzone")
+             .takes_value(true))
+        .arg(clap::Arg::with_name("integer-scale")
+             .help("Scale by an integer, with black borders (saved)")
+             .long("integer-scale")
+             .takes_value(true)
+             .possible_values(&["on", "off"]))
+        .arg(clap::Arg::with_name("scanlines")
+             .help("Draw scanlines like a CRT monitor (saved)")
+             .long("scanlines")
+             .takes_value(true)
+             .possible_values(&["on", "off"]))
+        .arg(clap::Arg::with_name("tate")
+             .help("Rotate the screen for a vertical monitor (saved)")
+             .long("tate")
+             .takes_value(true)
+             .possible_values(&["on", "off"]))
+        .arg(clap::Arg::with_name("synth")
+             .help("Synthesize sound effects instead of loading the audio files")
+             .long("synth"))
+        .arg(clap::Arg::with_name("stage")
+             .help("Load stage definition from file")
+             .long("stage")
+             .takes_value(true)
+             .value_name("FILE"))
         .get_matches();
 
     let fullscreen = matches.is_present("full");
@@ -53,17 +200,79 @@ pub fn main() -> Result<(), String> {
     } else {
         3
     };
-
-    let audio = SdlAudio::new(CHANNEL_COUNT, BASE_VOLUME);
-    let timer = StdTimer::new();
-    let system = StdSystem::new(audio);
-    if matches.is_present("oo") {
-        let app = GalanguaApp::new(timer, system);
-        run_app(app, scale, fullscreen)
+    let axis_deadzone = if let Some(deadzone) = matches.value_of("deadzone") {
+        String::from(deadzone).parse().ok().filter(|&d: &i16| d >= 0)
+            .ok_or_else(|| format!("Illegal deadzone: {}", deadzone))?
+    } else {
+        DEFAULT_AXIS_DEADZONE
+    };
+    let replay_log = if let Some(filename) = matches.value_of("replay") {
+        let text = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+        Some(InputLog::from_json_str(&text).map_err(|e| format!("{}: {}", filename, e))?)
+    } else {
+        None
+    };
+    let seed = if let Some(log) = &replay_log {
+        log.seed
+    } else if let Some(seed) = matches.value_of("seed") {
+        String::from(seed).parse().map_err(|_| format!("Illegal seed: {}", seed))?
+    } else {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+    };
+    // Replay is played on the recorded implementation and stage.
+    let oo = replay_log.as_ref().map_or(matches.is_present("oo"), |log| log.oo);
+    let (stage_filename, stage) = if let Some(log) = &replay_log {
+        ("replay stage", log.stage.clone())
+    } else if let Some(filename) = matches.value_of("stage") {
+        let text = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+        (filename, Some(text))
     } else {
-        let app = GalanguaEcsApp::new(timer, system);
-        run_app(app, scale, fullscreen)
+        ("", None)
+    };
+    let stage_definition = match &stage {
+        Some(text) => Some(StageDefinition::from_json_str(text).map_err(|e| format!("{}: {}", stage_filename, e))?),
+        None => None,
+    };
+
+    let mut audio = SdlAudio::new(CHANNEL_COUNT, BASE_VOLUME);
+    audio.set_synth(matches.is_present("synth"));
+
+    // Audio is given after the replay is found to be played on another system.
+    let mut save_system = StdSystem::new(NullAudio, save_file_path(), Some(Path::new(LEGACY_SAVE_FILE_NAME)));
+
+    // Display options given are saved, and used from the next time.
+    let saved_display = DisplayConfig::load(&save_system);
+    let switch = |name: &str, saved: bool| matches.value_of(name).map_or(saved, |value| value == "on");
+    let display = DisplayConfig {
+        integer_scale: switch("integer-scale", saved_display.integer_scale),
+        scanlines: switch("scanlines", saved_display.scanlines),
+        tate: switch("tate", saved_display.tate),
+    };
+    if display != saved_display {
+        display.save(&mut save_system).map_err(|e| e.to_string())?;
     }
+    let config = FrameworkConfig { scale, fullscreen, axis_deadzone, display };
+
+    if let Some(log) = replay_log {
+        // Settings come from the log, and nothing is saved while watching the replay.
+        let mut system = NullSystem::with_audio(audio);
+        log.restore_saved_values(&mut system);
+        return start(system, oo, stage_definition, &config, seed, InputMode::Replay(log));
+    }
+
+    let input_mode = if let Some(filename) = matches.value_of("record") {
+        let mut log = InputLog::new(seed);
+        log.oo = oo;
+        log.stage = stage;
+        log.take_saved_values(&save_system, &REPLAYED_KEYS);
+        InputMode::Record(String::from(filename), log)
+    } else if let Some(filename) = matches.value_of("snapshot") {
+        InputMode::Snapshot(String::from(filename))
+    } else {
+        InputMode::Live
+    };
+    let system = save_system.replace_audio(audio);
+    start(system, oo, stage_definition, &config, seed, input_mode)
 }
 
 counted_array!(const KEY_MAP_TABLE: [(Keycode, VKey); _] = [
@@ -87,6 +296,10 @@ counted_array!(const KEY_MAP_TABLE: [(Keycode, VKey); _] = [
     (Keycode::Num3, VKey::Num3), (Keycode::Num4, VKey::Num4), (Keycode::Num5, VKey::Num5),
     (Keycode::Num6, VKey::Num6), (Keycode::Num7, VKey::Num7), (Keycode::Num8, VKey::Num8),
     (Keycode::Num9, VKey::Num9),
+
+    (Keycode::F1, VKey::F1), (Keycode::F2, VKey::F2), (Keycode::F3, VKey::F3), (Keycode::F4, VKey::F4),
+    (Keycode::F5, VKey::F5), (Keycode::F6, VKey::F6), (Keycode::F7, VKey::F7), (Keycode::F8, VKey::F8),
+    (Keycode::F9, VKey::F9), (Keycode::F10, VKey::F10), (Keycode::F11, VKey::F11), (Keycode::F12, VKey::F12),
 ]);
 
 lazy_static! {
@@ -99,6 +312,14 @@ lazy_static! {
     };
 }
 
+// `$XDG_DATA_HOME/galangua/savedata.json` or the platform equivalent.
+fn save_file_path() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join(APP_DIR_NAME).join(SAVE_FILE_NAME),
+        None => PathBuf::from(LEGACY_SAVE_FILE_NAME),
+    }
+}
+
 fn map_key(keycode: Keycode) -> Option<VKey> {
     KEY_MAP.get(&keycode).map(|x| *x)
 }
@@ -1,14 +1,18 @@
-use sdl2::event::Event;
+use sdl2::controller::{Button, GameController};
+use sdl2::event::{Event, WindowEvent};
 use sdl2::image::InitFlag;
-use sdl2::joystick::Joystick;
 use sdl2::keyboard::Keycode;
 use sdl2::mixer::{AUDIO_S16LSB, DEFAULT_CHANNELS};
-use sdl2::Sdl;
+use sdl2::{GameControllerSubsystem, Sdl};
+use std::collections::HashMap;
 use std::thread;
 use std::time::{Duration, SystemTime};
 
+use galangua_common::framework::controller_input::ControllerInput;
+use galangua_common::framework::input_log::{dispatch_event, InputEvent};
 use galangua_common::framework::{AppTrait, VKey};
 
+use crate::display_config::DisplayConfig;
 use crate::sdl::sdl_renderer::SdlRenderer;
 
 type MapKeyFunc = fn(Keycode) -> Option<VKey>;
@@ -16,6 +20,14 @@ type MapKeyFunc = fn(Keycode) -> Option<VKey>;
 const FPS: u32 = 60;
 const MIN_FPS: u32 = 15;
 
+// D-pad is reported as the same axes as the left stick.
+const DPAD_BUTTONS: [(Button, u8, bool); 4] = [
+    (Button::DPadLeft, 0, false),
+    (Button::DPadRight, 0, true),
+    (Button::DPadUp, 1, false),
+    (Button::DPadDown, 1, true),
+];
+
 pub struct SdlAppFramework<App: AppTrait<SdlRenderer>> {
     sdl_context: Sdl,
     last_update_time: SystemTime,
@@ -23,6 +35,10 @@ pub struct SdlAppFramework<App: AppTrait<SdlRenderer>> {
     app: App,
     map_key: MapKeyFunc,
 
+    controller_subsystem: Option<GameControllerSubsystem>,
+    controllers: HashMap<u32, GameController>,  // Keyed by instance id.
+    controller_input: ControllerInput,
+
     #[cfg(debug_assertions)]
     fast_forward: bool,
 }
@@ -37,19 +53,34 @@ impl<App: AppTrait<SdlRenderer>> SdlAppFramework<App> {
             app,
             map_key,
 
+            controller_subsystem: None,
+            controllers: HashMap::new(),
+            controller_input: ControllerInput::default(),
+
             #[cfg(debug_assertions)]
             fast_forward: false,
         })
     }
 
-    pub fn run(&mut self, title: &str, width: u32, height: u32, scale: u32, fullscreen: bool) -> Result<(), String> {
+    pub fn into_app(self) -> App {
+        self.app
+    }
+
+    // Analog axis values within the deadzone are taken as neutral.
+    pub fn set_axis_deadzone(&mut self, deadzone: i16) {
+        self.controller_input.set_deadzone(deadzone);
+    }
+
+    pub fn run(&mut self, title: &str, width: u32, height: u32, scale: u32, fullscreen: bool, display: DisplayConfig) -> Result<(), String> {
         let video_subsystem = self.sdl_context.video()?;
         let _image_context = sdl2::image::init(InitFlag::PNG | InitFlag::JPG)?;
 
-        let _joystick = self.set_up_joystick()?;
+        // Connected controllers are notified with `ControllerDeviceAdded` at start, too.
+        self.controller_subsystem = Some(self.sdl_context.game_controller()?);
 
+        let (window_width, window_height) = if display.tate { (height, width) } else { (width, height) };
         let mut window_builder = video_subsystem
-            .window(title, width * scale, height * scale);
+            .window(title, window_width * scale, window_height * scale);
         if fullscreen {
             window_builder.fullscreen();
         } else {
@@ -82,11 +113,7 @@ impl<App: AppTrait<SdlRenderer>> SdlAppFramework<App> {
             sdl2::mixer::InitFlag::MP3 | sdl2::mixer::InitFlag::FLAC | sdl2::mixer::InitFlag::MOD | sdl2::mixer::InitFlag::OGG
         )?;
 
-        // Number of mixing channels available for sound effect `Chunk`s to play
-        // simultaneously.
-        sdl2::mixer::allocate_channels(4);
-
-        let mut renderer = SdlRenderer::new(canvas, (width, height));
+        let mut renderer = SdlRenderer::new(canvas, (width, height), display);
 
         self.app.init(&mut renderer);
 
@@ -140,15 +167,27 @@ impl<App: AppTrait<SdlRenderer>> SdlAppFramework<App> {
                         self.app.on_key(vkey, false);
                     }
                 }
-                Event::JoyAxisMotion { axis_idx, value: val, .. } => {
-                    let dir = if val > 10_000 { 1 } else if val < -10_000 { -1 } else { 0 };
-                    self.app.on_joystick_axis(axis_idx, dir);
+                Event::Window { win_event: WindowEvent::FocusGained, .. } => {
+                    self.app.on_focus(true);
+                }
+                Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                    self.app.on_focus(false);
+                }
+                Event::ControllerDeviceAdded { which, .. } => {
+                    self.open_controller(which);
+                }
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    self.close_controller(which);
+                }
+                Event::ControllerAxisMotion { which, axis, value, .. } => {
+                    let events = self.controller_input.on_axis(which, axis as u8, value);
+                    self.dispatch_events(&events);
                 }
-                Event::JoyButtonDown { button_idx, .. } => {
-                    self.app.on_joystick_button(button_idx, true);
+                Event::ControllerButtonDown { which, button, .. } => {
+                    self.on_controller_button(which, button, true);
                 }
-                Event::JoyButtonUp { button_idx, .. } => {
-                    self.app.on_joystick_button(button_idx, false);
+                Event::ControllerButtonUp { which, button, .. } => {
+                    self.on_controller_button(which, button, false);
                 }
                 _ => {}
             }
@@ -177,15 +216,38 @@ impl<App: AppTrait<SdlRenderer>> SdlAppFramework<App> {
         }
     }
 
-    fn set_up_joystick(&mut self) -> Result<Option<Joystick>, String> {
-        let joystick_subsystem = self.sdl_context.joystick()?;
-        let available = joystick_subsystem
-            .num_joysticks()
-            .map_err(|e| format!("can't enumerate joysticks: {}", e))?;
-        let joystick = (0..available).find_map(|id| match joystick_subsystem.open(id) {
-            Ok(c) => Some(c),
-            Err(_e) => None,
-        });
-        Ok(joystick)
+    fn open_controller(&mut self, joystick_index: u32) {
+        let controller_subsystem = self.controller_subsystem.as_ref().unwrap();
+        match controller_subsystem.open(joystick_index) {
+            Ok(controller) => {
+                self.controller_input.add_controller(controller.instance_id());
+                self.controllers.insert(controller.instance_id(), controller);
+            }
+            Err(err) => {
+                eprintln!("Failed to open controller {}: {}", joystick_index, err);
+            }
+        }
+    }
+
+    fn close_controller(&mut self, instance_id: u32) {
+        if self.controllers.remove(&instance_id).is_some() {
+            let events = self.controller_input.remove_controller(instance_id);
+            self.dispatch_events(&events);
+        }
+    }
+
+    fn on_controller_button(&mut self, instance_id: u32, button: Button, down: bool) {
+        let events = if let Some(&(_, axis, positive)) = DPAD_BUTTONS.iter().find(|(b, _, _)| *b == button) {
+            self.controller_input.on_dpad(instance_id, axis, positive, down)
+        } else {
+            self.controller_input.on_button(instance_id, button as u8, down)
+        };
+        self.dispatch_events(&events);
+    }
+
+    fn dispatch_events(&mut self, events: &[InputEvent]) {
+        for event in events {
+            dispatch_event(&mut self.app, event);
+        }
     }
 }
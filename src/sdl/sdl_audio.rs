@@ -1,30 +1,110 @@
-use sdl2::mixer::{Chunk, MAX_VOLUME};
+use sdl2::mixer::{Channel, Chunk, MAX_VOLUME};
+use std::collections::HashMap;
+
+use galangua_common::app::sound_patch::find_patch;
+use galangua_common::framework::AudioTrait;
+use galangua_common::util::synth::render;
 
 pub struct SdlAudio {
-    channels: Vec<Option<Chunk>>,
-    base_volume: i32,
+    bank: HashMap<String, Chunk>,  // Keyed by the filename without the extension.
+    channel_count: u32,
+    base_volume: f32,
+    synth: bool,
 }
 
 impl SdlAudio {
     pub fn new(channel_count: u32, base_volume: f32) -> Self {
-        let mut channels = Vec::with_capacity(channel_count as usize);
-        channels.resize_with(channel_count as usize, || None);
-
         Self {
-            channels,
-            base_volume: (MAX_VOLUME as f32 * base_volume) as i32,
+            bank: HashMap::new(),
+            channel_count,
+            base_volume,
+            synth: false,
+        }
+    }
+
+    // Synthesizes all sounds instead of loading the files.
+    pub fn set_synth(&mut self, synth: bool) {
+        self.synth = synth;
+    }
+
+    fn play(&mut self, channel: u32, filename: &str, loops: i32) {
+        if channel < self.channel_count {
+            if let Some(chunk) = self.chunk(filename) {
+                if let Err(err) = Channel(channel as i32).play(chunk, loops) {
+                    eprintln!("{}: {}", filename, err);
+                }
+            }
         }
     }
 
-    pub fn play_se(&mut self, channel: u32, filename: &str) {
-        if channel < self.channels.len() as u32 {
-            let path = format!("{}.ogg", filename);
-            let mut chunk = Chunk::from_file(path)
-                .expect("play_se: No music file");
-            chunk.set_volume(self.base_volume);
-            sdl2::mixer::Channel::all().play(&chunk, 0)
-                .expect("Play music failed");
-            self.channels[channel as usize] = Some(chunk);
+    // Loads the file if it is not in the bank, or synthesizes it when the file is missing.
+    fn chunk(&mut self, filename: &str) -> Option<&Chunk> {
+        if !self.bank.contains_key(filename) {
+            let loaded = if self.synth {
+                None
+            } else {
+                let path = format!("{}.ogg", filename);
+                Chunk::from_file(&path)
+                    .map_err(|err| eprintln!("{}: {}", path, err))
+                    .ok()
+            };
+            match loaded.or_else(|| synthesize(filename)) {
+                Some(chunk) => { self.bank.insert(String::from(filename), chunk); }
+                None => return None,
+            }
         }
+        self.bank.get(filename)
+    }
+}
+
+impl AudioTrait for SdlAudio {
+    // Needs the mixer opened.
+    fn load_sounds(&mut self, filenames: &[&str]) {
+        sdl2::mixer::allocate_channels(self.channel_count as i32);
+        for filename in filenames {
+            self.chunk(filename);
+        }
+    }
+
+    fn play_se(&mut self, channel: u32, filename: &str) {
+        self.play(channel, filename, 0);
     }
+
+    fn play_loop(&mut self, channel: u32, filename: &str) {
+        self.play(channel, filename, -1);
+    }
+
+    fn stop_se(&mut self, channel: u32, fade_ms: u32) {
+        if channel < self.channel_count {
+            if fade_ms > 0 {
+                Channel(channel as i32).fade_out(fade_ms as i32);
+            } else {
+                Channel(channel as i32).halt();
+            }
+        }
+    }
+
+    fn stop_all_se(&mut self) {
+        Channel::all().halt();
+    }
+
+    fn set_volume(&mut self, channel: u32, volume: f32) {
+        if channel < self.channel_count {
+            Channel(channel as i32).set_volume((MAX_VOLUME as f32 * self.base_volume * volume) as i32);
+        }
+    }
+}
+
+// Renders the patch in the format of the opened mixer (16 bit).
+fn synthesize(filename: &str) -> Option<Chunk> {
+    let patch = find_patch(filename)?;
+    let (frequency, _format, channels) = sdl2::mixer::query_spec()
+        .map_err(|err| eprintln!("{}: {}", filename, err))
+        .ok()?;
+    let pcm: Vec<i16> = render(patch, frequency as u32).iter()
+        .flat_map(|&sample| std::iter::repeat(sample).take(channels as usize))
+        .collect();
+    Chunk::from_raw_buffer(pcm.into_boxed_slice())
+        .map_err(|err| eprintln!("{}: {}", filename, err))
+        .ok()
 }
@@ -1,36 +1,143 @@
-use sdl2::pixels::Color;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::{Point, Rect};
-use sdl2::render::WindowCanvas;
+use sdl2::render::{Texture, WindowCanvas};
 use std::collections::HashMap;
 
+use galangua_common::framework::soft_renderer::darken_scanlines;
 use galangua_common::framework::sprite_sheet::SpriteSheet;
 use galangua_common::framework::types::Vec2I;
 use galangua_common::framework::RendererTrait;
 
 use super::sdl_texture_manager::SdlTextureManager;
+use crate::display_config::DisplayConfig;
+
+const SCANLINE_ALPHA: u8 = 96;
 
 pub struct SdlRenderer {
     canvas: WindowCanvas,
     texture_manager: SdlTextureManager,
     sprite_sheet: SpriteSheet,
     tex_color_map: HashMap<String, (u8, u8, u8)>,
+    // Drawn in the logical size, then scaled into the window at present.
+    // None if render targets are not supported, then drawn into the window directly.
+    screen: Option<Texture>,
+    scanline_frame: Option<Texture>,  // Streaming texture in the scaled size, for scanlines.
+    logical_size: (u32, u32),
+    display: DisplayConfig,
 }
 
 impl SdlRenderer {
-    pub fn new(mut canvas: WindowCanvas, logical_size: (u32, u32)) -> Self {
-        canvas.set_logical_size(logical_size.0, logical_size.1)
-            .expect("set_logical_size failed");
+    pub fn new(mut canvas: WindowCanvas, logical_size: (u32, u32), mut display: DisplayConfig) -> Self {
+        let screen = if canvas.render_target_supported() {
+            canvas.texture_creator()
+                .create_texture_target(None, logical_size.0, logical_size.1)
+                .map_err(|e| eprintln!("create_texture_target failed: {}", e))
+                .ok()
+        } else {
+            None
+        };
+        if screen.is_none() {
+            if display.tate || display.scanlines {
+                eprintln!("Render target is not supported, tate and scanlines are disabled");
+            }
+            display.tate = false;
+            display.scanlines = false;
+            canvas.set_logical_size(logical_size.0, logical_size.1)
+                .expect("set_logical_size failed");
+            // Not wrapped in sdl2 0.34.
+            let enable = if display.integer_scale { sdl2::sys::SDL_bool::SDL_TRUE } else { sdl2::sys::SDL_bool::SDL_FALSE };
+            if unsafe { sdl2::sys::SDL_RenderSetIntegerScale(canvas.raw(), enable) } != 0 {
+                panic!("SDL_RenderSetIntegerScale failed: {}", sdl2::get_error());
+            }
+        }
 
-        Self {
+        let mut renderer = Self {
             canvas,
             texture_manager: SdlTextureManager::new(),
             sprite_sheet: SpriteSheet::default(),
             tex_color_map: HashMap::new(),
-        }
+            screen,
+            scanline_frame: None,
+            logical_size,
+            display,
+        };
+        renderer.set_screen_target(true);
+        renderer
     }
 
     pub fn present(&mut self) {
+        if self.screen.is_none() {
+            self.canvas.present();
+            return;
+        }
+
+        self.set_screen_target(false);
+        let draw_color = self.canvas.draw_color();
+        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.canvas.clear();
+
+        let screen = self.screen.as_ref().unwrap();
+        let (w, h) = self.logical_size;
+        let shown_size = if self.display.tate { (h, w) } else { (w, h) };
+        let output_size = self.canvas.output_size().expect("output_size failed");
+        let dst = fit_rect(output_size, shown_size, self.display.integer_scale);
+        if self.display.tate {
+            // Rotated around the center, so the unrotated rectangle has the same center.
+            let rotated = Rect::from_center(dst.center(), dst.height(), dst.width());
+            self.canvas.copy_ex(screen, None, Some(rotated), 90.0, None, false, false)
+                .expect("copy_ex failed");
+        } else {
+            self.canvas.copy(screen, None, Some(dst))
+                .expect("copy failed");
+        }
+        if self.display.scanlines {
+            self.draw_scanlines(&dst, shown_size.1);
+        }
+
         self.canvas.present();
+        self.canvas.set_draw_color(draw_color);
+        self.set_screen_target(true);
+    }
+
+    // Reads back the scaled screen, and darkens rows on CPU.
+    fn draw_scanlines(&mut self, dst: &Rect, rows: u32) {
+        let (w, h) = (dst.width(), dst.height());
+        let format = PixelFormatEnum::RGBA32;
+        let mut pixels = self.canvas.read_pixels(Some(*dst), format)
+            .expect("read_pixels failed");
+        darken_scanlines(&mut pixels, w, h, rows, SCANLINE_ALPHA);
+
+        let reuse = self.scanline_frame.as_ref().map_or(false, |texture| {
+            let query = texture.query();
+            query.width == w && query.height == h
+        });
+        if !reuse {
+            if let Some(texture) = self.scanline_frame.take() {
+                unsafe { texture.destroy(); }
+            }
+            self.scanline_frame = Some(self.canvas.texture_creator()
+                .create_texture_streaming(format, w, h)
+                .expect("create_texture_streaming failed"));
+        }
+        let texture = self.scanline_frame.as_mut().unwrap();
+        texture.update(None, &pixels, (w * 4) as usize)
+            .expect("update failed");
+        self.canvas.copy(texture, None, Some(*dst))
+            .expect("copy failed");
+    }
+
+    // Draws into the screen texture, or into the window.
+    fn set_screen_target(&mut self, screen: bool) {
+        // `Canvas::with_texture_canvas` takes a closure, which can't wrap the drawing through `RendererTrait`.
+        let target = match &self.screen {
+            Some(texture) if screen => texture.raw(),
+            Some(_) => std::ptr::null_mut(),
+            None => return,
+        };
+        let result = unsafe { sdl2::sys::SDL_SetRenderTarget(self.canvas.raw(), target) };
+        if result != 0 {
+            panic!("SDL_SetRenderTarget failed: {}", sdl2::get_error());
+        }
     }
 }
 
@@ -148,3 +255,47 @@ impl RendererTrait for SdlRenderer {
         }
     }
 }
+
+// Largest rectangle in the middle of the window keeping the aspect ratio,
+// scaled by an integer unless the window is smaller than the size.
+fn fit_rect(output_size: (u32, u32), size: (u32, u32), integer_scale: bool) -> Rect {
+    let scale = f32::min(output_size.0 as f32 / size.0 as f32, output_size.1 as f32 / size.1 as f32);
+    let scale = if integer_scale && scale >= 1.0 { scale.floor() } else { scale };
+    let w = (size.0 as f32 * scale) as u32;
+    let h = (size.1 as f32 * scale) as u32;
+    Rect::new((output_size.0 as i32 - w as i32) / 2, (output_size.1 as i32 - h as i32) / 2, w, h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: (u32, u32) = (224, 288);
+
+    #[test]
+    fn test_fit_rect_integer_window() {
+        assert_eq!(Rect::new(0, 0, 448, 576), fit_rect((448, 576), SIZE, true));
+        assert_eq!(Rect::new(0, 0, 448, 576), fit_rect((448, 576), SIZE, false));
+    }
+
+    #[test]
+    fn test_fit_rect_non_integer_window() {
+        assert_eq!(Rect::new(176, 12, 448, 576), fit_rect((800, 600), SIZE, true));
+        assert_eq!(Rect::new(167, 0, 466, 600), fit_rect((800, 600), SIZE, false));
+        assert_eq!(Rect::new(14, 18, 672, 864), fit_rect((700, 900), SIZE, true));
+    }
+
+    #[test]
+    fn test_fit_rect_tate() {
+        let size = (SIZE.1, SIZE.0);
+        assert_eq!(Rect::new(384, 92, 1152, 896), fit_rect((1920, 1080), size, true));
+        assert_eq!(Rect::new(266, 0, 1388, 1080), fit_rect((1920, 1080), size, false));
+    }
+
+    #[test]
+    fn test_fit_rect_smaller_window() {
+        // Shrinks without the integer scale, not to be cut off.
+        assert_eq!(Rect::new(0, 28, 112, 144), fit_rect((112, 200), SIZE, true));
+        assert_eq!(Rect::new(0, 28, 112, 144), fit_rect((112, 200), SIZE, false));
+    }
+}